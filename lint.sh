@@ -2,7 +2,7 @@
 set -e
 
 echo "Running clippy..."
-cargo clippy --all-targets --workspace
+cargo clippy --all-targets --workspace --features arrow
 
 echo "Checking formatting..."
 cargo fmt --all --check
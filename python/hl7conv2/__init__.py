@@ -1 +1,15 @@
-from .hl7conv2 import Hl7Json, JsonHl7
+from .hl7conv2 import (
+    Deidentifier,
+    Hl7Json,
+    Hl7MutableMessage,
+    Hl7Transform,
+    JsonHl7,
+    MessageBuilder,
+    NteBuilder,
+    ObrBuilder,
+    ObxBuilder,
+    PidBuilder,
+    Pv1Builder,
+    SegmentBuilder,
+    hl7_to_ndjson,
+)
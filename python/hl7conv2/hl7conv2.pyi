@@ -1,17 +1,33 @@
-from typing import List, Dict, Union, Optional
+from typing import Any, IO, Iterator, List, Dict, Tuple, Union, Optional
+from os import PathLike
 
 class Hl7Json:
     """A Python class for converting HL7 messages to JSON format with validation."""
     
-    def __init__(self, hl7_string: str, validation_enabled: Optional[bool] = None, strict_validation: Optional[bool] = None, escaping_enabled: Optional[bool] = None) -> None:
+    def __init__(self, hl7_string: str, validation_enabled: Optional[bool] = None, strict_validation: Optional[bool] = None, escaping_enabled: Optional[bool] = None, standard_msh_numbering: Optional[bool] = None) -> None:
         """Creates a new Hl7Json instance from an HL7 message string."""
         ...
     
     @classmethod
-    def from_file(cls, path: str, validation_enabled: Optional[bool] = None, strict_validation: Optional[bool] = None, escaping_enabled: Optional[bool] = None) -> "Hl7Json":
+    def from_file(cls, path: str, validation_enabled: Optional[bool] = None, strict_validation: Optional[bool] = None, escaping_enabled: Optional[bool] = None, standard_msh_numbering: Optional[bool] = None) -> "Hl7Json":
         """Creates a new Hl7Json instance from an HL7 message file."""
         ...
     
+    @classmethod
+    def split_messages(cls, text: str, validation_enabled: Optional[bool] = None, strict_validation: Optional[bool] = None, escaping_enabled: Optional[bool] = None, standard_msh_numbering: Optional[bool] = None) -> List["Hl7Json"]:
+        """Splits text containing several back-to-back messages into one Hl7Json per MSH segment."""
+        ...
+    
+    @classmethod
+    def from_xml(cls, xml: str, validation_enabled: Optional[bool] = None, strict_validation: Optional[bool] = None, escaping_enabled: Optional[bool] = None, standard_msh_numbering: Optional[bool] = None) -> "Hl7Json":
+        """Creates a new Hl7Json instance from an HL7 v2.xml document."""
+        ...
+    
+    @classmethod
+    def iter_file(cls, path: str, validation_enabled: Optional[bool] = None, strict_validation: Optional[bool] = None, escaping_enabled: Optional[bool] = None, standard_msh_numbering: Optional[bool] = None) -> "Hl7JsonFileIterator":
+        """Lazily reads a file of concatenated HL7 messages, yielding one Hl7Json per MSH segment."""
+        ...
+    
     @property
     def hl7_string(self) -> str:
         """The original HL7 message string with normalized line endings."""
@@ -47,25 +63,350 @@ class Hl7Json:
         """Enable or disable escaping during parsing."""
         ...
     
+    @property
+    def standard_msh_numbering(self) -> bool:
+        """Whether MSH fields are numbered as in the HL7 standard (MSH-1 is the field separator)."""
+        ...
+    
+    @standard_msh_numbering.setter
+    def standard_msh_numbering(self, value: bool) -> None:
+        """Enable or disable standard MSH field numbering."""
+        ...
+    
     @property
     def hl7_json(self) -> List[Dict[str, str]]:
         """Converts the HL7 message to a JSON-like structure."""
         ...
     
+    @property
+    def hl7_typed_json(self) -> List[Dict[str, Optional[str]]]:
+        """Like hl7_json, but the HL7 explicit null (\"\") becomes None; absent fields are omitted and empty fields are ""."""
+        ...
+    
+    @property
+    def hl7_nested_json(self) -> List[Dict[str, Any]]:
+        """Converts the HL7 message to nested JSON: {"segment_name", "fields"} where fields -> repetitions -> components -> subcomponents."""
+        ...
+    
+    @property
+    def hl7_messages_json(self) -> List[List[Dict[str, str]]]:
+        """Converts each message of a multi-message string separately; validation errors name the failing message index."""
+        ...
+    
+    @property
+    def hl7_grouped_json(self) -> Dict[str, Any]:
+        """Converts the HL7 message to JSON nested under its message structure groups; unplaced segments are listed in diagnostics."""
+        ...
+    
+    @property
+    def hl7_xml(self) -> str:
+        """Converts the HL7 message to the HL7 v2.xml encoding."""
+        ...
+    
+    @property
+    def google_parsed_data(self) -> Dict[str, Any]:
+        """Converts the HL7 message to the Cloud Healthcare parsedData shape: segments with segmentId, setId and fields."""
+        ...
+    
+    @property
+    def hl7_batch_json(self) -> Dict[str, Any]:
+        """Converts an FHS/BHS batch file to nested JSON: header, batches (header, messages, trailer) and trailer."""
+        ...
+    
+    @property
+    def arrow_tables(self) -> Dict[str, "Hl7ArrowTable"]:
+        """Converts all messages in the string to Arrow tables, one per segment type."""
+        ...
+    
+    def write_parquet(self, directory: str) -> None:
+        """Writes one <SEGMENT>.parquet file per segment type to the directory."""
+        ...
+    
+    def get(self, path: str) -> Optional[str]:
+        """Returns the value at a path such as "PID-5-1" or "OBX(2)-5", or None when absent."""
+        ...
+    
+    def get_all(self, path: str) -> List[str]:
+        """Returns the values at a path across all segment occurrences and field repetitions not given in the path."""
+        ...
+    
+    def set(self, path: str, value: str) -> None:
+        """Sets the value at a path and updates hl7_string."""
+        ...
+    
+    def query(self, segment_name: str, conditions: Optional[Union[List[Tuple[str, str, str]], Dict[str, str]]] = None, select: Optional[List[str]] = None) -> List[Dict[str, Optional[str]]]:
+        """Returns the segments whose values pass every condition, or one dict of the select keys per match."""
+        ...
+    
+    def diff(self, other: Union["Hl7Json", str]) -> List[Dict[str, Optional[str]]]:
+        """Returns the changes from this message to another as dicts with kind, segment, path, old_value and new_value."""
+        ...
+    
+    def diff_text(self, other: Union["Hl7Json", str]) -> str:
+        """Returns the changes from this message to another as unified-diff style text."""
+        ...
+    
     def validate(self, strict_mode: Optional[bool] = None, validate_required_fields: Optional[bool] = None) -> None:
         """Validates the HL7 message with optional custom settings."""
         ...
 
 
+class Hl7JsonFileIterator(Iterator[Hl7Json]):
+    """Iterator over the messages of an HL7 archive file."""
+    
+    def __iter__(self) -> "Hl7JsonFileIterator": ...
+    
+    def __next__(self) -> Hl7Json: ...
+
+class Hl7ArrowTable:
+    """Arrow table of one segment type; exported through the Arrow PyCapsule interface."""
+    
+    @property
+    def num_rows(self) -> int: ...
+    
+    @property
+    def column_names(self) -> List[str]:
+        """message_control_id, segment_sequence, then one column per flat JSON key."""
+        ...
+    
+    def __len__(self) -> int: ...
+    
+    def __arrow_c_schema__(self) -> Any: ...
+    
+    def __arrow_c_stream__(self, requested_schema: Optional[Any] = None) -> Any: ...
+    
+    def write_parquet(self, path: str) -> None:
+        """Writes the table to a Parquet file."""
+        ...
+
+class Hl7MutableMessage:
+    """An HL7 message that can be edited in place and written back to ER7."""
+    
+    def __init__(self, hl7_string: str, standard_msh_numbering: Optional[bool] = None) -> None: ...
+    
+    @property
+    def hl7_string(self) -> str:
+        """The edited message in ER7 with the message's own delimiters."""
+        ...
+    
+    @property
+    def hl7_json(self) -> List[Dict[str, str]]: ...
+    
+    @property
+    def segment_names(self) -> List[str]: ...
+    
+    def __len__(self) -> int: ...
+    
+    def insert_segment(self, index: int, segment: str) -> None: ...
+    
+    def append_segment(self, segment: str) -> None: ...
+    
+    def remove_segment(self, index: int) -> str:
+        """Removes the segment at index and returns it as ER7."""
+        ...
+    
+    def move_segment(self, from_index: int, to_index: int) -> None: ...
+    
+    def set_field_literal(self, index: int, field: int, value: str) -> None:
+        """Replaces a field of the segment at index; the whole value is escaped, unlike set(path, value)."""
+        ...
+    
+    def set_repetition_literal(self, index: int, field: int, repetition: int, value: str) -> None: ...
+    
+    def set_component_literal(self, index: int, field: int, repetition: int, component: int, value: str) -> None: ...
+    
+    def set_subcomponent_literal(self, index: int, field: int, repetition: int, component: int, subcomponent: int, value: str) -> None: ...
+    
+    def get(self, path: str) -> Optional[str]: ...
+    
+    def get_all(self, path: str) -> List[str]: ...
+    
+    def set(self, path: str, value: str) -> None: ...
+
+class Hl7Transform:
+    """A declarative mapping of set, copy, move, delete, lookup and segment rules applied to HL7 messages."""
+    
+    def __init__(self, mapping: Union[str, Dict[str, Any]]) -> None:
+        """Loads a mapping from a dict or a JSON string; invalid rules raise an error naming the rule index."""
+        ...
+    
+    @classmethod
+    def from_toml(cls, toml: str) -> "Hl7Transform": ...
+    
+    @classmethod
+    def from_file(cls, path: str) -> "Hl7Transform":
+        """Loads a mapping file; .toml files are read as TOML, anything else as JSON."""
+        ...
+    
+    def __len__(self) -> int: ...
+    
+    def apply(self, hl7_string: str, standard_msh_numbering: Optional[bool] = None) -> str:
+        """Applies every rule in order and returns the transformed message in ER7."""
+        ...
+
+class Deidentifier:
+    """Replaces PHI in HL7 messages by redaction, keyed hashing, pseudonyms and per-patient date shifting."""
+    
+    def __init__(self, secret: str, strategies: Optional[Dict[str, str]] = None, rules: Optional[List[Tuple[str, str]]] = None, max_date_shift_days: Optional[int] = None, redaction_text: Optional[str] = None) -> None:
+        """Strategies are keep, redact, hash, pseudonym or date_shift; categories are name, identifier, address, phone or date."""
+        ...
+    
+    @property
+    def rules(self) -> List[Tuple[str, str]]: ...
+    
+    def date_shift_days(self, patient_key: str) -> int: ...
+    
+    def deidentify(self, hl7_string: str, standard_msh_numbering: Optional[bool] = None) -> Tuple[str, List[Dict[str, str]]]:
+        """Returns the de-identified message in ER7 and the paths that were changed; pseudonyms persist across calls."""
+        ...
+
+class SegmentBuilder:
+    """Builds a segment of any type; values are escaped when the message is built."""
+    
+    def __init__(self, name: str) -> None: ...
+    
+    def with_field(self, field: int, value: str) -> "SegmentBuilder": ...
+    
+    def with_components(self, field: int, components: List[str]) -> "SegmentBuilder": ...
+    
+    def with_repetition(self, field: int, components: List[str]) -> "SegmentBuilder": ...
+
+class PidBuilder:
+    """Builds a PID segment."""
+    
+    def __init__(self) -> None: ...
+    
+    def with_set_id(self, set_id: str) -> "PidBuilder": ...
+    
+    def with_patient_identifier(self, id: str, assigning_authority: str, identifier_type: str) -> "PidBuilder": ...
+    
+    def with_patient_name(self, family_name: str, given_name: str) -> "PidBuilder": ...
+    
+    def with_date_of_birth(self, date_of_birth: str) -> "PidBuilder": ...
+    
+    def with_administrative_sex(self, sex: str) -> "PidBuilder": ...
+    
+    def with_address(self, street: str, city: str, state: str, postal_code: str) -> "PidBuilder": ...
+    
+    def with_phone_number(self, phone_number: str) -> "PidBuilder": ...
+    
+    def with_account_number(self, account_number: str) -> "PidBuilder": ...
+
+class Pv1Builder:
+    """Builds a PV1 segment."""
+    
+    def __init__(self) -> None: ...
+    
+    def with_set_id(self, set_id: str) -> "Pv1Builder": ...
+    
+    def with_patient_class(self, patient_class: str) -> "Pv1Builder": ...
+    
+    def with_assigned_location(self, point_of_care: str, room: str, bed: str) -> "Pv1Builder": ...
+    
+    def with_attending_doctor(self, id: str, family_name: str, given_name: str) -> "Pv1Builder": ...
+    
+    def with_visit_number(self, visit_number: str) -> "Pv1Builder": ...
+    
+    def with_admit_datetime(self, admit_datetime: str) -> "Pv1Builder": ...
+
+class ObrBuilder:
+    """Builds an OBR segment."""
+    
+    def __init__(self) -> None: ...
+    
+    def with_set_id(self, set_id: str) -> "ObrBuilder": ...
+    
+    def with_placer_order_number(self, placer_order_number: str) -> "ObrBuilder": ...
+    
+    def with_filler_order_number(self, filler_order_number: str) -> "ObrBuilder": ...
+    
+    def with_universal_service_id(self, code: str, text: str, coding_system: str) -> "ObrBuilder": ...
+    
+    def with_observation_datetime(self, observation_datetime: str) -> "ObrBuilder": ...
+    
+    def with_result_status(self, result_status: str) -> "ObrBuilder": ...
+
+class ObxBuilder:
+    """Builds an OBX segment."""
+    
+    def __init__(self) -> None: ...
+    
+    def with_set_id(self, set_id: str) -> "ObxBuilder": ...
+    
+    def with_value_type(self, value_type: str) -> "ObxBuilder": ...
+    
+    def with_observation_identifier(self, code: str, text: str, coding_system: str) -> "ObxBuilder": ...
+    
+    def with_observation_sub_id(self, sub_id: str) -> "ObxBuilder": ...
+    
+    def with_value(self, value: str) -> "ObxBuilder": ...
+    
+    def with_units(self, units: str) -> "ObxBuilder": ...
+    
+    def with_reference_range(self, reference_range: str) -> "ObxBuilder": ...
+    
+    def with_abnormal_flags(self, abnormal_flags: str) -> "ObxBuilder": ...
+    
+    def with_result_status(self, result_status: str) -> "ObxBuilder": ...
+    
+    def with_observation_datetime(self, observation_datetime: str) -> "ObxBuilder": ...
+
+class NteBuilder:
+    """Builds an NTE segment."""
+    
+    def __init__(self) -> None: ...
+    
+    def with_set_id(self, set_id: str) -> "NteBuilder": ...
+    
+    def with_source(self, source: str) -> "NteBuilder": ...
+    
+    def with_comment(self, comment: str) -> "NteBuilder": ...
+
+class MessageBuilder:
+    """Builds an outbound message; MSH-1, MSH-2, MSH-7, MSH-10, MSH-11 and MSH-12 are filled in."""
+    
+    def __init__(self, message_type: str, trigger_event: str, version: str) -> None: ...
+    
+    @property
+    def control_id(self) -> str: ...
+    
+    @property
+    def timestamp(self) -> str: ...
+    
+    @property
+    def hl7_string(self) -> str: ...
+    
+    def with_message_structure(self, message_structure: str) -> "MessageBuilder": ...
+    
+    def with_sending_application(self, sending_application: str) -> "MessageBuilder": ...
+    
+    def with_sending_facility(self, sending_facility: str) -> "MessageBuilder": ...
+    
+    def with_receiving_application(self, receiving_application: str) -> "MessageBuilder": ...
+    
+    def with_receiving_facility(self, receiving_facility: str) -> "MessageBuilder": ...
+    
+    def with_timestamp(self, timestamp: str) -> "MessageBuilder": ...
+    
+    def with_control_id(self, control_id: str) -> "MessageBuilder": ...
+    
+    def with_processing_id(self, processing_id: str) -> "MessageBuilder": ...
+    
+    def with_segment(self, segment: Union[SegmentBuilder, PidBuilder, Pv1Builder, ObrBuilder, ObxBuilder, NteBuilder]) -> "MessageBuilder": ...
+    
+    def build(self) -> str:
+        """Returns the escaped ER7 message."""
+        ...
+
 class JsonHl7:
     """A Python class for converting JSON data to HL7 format."""
     
-    def __init__(self, json_data: List[Dict[str, str]]) -> None:
-        """Creates a new JsonHl7 instance from JSON data."""
+    def __init__(self, json_data: Union[List[Dict[str, str]], List[Dict[str, Optional[str]]], List[Dict[str, Any]]], standard_msh_numbering: Optional[bool] = None, strict_keys: Optional[bool] = None, validate_schema: Optional[bool] = None) -> None:
+        """Creates a new JsonHl7 instance from flat, typed (None for HL7 null) or nested JSON data."""
         ...
     
     @classmethod
-    def from_file(cls, path: str) -> "JsonHl7":
+    def from_file(cls, path: str, standard_msh_numbering: Optional[bool] = None, strict_keys: Optional[bool] = None, validate_schema: Optional[bool] = None) -> "JsonHl7":
         """Creates a new JsonHl7 instance from a JSON file."""
         ...
     
@@ -74,6 +415,45 @@ class JsonHl7:
         """The JSON data representing HL7 segments."""
         ...
     
+    @property
+    def standard_msh_numbering(self) -> bool:
+        """Whether MSH fields in the JSON data use standard numbering."""
+        ...
+    
+    @property
+    def strict_keys(self) -> bool:
+        """Whether malformed or conflicting keys raise instead of being reported."""
+        ...
+    
+    @property
+    def key_warnings(self) -> List[str]:
+        """Malformed keys, conflicting keys and non-string values found in lenient mode."""
+        ...
+    
+    @classmethod
+    def from_google_parsed_data(cls, parsed_data: Dict[str, Any]) -> "JsonHl7":
+        """Creates a new JsonHl7 instance from Cloud Healthcare parsedData or a whole store message resource."""
+        ...
+    
+    @staticmethod
+    def json_schema(standard_msh_numbering: Optional[bool] = None) -> Dict[str, Any]:
+        """Returns the JSON Schema (draft 2020-12) for the flat segment-map format."""
+        ...
+    
+    @staticmethod
+    def segment_json_schema(segment_name: str, standard_msh_numbering: Optional[bool] = None) -> Dict[str, Any]:
+        """Returns the JSON Schema for a single segment of the flat format."""
+        ...
+    
+    def validate_schema(self) -> None:
+        """Validates the JSON data against json_schema()."""
+        ...
+    
+    @staticmethod
+    def batch_json_to_hl7(batch_json: Dict[str, Any], standard_msh_numbering: Optional[bool] = None, escape: Optional[bool] = None) -> str:
+        """Converts nested batch JSON (as produced by Hl7Json.hl7_batch_json) back to an HL7 batch file."""
+        ...
+    
     @property
     def hl7_string(self) -> str:
         """Converts the JSON data to HL7 message format with escaping."""
@@ -84,4 +464,13 @@ class JsonHl7:
         """Converts the JSON data to HL7 message format without escaping special characters."""
         ...
 
-__all__ = ["Hl7Json", "JsonHl7"]
\ No newline at end of file
+    @property
+    def hl7_xml(self) -> str:
+        """Converts the JSON data to the HL7 v2.xml encoding."""
+        ...
+
+def hl7_to_ndjson(source: Union[str, PathLike, IO[str], IO[bytes]], output_path: str, validation_enabled: Optional[bool] = None, strict_validation: Optional[bool] = None, escaping_enabled: Optional[bool] = None, standard_msh_numbering: Optional[bool] = None) -> Dict[str, int]:
+    """Streams messages from a file path or readable object to newline-delimited JSON; failed messages become {"message_index", "error"} records."""
+    ...
+
+__all__ = ["Deidentifier", "Hl7Json", "Hl7MutableMessage", "Hl7Transform", "JsonHl7", "MessageBuilder", "NteBuilder", "ObrBuilder", "ObxBuilder", "PidBuilder", "Pv1Builder", "SegmentBuilder", "hl7_to_ndjson"]
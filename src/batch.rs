@@ -0,0 +1,270 @@
+use crate::conversion::{
+    escape_handler_for_json, json_segment_to_hl7, json_to_hl7, SerializeOptions,
+};
+use crate::errors::Hl7Error;
+use crate::escape::{
+    create_default_escape_handler, create_escape_handler_for_message, Hl7EscapeHandler,
+};
+use crate::message::{Hl7Message, ParseOptions};
+use crate::segments::{Hl7Segment, MshNumbering};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::slice;
+
+pub type SegmentJson = BTreeMap<String, String>;
+
+#[derive(Debug, Clone, Default)]
+pub struct Hl7Batch {
+    pub header: Option<Hl7Segment>,
+    pub messages: Vec<Hl7Message>,
+    pub trailer: Option<Hl7Segment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Hl7BatchFile {
+    pub header: Option<Hl7Segment>,
+    pub batches: Vec<Hl7Batch>,
+    pub trailer: Option<Hl7Segment>,
+    pub msh_numbering: MshNumbering,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hl7BatchJson {
+    #[serde(default)]
+    pub header: Option<SegmentJson>,
+    #[serde(default)]
+    pub messages: Vec<Vec<SegmentJson>>,
+    #[serde(default)]
+    pub trailer: Option<SegmentJson>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hl7BatchFileJson {
+    #[serde(default)]
+    pub header: Option<SegmentJson>,
+    #[serde(default)]
+    pub batches: Vec<Hl7BatchJson>,
+    #[serde(default)]
+    pub trailer: Option<SegmentJson>,
+}
+
+struct BatchParser<'a> {
+    options: &'a ParseOptions,
+    handler: Hl7EscapeHandler,
+    batches: Vec<Hl7Batch>,
+    batch: Option<Hl7Batch>,
+    message: Vec<&'a str>,
+}
+
+impl<'a> BatchParser<'a> {
+    fn envelope_segment(&self, line: &str) -> Hl7Segment {
+        let handler =
+            Hl7EscapeHandler::from_msh_segment(line).unwrap_or_else(|_| self.handler.clone());
+        Hl7Segment::parse_with_numbering(
+            line,
+            &handler,
+            self.options.unescape,
+            self.options.msh_numbering,
+        )
+    }
+
+    fn flush_message(&mut self) -> Result<(), Hl7Error> {
+        if self.message.is_empty() {
+            return Ok(());
+        }
+
+        let message = Hl7Message::parse(&self.message.join("\n"), self.options)?;
+        self.message.clear();
+        self.batch
+            .get_or_insert_with(Hl7Batch::default)
+            .messages
+            .push(message);
+        Ok(())
+    }
+
+    fn flush_batch(&mut self) -> Result<(), Hl7Error> {
+        self.flush_message()?;
+        if let Some(batch) = self.batch.take() {
+            self.batches.push(batch);
+        }
+        Ok(())
+    }
+}
+
+impl Hl7BatchFile {
+    pub fn parse(hl7_string: &str, options: &ParseOptions) -> Result<Self, Hl7Error> {
+        if hl7_string.trim().is_empty() {
+            return Err(Hl7Error::EmptyMessage);
+        }
+
+        let mut parser = BatchParser {
+            options,
+            handler: create_escape_handler_for_message(hl7_string)?,
+            batches: Vec::new(),
+            batch: None,
+            message: Vec::new(),
+        };
+        let mut header = None;
+        let mut trailer = None;
+        let mut seen_segment = false;
+
+        for line in hl7_string.split(['\r', '\n']) {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let name = line.get(..3).unwrap_or(line);
+            if trailer.is_some() {
+                return Err(Hl7Error::InvalidFormat(format!(
+                    "{} segment found after FTS",
+                    name
+                )));
+            }
+
+            match name {
+                "FHS" => {
+                    if seen_segment {
+                        return Err(Hl7Error::InvalidFormat(
+                            "FHS must be the first segment of a batch file".to_string(),
+                        ));
+                    }
+                    header = Some(parser.envelope_segment(line));
+                }
+                "BHS" => {
+                    parser.flush_batch()?;
+                    parser.batch = Some(Hl7Batch {
+                        header: Some(parser.envelope_segment(line)),
+                        ..Hl7Batch::default()
+                    });
+                }
+                "MSH" => {
+                    parser.flush_message()?;
+                    parser.message.push(line);
+                }
+                "BTS" => {
+                    parser.flush_message()?;
+                    let bts = parser.envelope_segment(line);
+                    parser.batch.get_or_insert_with(Hl7Batch::default).trailer = Some(bts);
+                    parser.flush_batch()?;
+                }
+                "FTS" => {
+                    parser.flush_batch()?;
+                    trailer = Some(parser.envelope_segment(line));
+                }
+                _ => {
+                    if parser.message.is_empty() {
+                        return Err(Hl7Error::InvalidFormat(format!(
+                            "{} segment found outside of a message",
+                            name
+                        )));
+                    }
+                    parser.message.push(line);
+                }
+            }
+            seen_segment = true;
+        }
+        parser.flush_batch()?;
+
+        let batch_file = Hl7BatchFile {
+            header,
+            batches: parser.batches,
+            trailer,
+            msh_numbering: options.msh_numbering,
+        };
+        batch_file.validate_counts()?;
+        Ok(batch_file)
+    }
+
+    pub fn validate_counts(&self) -> Result<(), Hl7Error> {
+        for batch in &self.batches {
+            check_count(batch.trailer.as_ref(), batch.messages.len())?;
+        }
+        check_count(self.trailer.as_ref(), self.batches.len())
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = &Hl7Message> {
+        self.batches.iter().flat_map(|batch| batch.messages.iter())
+    }
+
+    pub fn to_json(&self) -> Hl7BatchFileJson {
+        Hl7BatchFileJson {
+            header: self.header.as_ref().map(Hl7Segment::to_json),
+            batches: self
+                .batches
+                .iter()
+                .map(|batch| Hl7BatchJson {
+                    header: batch.header.as_ref().map(Hl7Segment::to_json),
+                    messages: batch.messages.iter().map(Hl7Message::to_json).collect(),
+                    trailer: batch.trailer.as_ref().map(Hl7Segment::to_json),
+                })
+                .collect(),
+            trailer: self.trailer.as_ref().map(Hl7Segment::to_json),
+        }
+    }
+}
+
+impl Hl7BatchFileJson {
+    pub fn to_hl7(&self, options: &SerializeOptions) -> String {
+        let handler = self
+            .header
+            .as_ref()
+            .or_else(|| self.batches.iter().find_map(|batch| batch.header.as_ref()))
+            .map(|header| escape_handler_for_json(slice::from_ref(header), options.msh_numbering))
+            .unwrap_or_else(create_default_escape_handler);
+        let envelope = |segment: &SegmentJson| {
+            let handler = escape_handler_for_json(slice::from_ref(segment), options.msh_numbering);
+            json_segment_to_hl7(segment, &handler, options)
+        };
+
+        let mut lines = Vec::new();
+        lines.extend(self.header.iter().map(envelope));
+        for batch in &self.batches {
+            lines.extend(batch.header.iter().map(envelope));
+            lines.extend(
+                batch
+                    .messages
+                    .iter()
+                    .map(|message| json_to_hl7(message, options)),
+            );
+            lines.extend(
+                batch
+                    .trailer
+                    .iter()
+                    .map(|segment| json_segment_to_hl7(segment, &handler, options)),
+            );
+        }
+        lines.extend(
+            self.trailer
+                .iter()
+                .map(|segment| json_segment_to_hl7(segment, &handler, options)),
+        );
+
+        lines.join("\n")
+    }
+}
+
+fn check_count(trailer: Option<&Hl7Segment>, actual: usize) -> Result<(), Hl7Error> {
+    let Some(trailer) = trailer else {
+        return Ok(());
+    };
+    let Some(field) = trailer
+        .fields
+        .get(&1)
+        .filter(|f| !f.value.trim().is_empty())
+    else {
+        return Ok(());
+    };
+
+    let declared =
+        field.value.trim().parse::<usize>().map_err(|_| {
+            Hl7Error::field_error(&trailer.segment_name, 1, "count must be a number")
+        })?;
+    if declared != actual {
+        return Err(Hl7Error::CountMismatch {
+            segment: trailer.segment_name.clone(),
+            declared,
+            actual,
+        });
+    }
+    Ok(())
+}
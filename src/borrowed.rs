@@ -0,0 +1,308 @@
+use crate::errors::Hl7Error;
+use crate::escape::{create_escape_handler_for_message, Delimiters, Hl7EscapeHandler};
+use crate::message::Hl7Message;
+use crate::segments::{is_header_segment, Hl7Field, Hl7Segment, MshNumbering};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+pub struct MessageRef<'a> {
+    input: &'a str,
+    segments: Vec<SegmentRef<'a>>,
+    escape_handler: Hl7EscapeHandler,
+    msh_numbering: MshNumbering,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentRef<'a> {
+    raw: &'a str,
+    name: &'a str,
+    delimiters: Delimiters,
+    msh_numbering: MshNumbering,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FieldRef<'a> {
+    raw: &'a str,
+    delimiters: Delimiters,
+    verbatim: bool,
+}
+
+impl<'a> MessageRef<'a> {
+    pub fn parse(input: &'a str, msh_numbering: MshNumbering) -> Result<Self, Hl7Error> {
+        if input.trim().is_empty() {
+            return Err(Hl7Error::EmptyMessage);
+        }
+
+        let escape_handler = create_escape_handler_for_message(input)?;
+        let delimiters = escape_handler.delimiters();
+        let segments = input
+            .split(['\r', '\n'])
+            .filter(|seg| !seg.trim().is_empty())
+            .map(|seg| SegmentRef::new(seg, delimiters, msh_numbering))
+            .collect();
+
+        Ok(MessageRef {
+            input,
+            segments,
+            escape_handler,
+            msh_numbering,
+        })
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.input
+    }
+
+    pub fn segments(&self) -> &[SegmentRef<'a>] {
+        &self.segments
+    }
+
+    pub fn segments_named<'s>(
+        &'s self,
+        name: &'s str,
+    ) -> impl Iterator<Item = &'s SegmentRef<'a>> + 's {
+        self.segments.iter().filter(move |seg| seg.name == name)
+    }
+
+    pub fn escape_handler(&self) -> &Hl7EscapeHandler {
+        &self.escape_handler
+    }
+
+    pub fn msh_numbering(&self) -> MshNumbering {
+        self.msh_numbering
+    }
+
+    pub fn to_owned_message(&self, unescape: bool) -> Hl7Message {
+        Hl7Message {
+            segments: self.to_owned_segments(unescape),
+            escape_handler: self.escape_handler.clone(),
+            msh_numbering: self.msh_numbering,
+            unescaped: unescape,
+        }
+    }
+
+    pub fn to_owned_segments(&self, unescape: bool) -> Vec<Hl7Segment> {
+        self.segments
+            .iter()
+            .map(|seg| seg.to_owned_segment(&self.escape_handler, unescape))
+            .collect()
+    }
+
+    pub fn to_json(&self, unescape: bool) -> Vec<BTreeMap<String, String>> {
+        self.segments
+            .iter()
+            .map(|seg| {
+                seg.to_owned_segment(&self.escape_handler, unescape)
+                    .to_json()
+            })
+            .collect()
+    }
+}
+
+impl<'a> SegmentRef<'a> {
+    pub fn new(raw: &'a str, delimiters: Delimiters, msh_numbering: MshNumbering) -> Self {
+        let name = raw.split(delimiters.field).next().unwrap_or_default();
+        SegmentRef {
+            raw,
+            name,
+            delimiters,
+            msh_numbering,
+        }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.raw
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = (usize, FieldRef<'a>)> + 'a {
+        let delimiters = self.delimiters;
+        let is_header = is_header_segment(self.name);
+        let offset = usize::from(is_header && self.msh_numbering == MshNumbering::Standard);
+
+        let field_separator = if offset == 1 {
+            let start = self.name.len();
+            let end = start + delimiters.field.len_utf8();
+            self.raw
+                .get(start..end)
+                .map(|separator| (1, FieldRef::verbatim(separator, delimiters)))
+        } else {
+            None
+        };
+
+        let fields =
+            self.raw
+                .split(delimiters.field)
+                .enumerate()
+                .skip(1)
+                .map(move |(index, part)| {
+                    let field = if is_header && index == 1 {
+                        FieldRef::verbatim(part, delimiters)
+                    } else {
+                        FieldRef::new(part, delimiters)
+                    };
+                    (index + offset, field)
+                });
+
+        field_separator.into_iter().chain(fields)
+    }
+
+    pub fn field(&self, index: usize) -> Option<FieldRef<'a>> {
+        self.fields()
+            .find(|(field_index, _)| *field_index == index)
+            .map(|(_, field)| field)
+    }
+
+    pub fn to_owned_segment(&self, handler: &Hl7EscapeHandler, unescape: bool) -> Hl7Segment {
+        Hl7Segment {
+            segment_name: self.name.to_string(),
+            fields: self
+                .fields()
+                .map(|(index, field)| (index, field.to_owned_field(handler, unescape)))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> FieldRef<'a> {
+    pub fn new(raw: &'a str, delimiters: Delimiters) -> Self {
+        FieldRef {
+            raw,
+            delimiters,
+            verbatim: false,
+        }
+    }
+
+    pub fn verbatim(raw: &'a str, delimiters: Delimiters) -> Self {
+        FieldRef {
+            raw,
+            delimiters,
+            verbatim: true,
+        }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.raw
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    pub fn value(&self, handler: &Hl7EscapeHandler) -> Cow<'a, str> {
+        if self.verbatim {
+            Cow::Borrowed(self.raw)
+        } else {
+            handler.unescape_cow(self.raw)
+        }
+    }
+
+    pub fn has_repetitions(&self) -> bool {
+        !self.verbatim
+            && self.raw.contains(self.delimiters.repetition)
+            && !self.delimiters.contains_encoding_characters(self.raw)
+    }
+
+    pub fn repetitions(&self) -> impl Iterator<Item = FieldRef<'a>> + 'a {
+        let delimiters = self.delimiters;
+        let has_repetitions = self.has_repetitions();
+        let verbatim = self.verbatim;
+
+        self.raw
+            .split(move |ch: char| has_repetitions && ch == delimiters.repetition)
+            .map(move |rep| FieldRef {
+                raw: rep,
+                delimiters,
+                verbatim,
+            })
+    }
+
+    pub fn components(&self) -> impl Iterator<Item = &'a str> + 'a {
+        let separator = self.delimiters.component;
+        let verbatim = self.verbatim;
+        self.raw.split(move |ch: char| !verbatim && ch == separator)
+    }
+
+    pub fn component(&self, index: usize) -> Option<&'a str> {
+        index
+            .checked_sub(1)
+            .and_then(|index| self.components().nth(index))
+    }
+
+    pub fn subcomponent(&self, component: usize, index: usize) -> Option<&'a str> {
+        let subcomponent_separator = self.delimiters.subcomponent;
+        self.component(component).and_then(|component| {
+            index
+                .checked_sub(1)
+                .and_then(|index| component.split(subcomponent_separator).nth(index))
+        })
+    }
+
+    pub fn to_owned_field(&self, handler: &Hl7EscapeHandler, unescape: bool) -> Hl7Field {
+        if self.verbatim {
+            return Hl7Field::plain(self.raw);
+        }
+
+        if self.has_repetitions() {
+            Hl7Field {
+                value: self.raw.to_string(),
+                components: None,
+                subcomponents: None,
+                repetitions: Some(
+                    self.repetitions()
+                        .map(|rep| rep.to_owned_repetition(handler, unescape))
+                        .collect(),
+                ),
+            }
+        } else {
+            self.to_owned_repetition(handler, unescape)
+        }
+    }
+
+    fn to_owned_repetition(self, handler: &Hl7EscapeHandler, unescape: bool) -> Hl7Field {
+        let delimiters = self.delimiters;
+        let is_encoding_characters = delimiters.contains_encoding_characters(self.raw);
+        let decode = |raw: &str| {
+            if unescape {
+                handler.unescape_cow(raw).into_owned()
+            } else {
+                raw.to_string()
+            }
+        };
+
+        let has_components = self.raw.contains(delimiters.component) && !is_encoding_characters;
+        let has_subcomponents =
+            self.raw.contains(delimiters.subcomponent) && !is_encoding_characters;
+
+        let components = if has_components || has_subcomponents {
+            Some(self.components().map(decode).collect())
+        } else {
+            None
+        };
+        let subcomponents = if has_subcomponents {
+            Some(
+                self.components()
+                    .map(|component| {
+                        component
+                            .split(delimiters.subcomponent)
+                            .map(decode)
+                            .collect()
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        Hl7Field {
+            value: decode(self.raw),
+            components,
+            subcomponents,
+            repetitions: None,
+        }
+    }
+}
@@ -0,0 +1,1116 @@
+use crate::errors::Hl7Error;
+use crate::escape::Hl7EscapeHandler;
+use crate::segments::NestedField;
+use crate::structure::MessageStructure;
+use crate::utils;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static CONTROL_ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass(skip_from_py_object))]
+pub struct SegmentBuilder {
+    name: String,
+    fields: BTreeMap<usize, NestedField>,
+    numbered: bool,
+}
+
+impl SegmentBuilder {
+    pub fn new(name: &str) -> Self {
+        SegmentBuilder {
+            name: name.to_string(),
+            fields: BTreeMap::new(),
+            numbered: false,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn with_field(self, field: usize, value: &str) -> Self {
+        self.with_components(field, &[value])
+    }
+
+    pub fn with_components(mut self, field: usize, components: &[&str]) -> Self {
+        self.fields.insert(field, vec![to_components(components)]);
+        self
+    }
+
+    pub fn with_repetition(mut self, field: usize, components: &[&str]) -> Self {
+        self.fields
+            .entry(field)
+            .or_default()
+            .push(to_components(components));
+        self
+    }
+
+    pub fn to_er7(&self, handler: &Hl7EscapeHandler) -> String {
+        let delimiters = handler.delimiters();
+        let max_field = self.fields.keys().max().copied().unwrap_or(0);
+
+        let mut er7 = self.name.clone();
+        for index in 1..=max_field {
+            er7.push(delimiters.field);
+            let Some(repetitions) = self.fields.get(&index) else {
+                continue;
+            };
+            let field = repetitions
+                .iter()
+                .map(|components| {
+                    components
+                        .iter()
+                        .map(|subcomponents| {
+                            subcomponents
+                                .iter()
+                                .map(|value| handler.escape(value))
+                                .collect::<Vec<_>>()
+                                .join(&delimiters.subcomponent.to_string())
+                        })
+                        .collect::<Vec<_>>()
+                        .join(&delimiters.component.to_string())
+                })
+                .collect::<Vec<_>>()
+                .join(&delimiters.repetition.to_string());
+            er7.push_str(&field);
+        }
+        er7
+    }
+
+    fn numbered(mut self) -> Self {
+        self.numbered = true;
+        self
+    }
+
+    fn has_field(&self, field: usize) -> bool {
+        self.fields.contains_key(&field)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass(skip_from_py_object))]
+pub struct PidBuilder {
+    segment: SegmentBuilder,
+}
+
+impl Default for PidBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PidBuilder {
+    pub fn new() -> Self {
+        PidBuilder {
+            segment: SegmentBuilder::new("PID").numbered(),
+        }
+    }
+
+    pub fn with_set_id(mut self, set_id: &str) -> Self {
+        self.segment = self.segment.with_field(1, set_id);
+        self
+    }
+
+    pub fn with_patient_identifier(
+        mut self,
+        id: &str,
+        assigning_authority: &str,
+        identifier_type: &str,
+    ) -> Self {
+        self.segment = self
+            .segment
+            .with_repetition(3, &[id, "", "", assigning_authority, identifier_type]);
+        self
+    }
+
+    pub fn with_patient_name(mut self, family_name: &str, given_name: &str) -> Self {
+        self.segment = self.segment.with_components(5, &[family_name, given_name]);
+        self
+    }
+
+    pub fn with_date_of_birth(mut self, date_of_birth: &str) -> Self {
+        self.segment = self.segment.with_field(7, date_of_birth);
+        self
+    }
+
+    pub fn with_administrative_sex(mut self, sex: &str) -> Self {
+        self.segment = self.segment.with_field(8, sex);
+        self
+    }
+
+    pub fn with_address(
+        mut self,
+        street: &str,
+        city: &str,
+        state: &str,
+        postal_code: &str,
+    ) -> Self {
+        self.segment = self
+            .segment
+            .with_repetition(11, &[street, "", city, state, postal_code]);
+        self
+    }
+
+    pub fn with_phone_number(mut self, phone_number: &str) -> Self {
+        self.segment = self.segment.with_repetition(13, &[phone_number]);
+        self
+    }
+
+    pub fn with_account_number(mut self, account_number: &str) -> Self {
+        self.segment = self.segment.with_field(18, account_number);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass(skip_from_py_object))]
+pub struct Pv1Builder {
+    segment: SegmentBuilder,
+}
+
+impl Default for Pv1Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pv1Builder {
+    pub fn new() -> Self {
+        Pv1Builder {
+            segment: SegmentBuilder::new("PV1").numbered(),
+        }
+    }
+
+    pub fn with_set_id(mut self, set_id: &str) -> Self {
+        self.segment = self.segment.with_field(1, set_id);
+        self
+    }
+
+    pub fn with_patient_class(mut self, patient_class: &str) -> Self {
+        self.segment = self.segment.with_field(2, patient_class);
+        self
+    }
+
+    pub fn with_assigned_location(mut self, point_of_care: &str, room: &str, bed: &str) -> Self {
+        self.segment = self.segment.with_components(3, &[point_of_care, room, bed]);
+        self
+    }
+
+    pub fn with_attending_doctor(mut self, id: &str, family_name: &str, given_name: &str) -> Self {
+        self.segment = self
+            .segment
+            .with_repetition(7, &[id, family_name, given_name]);
+        self
+    }
+
+    pub fn with_visit_number(mut self, visit_number: &str) -> Self {
+        self.segment = self.segment.with_field(19, visit_number);
+        self
+    }
+
+    pub fn with_admit_datetime(mut self, admit_datetime: &str) -> Self {
+        self.segment = self.segment.with_field(44, admit_datetime);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass(skip_from_py_object))]
+pub struct ObrBuilder {
+    segment: SegmentBuilder,
+}
+
+impl Default for ObrBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ObrBuilder {
+    pub fn new() -> Self {
+        ObrBuilder {
+            segment: SegmentBuilder::new("OBR").numbered(),
+        }
+    }
+
+    pub fn with_set_id(mut self, set_id: &str) -> Self {
+        self.segment = self.segment.with_field(1, set_id);
+        self
+    }
+
+    pub fn with_placer_order_number(mut self, placer_order_number: &str) -> Self {
+        self.segment = self.segment.with_field(2, placer_order_number);
+        self
+    }
+
+    pub fn with_filler_order_number(mut self, filler_order_number: &str) -> Self {
+        self.segment = self.segment.with_field(3, filler_order_number);
+        self
+    }
+
+    pub fn with_universal_service_id(
+        mut self,
+        code: &str,
+        text: &str,
+        coding_system: &str,
+    ) -> Self {
+        self.segment = self
+            .segment
+            .with_components(4, &[code, text, coding_system]);
+        self
+    }
+
+    pub fn with_observation_datetime(mut self, observation_datetime: &str) -> Self {
+        self.segment = self.segment.with_field(7, observation_datetime);
+        self
+    }
+
+    pub fn with_result_status(mut self, result_status: &str) -> Self {
+        self.segment = self.segment.with_field(25, result_status);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass(skip_from_py_object))]
+pub struct ObxBuilder {
+    segment: SegmentBuilder,
+}
+
+impl Default for ObxBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ObxBuilder {
+    pub fn new() -> Self {
+        ObxBuilder {
+            segment: SegmentBuilder::new("OBX").numbered(),
+        }
+    }
+
+    pub fn with_set_id(mut self, set_id: &str) -> Self {
+        self.segment = self.segment.with_field(1, set_id);
+        self
+    }
+
+    pub fn with_value_type(mut self, value_type: &str) -> Self {
+        self.segment = self.segment.with_field(2, value_type);
+        self
+    }
+
+    pub fn with_observation_identifier(
+        mut self,
+        code: &str,
+        text: &str,
+        coding_system: &str,
+    ) -> Self {
+        self.segment = self
+            .segment
+            .with_components(3, &[code, text, coding_system]);
+        self
+    }
+
+    pub fn with_observation_sub_id(mut self, sub_id: &str) -> Self {
+        self.segment = self.segment.with_field(4, sub_id);
+        self
+    }
+
+    pub fn with_value(mut self, value: &str) -> Self {
+        self.segment = self.segment.with_field(5, value);
+        self
+    }
+
+    pub fn with_units(mut self, units: &str) -> Self {
+        self.segment = self.segment.with_field(6, units);
+        self
+    }
+
+    pub fn with_reference_range(mut self, reference_range: &str) -> Self {
+        self.segment = self.segment.with_field(7, reference_range);
+        self
+    }
+
+    pub fn with_abnormal_flags(mut self, abnormal_flags: &str) -> Self {
+        self.segment = self.segment.with_field(8, abnormal_flags);
+        self
+    }
+
+    pub fn with_result_status(mut self, result_status: &str) -> Self {
+        self.segment = self.segment.with_field(11, result_status);
+        self
+    }
+
+    pub fn with_observation_datetime(mut self, observation_datetime: &str) -> Self {
+        self.segment = self.segment.with_field(14, observation_datetime);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass(skip_from_py_object))]
+pub struct NteBuilder {
+    segment: SegmentBuilder,
+}
+
+impl Default for NteBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NteBuilder {
+    pub fn new() -> Self {
+        NteBuilder {
+            segment: SegmentBuilder::new("NTE").numbered(),
+        }
+    }
+
+    pub fn with_set_id(mut self, set_id: &str) -> Self {
+        self.segment = self.segment.with_field(1, set_id);
+        self
+    }
+
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.segment = self.segment.with_field(2, source);
+        self
+    }
+
+    pub fn with_comment(mut self, comment: &str) -> Self {
+        self.segment = self.segment.with_repetition(3, &[comment]);
+        self
+    }
+}
+
+impl From<PidBuilder> for SegmentBuilder {
+    fn from(builder: PidBuilder) -> Self {
+        builder.segment
+    }
+}
+
+impl From<Pv1Builder> for SegmentBuilder {
+    fn from(builder: Pv1Builder) -> Self {
+        builder.segment
+    }
+}
+
+impl From<ObrBuilder> for SegmentBuilder {
+    fn from(builder: ObrBuilder) -> Self {
+        builder.segment
+    }
+}
+
+impl From<ObxBuilder> for SegmentBuilder {
+    fn from(builder: ObxBuilder) -> Self {
+        builder.segment
+    }
+}
+
+impl From<NteBuilder> for SegmentBuilder {
+    fn from(builder: NteBuilder) -> Self {
+        builder.segment
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyclass(skip_from_py_object))]
+pub struct MessageBuilder {
+    message_type: String,
+    trigger_event: String,
+    message_structure: Option<String>,
+    version: String,
+    sending_application: String,
+    sending_facility: String,
+    receiving_application: String,
+    receiving_facility: String,
+    timestamp: String,
+    control_id: String,
+    processing_id: String,
+    segments: Vec<SegmentBuilder>,
+}
+
+impl MessageBuilder {
+    pub fn new(message_type: &str, trigger_event: &str, version: &str) -> Self {
+        let timestamp = current_timestamp();
+        let counter = CONTROL_ID_COUNTER.fetch_add(1, Ordering::Relaxed) % 1_000_000;
+        MessageBuilder {
+            message_type: message_type.to_string(),
+            trigger_event: trigger_event.to_string(),
+            message_structure: None,
+            version: version.to_string(),
+            sending_application: String::new(),
+            sending_facility: String::new(),
+            receiving_application: String::new(),
+            receiving_facility: String::new(),
+            control_id: format!("{}{:06}", timestamp, counter),
+            timestamp,
+            processing_id: "P".to_string(),
+            segments: Vec::new(),
+        }
+    }
+
+    pub fn with_message_structure(mut self, message_structure: &str) -> Self {
+        self.message_structure = Some(message_structure.to_string());
+        self
+    }
+
+    pub fn with_sending_application(mut self, sending_application: &str) -> Self {
+        self.sending_application = sending_application.to_string();
+        self
+    }
+
+    pub fn with_sending_facility(mut self, sending_facility: &str) -> Self {
+        self.sending_facility = sending_facility.to_string();
+        self
+    }
+
+    pub fn with_receiving_application(mut self, receiving_application: &str) -> Self {
+        self.receiving_application = receiving_application.to_string();
+        self
+    }
+
+    pub fn with_receiving_facility(mut self, receiving_facility: &str) -> Self {
+        self.receiving_facility = receiving_facility.to_string();
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: &str) -> Self {
+        self.timestamp = timestamp.to_string();
+        self
+    }
+
+    pub fn with_control_id(mut self, control_id: &str) -> Self {
+        self.control_id = control_id.to_string();
+        self
+    }
+
+    pub fn with_processing_id(mut self, processing_id: &str) -> Self {
+        self.processing_id = processing_id.to_string();
+        self
+    }
+
+    pub fn with_segment(mut self, segment: impl Into<SegmentBuilder>) -> Self {
+        self.segments.push(segment.into());
+        self
+    }
+
+    pub fn control_id(&self) -> &str {
+        &self.control_id
+    }
+
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    pub fn build(&self) -> Result<String, Hl7Error> {
+        let required = [
+            ("message type", &self.message_type),
+            ("trigger event", &self.trigger_event),
+            ("version", &self.version),
+        ];
+        for (name, value) in required {
+            if value.is_empty() {
+                return Err(Hl7Error::InvalidFormat(format!(
+                    "MSH {} must not be empty",
+                    name
+                )));
+            }
+        }
+
+        let handler = Hl7EscapeHandler::default();
+        let message_structure = match &self.message_structure {
+            Some(structure) => structure.clone(),
+            None => MessageStructure::for_message_type(&self.message_type, &self.trigger_event)
+                .map(|structure| structure.name)
+                .unwrap_or_else(|| format!("{}_{}", self.message_type, self.trigger_event)),
+        };
+        let message_type = [
+            self.message_type.as_str(),
+            self.trigger_event.as_str(),
+            &message_structure,
+        ];
+        let header = SegmentBuilder::new("MSH")
+            .with_field(2, &self.sending_application)
+            .with_field(3, &self.sending_facility)
+            .with_field(4, &self.receiving_application)
+            .with_field(5, &self.receiving_facility)
+            .with_field(6, &self.timestamp)
+            .with_components(8, &message_type)
+            .with_field(9, &self.control_id)
+            .with_field(10, &self.processing_id)
+            .with_field(11, &self.version);
+        let header = header.to_er7(&handler);
+        let header = format!(
+            "MSH{}{}{}",
+            handler.get_field_separator(),
+            handler.encoding_characters(),
+            &header[4..]
+        );
+
+        let mut lines = vec![header];
+        let mut set_ids: BTreeMap<&str, usize> = BTreeMap::new();
+        for segment in &self.segments {
+            validate_segment_name(&segment.name)?;
+
+            if segment.name != "NTE" {
+                set_ids.remove("NTE");
+            }
+            if segment.name == "OBR" {
+                set_ids.remove("OBX");
+            }
+
+            let set_id = set_ids.entry(&segment.name).or_insert(0);
+            *set_id += 1;
+            if segment.numbered && !segment.has_field(1) {
+                let numbered = segment.clone().with_field(1, &set_id.to_string());
+                lines.push(numbered.to_er7(&handler));
+            } else {
+                lines.push(segment.to_er7(&handler));
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+fn to_components(components: &[&str]) -> Vec<Vec<String>> {
+    let length = components
+        .iter()
+        .rposition(|component| !component.is_empty())
+        .map_or(1, |last| last + 1);
+    components
+        .iter()
+        .chain(std::iter::repeat(&""))
+        .take(length)
+        .map(|component| vec![component.to_string()])
+        .collect()
+}
+
+fn validate_segment_name(name: &str) -> Result<(), Hl7Error> {
+    let is_segment_name = name.len() == 3
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit());
+    if !is_segment_name || name == "MSH" {
+        return Err(Hl7Error::InvalidSegmentName {
+            name: name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn current_timestamp() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (days, seconds_of_day) = (seconds / 86_400, seconds % 86_400);
+    let (year, month, day) = utils::civil_from_days(days as i64);
+
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SegmentBuilder {
+    #[new]
+    fn py_new(name: &str) -> Self {
+        SegmentBuilder::new(name)
+    }
+
+    #[pyo3(name = "with_field")]
+    fn py_with_field<'py>(
+        slf: PyRefMut<'py, Self>,
+        field: usize,
+        value: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_field(field, value))
+    }
+
+    #[pyo3(name = "with_components")]
+    fn py_with_components<'py>(
+        slf: PyRefMut<'py, Self>,
+        field: usize,
+        components: Vec<String>,
+    ) -> PyRefMut<'py, Self> {
+        let components: Vec<&str> = components.iter().map(String::as_str).collect();
+        chain(slf, |builder| builder.with_components(field, &components))
+    }
+
+    #[pyo3(name = "with_repetition")]
+    fn py_with_repetition<'py>(
+        slf: PyRefMut<'py, Self>,
+        field: usize,
+        components: Vec<String>,
+    ) -> PyRefMut<'py, Self> {
+        let components: Vec<&str> = components.iter().map(String::as_str).collect();
+        chain(slf, |builder| builder.with_repetition(field, &components))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PidBuilder {
+    #[new]
+    fn py_new() -> Self {
+        PidBuilder::new()
+    }
+
+    #[pyo3(name = "with_set_id")]
+    fn py_with_set_id<'py>(slf: PyRefMut<'py, Self>, set_id: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_set_id(set_id))
+    }
+
+    #[pyo3(name = "with_patient_identifier")]
+    fn py_with_patient_identifier<'py>(
+        slf: PyRefMut<'py, Self>,
+        id: &str,
+        assigning_authority: &str,
+        identifier_type: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_patient_identifier(id, assigning_authority, identifier_type)
+        })
+    }
+
+    #[pyo3(name = "with_patient_name")]
+    fn py_with_patient_name<'py>(
+        slf: PyRefMut<'py, Self>,
+        family_name: &str,
+        given_name: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_patient_name(family_name, given_name)
+        })
+    }
+
+    #[pyo3(name = "with_date_of_birth")]
+    fn py_with_date_of_birth<'py>(
+        slf: PyRefMut<'py, Self>,
+        date_of_birth: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_date_of_birth(date_of_birth))
+    }
+
+    #[pyo3(name = "with_administrative_sex")]
+    fn py_with_administrative_sex<'py>(slf: PyRefMut<'py, Self>, sex: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_administrative_sex(sex))
+    }
+
+    #[pyo3(name = "with_address")]
+    fn py_with_address<'py>(
+        slf: PyRefMut<'py, Self>,
+        street: &str,
+        city: &str,
+        state: &str,
+        postal_code: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_address(street, city, state, postal_code)
+        })
+    }
+
+    #[pyo3(name = "with_phone_number")]
+    fn py_with_phone_number<'py>(
+        slf: PyRefMut<'py, Self>,
+        phone_number: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_phone_number(phone_number))
+    }
+
+    #[pyo3(name = "with_account_number")]
+    fn py_with_account_number<'py>(
+        slf: PyRefMut<'py, Self>,
+        account_number: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_account_number(account_number))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Pv1Builder {
+    #[new]
+    fn py_new() -> Self {
+        Pv1Builder::new()
+    }
+
+    #[pyo3(name = "with_set_id")]
+    fn py_with_set_id<'py>(slf: PyRefMut<'py, Self>, set_id: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_set_id(set_id))
+    }
+
+    #[pyo3(name = "with_patient_class")]
+    fn py_with_patient_class<'py>(
+        slf: PyRefMut<'py, Self>,
+        patient_class: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_patient_class(patient_class))
+    }
+
+    #[pyo3(name = "with_assigned_location")]
+    fn py_with_assigned_location<'py>(
+        slf: PyRefMut<'py, Self>,
+        point_of_care: &str,
+        room: &str,
+        bed: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_assigned_location(point_of_care, room, bed)
+        })
+    }
+
+    #[pyo3(name = "with_attending_doctor")]
+    fn py_with_attending_doctor<'py>(
+        slf: PyRefMut<'py, Self>,
+        id: &str,
+        family_name: &str,
+        given_name: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_attending_doctor(id, family_name, given_name)
+        })
+    }
+
+    #[pyo3(name = "with_visit_number")]
+    fn py_with_visit_number<'py>(
+        slf: PyRefMut<'py, Self>,
+        visit_number: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_visit_number(visit_number))
+    }
+
+    #[pyo3(name = "with_admit_datetime")]
+    fn py_with_admit_datetime<'py>(
+        slf: PyRefMut<'py, Self>,
+        admit_datetime: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_admit_datetime(admit_datetime))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ObrBuilder {
+    #[new]
+    fn py_new() -> Self {
+        ObrBuilder::new()
+    }
+
+    #[pyo3(name = "with_set_id")]
+    fn py_with_set_id<'py>(slf: PyRefMut<'py, Self>, set_id: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_set_id(set_id))
+    }
+
+    #[pyo3(name = "with_placer_order_number")]
+    fn py_with_placer_order_number<'py>(
+        slf: PyRefMut<'py, Self>,
+        placer_order_number: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_placer_order_number(placer_order_number)
+        })
+    }
+
+    #[pyo3(name = "with_filler_order_number")]
+    fn py_with_filler_order_number<'py>(
+        slf: PyRefMut<'py, Self>,
+        filler_order_number: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_filler_order_number(filler_order_number)
+        })
+    }
+
+    #[pyo3(name = "with_universal_service_id")]
+    fn py_with_universal_service_id<'py>(
+        slf: PyRefMut<'py, Self>,
+        code: &str,
+        text: &str,
+        coding_system: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_universal_service_id(code, text, coding_system)
+        })
+    }
+
+    #[pyo3(name = "with_observation_datetime")]
+    fn py_with_observation_datetime<'py>(
+        slf: PyRefMut<'py, Self>,
+        observation_datetime: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_observation_datetime(observation_datetime)
+        })
+    }
+
+    #[pyo3(name = "with_result_status")]
+    fn py_with_result_status<'py>(
+        slf: PyRefMut<'py, Self>,
+        result_status: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_result_status(result_status))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ObxBuilder {
+    #[new]
+    fn py_new() -> Self {
+        ObxBuilder::new()
+    }
+
+    #[pyo3(name = "with_set_id")]
+    fn py_with_set_id<'py>(slf: PyRefMut<'py, Self>, set_id: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_set_id(set_id))
+    }
+
+    #[pyo3(name = "with_value_type")]
+    fn py_with_value_type<'py>(slf: PyRefMut<'py, Self>, value_type: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_value_type(value_type))
+    }
+
+    #[pyo3(name = "with_observation_identifier")]
+    fn py_with_observation_identifier<'py>(
+        slf: PyRefMut<'py, Self>,
+        code: &str,
+        text: &str,
+        coding_system: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_observation_identifier(code, text, coding_system)
+        })
+    }
+
+    #[pyo3(name = "with_observation_sub_id")]
+    fn py_with_observation_sub_id<'py>(
+        slf: PyRefMut<'py, Self>,
+        sub_id: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_observation_sub_id(sub_id))
+    }
+
+    #[pyo3(name = "with_value")]
+    fn py_with_value<'py>(slf: PyRefMut<'py, Self>, value: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_value(value))
+    }
+
+    #[pyo3(name = "with_units")]
+    fn py_with_units<'py>(slf: PyRefMut<'py, Self>, units: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_units(units))
+    }
+
+    #[pyo3(name = "with_reference_range")]
+    fn py_with_reference_range<'py>(
+        slf: PyRefMut<'py, Self>,
+        reference_range: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_reference_range(reference_range))
+    }
+
+    #[pyo3(name = "with_abnormal_flags")]
+    fn py_with_abnormal_flags<'py>(
+        slf: PyRefMut<'py, Self>,
+        abnormal_flags: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_abnormal_flags(abnormal_flags))
+    }
+
+    #[pyo3(name = "with_result_status")]
+    fn py_with_result_status<'py>(
+        slf: PyRefMut<'py, Self>,
+        result_status: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_result_status(result_status))
+    }
+
+    #[pyo3(name = "with_observation_datetime")]
+    fn py_with_observation_datetime<'py>(
+        slf: PyRefMut<'py, Self>,
+        observation_datetime: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_observation_datetime(observation_datetime)
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NteBuilder {
+    #[new]
+    fn py_new() -> Self {
+        NteBuilder::new()
+    }
+
+    #[pyo3(name = "with_set_id")]
+    fn py_with_set_id<'py>(slf: PyRefMut<'py, Self>, set_id: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_set_id(set_id))
+    }
+
+    #[pyo3(name = "with_source")]
+    fn py_with_source<'py>(slf: PyRefMut<'py, Self>, source: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_source(source))
+    }
+
+    #[pyo3(name = "with_comment")]
+    fn py_with_comment<'py>(slf: PyRefMut<'py, Self>, comment: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_comment(comment))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl MessageBuilder {
+    #[new]
+    fn py_new(message_type: &str, trigger_event: &str, version: &str) -> Self {
+        MessageBuilder::new(message_type, trigger_event, version)
+    }
+
+    #[getter]
+    #[pyo3(name = "control_id")]
+    fn py_control_id(&self) -> String {
+        self.control_id.clone()
+    }
+
+    #[getter]
+    #[pyo3(name = "timestamp")]
+    fn py_timestamp(&self) -> String {
+        self.timestamp.clone()
+    }
+
+    #[getter]
+    fn hl7_string(&self) -> PyResult<String> {
+        Ok(self.build()?)
+    }
+
+    #[pyo3(name = "build")]
+    fn py_build(&self) -> PyResult<String> {
+        Ok(self.build()?)
+    }
+
+    #[pyo3(name = "with_message_structure")]
+    fn py_with_message_structure<'py>(
+        slf: PyRefMut<'py, Self>,
+        message_structure: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_message_structure(message_structure)
+        })
+    }
+
+    #[pyo3(name = "with_sending_application")]
+    fn py_with_sending_application<'py>(
+        slf: PyRefMut<'py, Self>,
+        sending_application: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_sending_application(sending_application)
+        })
+    }
+
+    #[pyo3(name = "with_sending_facility")]
+    fn py_with_sending_facility<'py>(
+        slf: PyRefMut<'py, Self>,
+        sending_facility: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_sending_facility(sending_facility)
+        })
+    }
+
+    #[pyo3(name = "with_receiving_application")]
+    fn py_with_receiving_application<'py>(
+        slf: PyRefMut<'py, Self>,
+        receiving_application: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_receiving_application(receiving_application)
+        })
+    }
+
+    #[pyo3(name = "with_receiving_facility")]
+    fn py_with_receiving_facility<'py>(
+        slf: PyRefMut<'py, Self>,
+        receiving_facility: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| {
+            builder.with_receiving_facility(receiving_facility)
+        })
+    }
+
+    #[pyo3(name = "with_timestamp")]
+    fn py_with_timestamp<'py>(slf: PyRefMut<'py, Self>, timestamp: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_timestamp(timestamp))
+    }
+
+    #[pyo3(name = "with_control_id")]
+    fn py_with_control_id<'py>(slf: PyRefMut<'py, Self>, control_id: &str) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_control_id(control_id))
+    }
+
+    #[pyo3(name = "with_processing_id")]
+    fn py_with_processing_id<'py>(
+        slf: PyRefMut<'py, Self>,
+        processing_id: &str,
+    ) -> PyRefMut<'py, Self> {
+        chain(slf, |builder| builder.with_processing_id(processing_id))
+    }
+
+    #[pyo3(name = "with_segment")]
+    fn py_with_segment<'py>(
+        slf: PyRefMut<'py, Self>,
+        segment: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let segment = py_segment_builder(segment)?;
+        Ok(chain(slf, |builder| builder.with_segment(segment)))
+    }
+}
+
+#[cfg(feature = "python")]
+fn chain<'py, T>(mut slf: PyRefMut<'py, T>, update: impl FnOnce(T) -> T) -> PyRefMut<'py, T>
+where
+    T: pyo3::PyClass<Frozen = pyo3::pyclass::boolean_struct::False> + Default,
+{
+    let builder = std::mem::take(&mut *slf);
+    *slf = update(builder);
+    slf
+}
+
+#[cfg(feature = "python")]
+fn py_segment_builder(segment: &Bound<'_, PyAny>) -> PyResult<SegmentBuilder> {
+    if let Ok(builder) = segment.extract::<PyRef<SegmentBuilder>>() {
+        return Ok(builder.clone());
+    }
+    if let Ok(builder) = segment.extract::<PyRef<PidBuilder>>() {
+        return Ok(builder.segment.clone());
+    }
+    if let Ok(builder) = segment.extract::<PyRef<Pv1Builder>>() {
+        return Ok(builder.segment.clone());
+    }
+    if let Ok(builder) = segment.extract::<PyRef<ObrBuilder>>() {
+        return Ok(builder.segment.clone());
+    }
+    if let Ok(builder) = segment.extract::<PyRef<ObxBuilder>>() {
+        return Ok(builder.segment.clone());
+    }
+    if let Ok(builder) = segment.extract::<PyRef<NteBuilder>>() {
+        return Ok(builder.segment.clone());
+    }
+    Err(pyo3::exceptions::PyTypeError::new_err(
+        "segment must be a SegmentBuilder, PidBuilder, Pv1Builder, ObrBuilder, ObxBuilder or NteBuilder",
+    ))
+}
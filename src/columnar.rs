@@ -0,0 +1,135 @@
+use crate::conversion::compare_json_keys;
+use crate::errors::Hl7Error;
+use crate::message::{parse_messages, Hl7Message, ParseOptions};
+use arrow::array::{ArrayRef, RecordBatch, StringArray, UInt32Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+pub const MESSAGE_CONTROL_ID_COLUMN: &str = "message_control_id";
+pub const SEGMENT_SEQUENCE_COLUMN: &str = "segment_sequence";
+
+#[derive(Debug, Clone, Default)]
+pub struct SegmentTables {
+    tables: BTreeMap<String, SegmentRows>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct SegmentRows {
+    control_ids: Vec<Option<String>>,
+    sequences: Vec<u32>,
+    values: Vec<BTreeMap<String, String>>,
+}
+
+impl SegmentTables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_message(&mut self, message: &Hl7Message) {
+        let control_id = message
+            .segments
+            .iter()
+            .find(|segment| segment.segment_name == "MSH")
+            .and_then(|msh| msh.fields.get(&message.msh_numbering.control_id_field()))
+            .map(|field| field.value.clone())
+            .filter(|value| !value.is_empty());
+
+        for (index, segment) in message.segments.iter().enumerate() {
+            let mut values = segment.to_json();
+            values.remove("segment_name");
+
+            let rows = self.tables.entry(segment.segment_name.clone()).or_default();
+            rows.control_ids.push(control_id.clone());
+            rows.sequences.push(index as u32 + 1);
+            rows.values.push(values);
+        }
+    }
+
+    pub fn finish(self) -> Result<BTreeMap<String, RecordBatch>, Hl7Error> {
+        self.tables
+            .into_iter()
+            .map(|(name, rows)| rows.into_record_batch().map(|batch| (name, batch)))
+            .collect()
+    }
+}
+
+impl SegmentRows {
+    fn into_record_batch(self) -> Result<RecordBatch, Hl7Error> {
+        let mut paths: Vec<String> = self
+            .values
+            .iter()
+            .flat_map(|row| row.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .cloned()
+            .collect();
+        paths.sort_by(|a, b| compare_json_keys(a, b));
+
+        let mut fields = vec![
+            Field::new(MESSAGE_CONTROL_ID_COLUMN, DataType::Utf8, true),
+            Field::new(SEGMENT_SEQUENCE_COLUMN, DataType::UInt32, false),
+        ];
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(self.control_ids)),
+            Arc::new(UInt32Array::from(self.sequences)),
+        ];
+        for path in &paths {
+            fields.push(Field::new(path, DataType::Utf8, true));
+            columns.push(Arc::new(
+                self.values
+                    .iter()
+                    .map(|row| row.get(path).map(String::as_str))
+                    .collect::<StringArray>(),
+            ));
+        }
+
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+            .map_err(|e| Hl7Error::EncodingError(e.to_string()))
+    }
+}
+
+pub fn messages_to_record_batches(
+    messages: &[Hl7Message],
+) -> Result<BTreeMap<String, RecordBatch>, Hl7Error> {
+    let mut tables = SegmentTables::new();
+    for message in messages {
+        tables.add_message(message);
+    }
+    tables.finish()
+}
+
+pub fn hl7_to_record_batches(
+    hl7_string: &str,
+    options: &ParseOptions,
+) -> Result<BTreeMap<String, RecordBatch>, Hl7Error> {
+    messages_to_record_batches(&parse_messages(hl7_string, options)?)
+}
+
+pub fn write_parquet<W: Write + Send>(batch: &RecordBatch, writer: W) -> Result<(), Hl7Error> {
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)
+        .map_err(|e| Hl7Error::EncodingError(e.to_string()))?;
+    writer
+        .write(batch)
+        .map_err(|e| Hl7Error::EncodingError(e.to_string()))?;
+    writer
+        .close()
+        .map_err(|e| Hl7Error::EncodingError(e.to_string()))?;
+    Ok(())
+}
+
+pub fn write_parquet_dir(
+    batches: &BTreeMap<String, RecordBatch>,
+    directory: &Path,
+) -> Result<(), Hl7Error> {
+    std::fs::create_dir_all(directory)?;
+    for (segment_name, batch) in batches {
+        let file = File::create(directory.join(format!("{}.parquet", segment_name)))?;
+        write_parquet(batch, file)?;
+    }
+    Ok(())
+}
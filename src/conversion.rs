@@ -0,0 +1,410 @@
+use crate::errors::Hl7Error;
+use crate::escape::{create_default_escape_handler, Hl7EscapeHandler};
+use crate::message::{Hl7Message, ParseOptions};
+use crate::segments::{
+    is_header_segment, Hl7NestedSegment, MshNumbering, TypedSegmentJson, HL7_NULL,
+};
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializeOptions {
+    pub escape: bool,
+    pub msh_numbering: MshNumbering,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self {
+            escape: true,
+            msh_numbering: MshNumbering::Legacy,
+        }
+    }
+}
+
+impl SerializeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lossless() -> Self {
+        Self::default().with_escaping(false)
+    }
+
+    pub fn with_escaping(mut self, escape: bool) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    pub fn with_msh_numbering(mut self, numbering: MshNumbering) -> Self {
+        self.msh_numbering = numbering;
+        self
+    }
+}
+
+pub fn hl7_to_json(
+    hl7_string: &str,
+    options: &ParseOptions,
+) -> Result<Vec<BTreeMap<String, String>>, Hl7Error> {
+    Ok(Hl7Message::parse(hl7_string, options)?.to_json())
+}
+
+pub fn json_to_hl7(json_data: &[BTreeMap<String, String>], options: &SerializeOptions) -> String {
+    let handler = escape_handler_for_json(json_data, options.msh_numbering);
+
+    json_data
+        .iter()
+        .map(|segment_json| json_segment_to_hl7(segment_json, &handler, options))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn try_json_to_hl7(
+    json_data: &[BTreeMap<String, String>],
+    options: &SerializeOptions,
+) -> Result<String, Hl7Error> {
+    match check_json(json_data).into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(json_to_hl7(json_data, options)),
+    }
+}
+
+pub fn hl7_to_typed_json(
+    hl7_string: &str,
+    options: &ParseOptions,
+) -> Result<Vec<TypedSegmentJson>, Hl7Error> {
+    Ok(Hl7Message::parse(hl7_string, options)?.to_typed_json())
+}
+
+pub fn typed_json_to_hl7(json_data: &[TypedSegmentJson], options: &SerializeOptions) -> String {
+    let flat_json: Vec<_> = json_data.iter().map(typed_segment_to_flat_json).collect();
+    json_to_hl7(&flat_json, options)
+}
+
+pub fn typed_segment_to_flat_json(segment_json: &TypedSegmentJson) -> BTreeMap<String, String> {
+    segment_json
+        .iter()
+        .map(|(key, value)| {
+            let value = value.clone().unwrap_or_else(|| HL7_NULL.to_string());
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+pub fn hl7_to_nested_json(
+    hl7_string: &str,
+    options: &ParseOptions,
+) -> Result<Vec<Hl7NestedSegment>, Hl7Error> {
+    Ok(Hl7Message::parse(hl7_string, options)?.to_nested_json())
+}
+
+pub fn nested_json_to_hl7(json_data: &[Hl7NestedSegment], options: &SerializeOptions) -> String {
+    let flat_json: Vec<_> = json_data
+        .iter()
+        .map(Hl7NestedSegment::to_flat_json)
+        .collect();
+    json_to_hl7(&flat_json, options)
+}
+
+pub fn escape_handler_for_json(
+    json_data: &[BTreeMap<String, String>],
+    numbering: MshNumbering,
+) -> Hl7EscapeHandler {
+    let Some(msh) = json_data.iter().find(|segment| {
+        segment
+            .get("segment_name")
+            .is_some_and(|name| is_header_segment(name))
+    }) else {
+        return create_default_escape_handler();
+    };
+
+    let handler = match numbering {
+        MshNumbering::Legacy => msh
+            .get("1")
+            .map(|encoding_characters| Hl7EscapeHandler::from_msh_field(encoding_characters)),
+        MshNumbering::Standard => msh.get("2").map(|encoding_characters| {
+            let field_separator = msh
+                .get("1")
+                .and_then(|separator| separator.chars().next())
+                .unwrap_or('|');
+            Hl7EscapeHandler::from_encoding_characters(field_separator, encoding_characters)
+        }),
+    };
+
+    handler
+        .and_then(Result::ok)
+        .unwrap_or_else(create_default_escape_handler)
+}
+
+type SubcomponentMap = BTreeMap<usize, String>;
+type ComponentMap = BTreeMap<usize, SubcomponentMap>;
+type RepetitionMap = BTreeMap<usize, ComponentMap>;
+type JsonKeyPosition = (usize, usize, usize, usize);
+
+fn parse_json_key(key: &str) -> Option<JsonKeyPosition> {
+    let (field_part, rep_index, rest) = if let Some(bracket_start) = key.find('[') {
+        let bracket_end = key.find(']')?;
+        if bracket_end < bracket_start {
+            return None;
+        }
+        let rep_index = key[bracket_start + 1..bracket_end].parse::<usize>().ok()?;
+        let rest = &key[bracket_end + 1..];
+        let rest = if rest.is_empty() {
+            None
+        } else {
+            Some(rest.strip_prefix('.')?)
+        };
+        (&key[..bracket_start], rep_index, rest)
+    } else {
+        match key.split_once('.') {
+            Some((field_part, rest)) => (field_part, 0, Some(rest)),
+            None => (key, 0, None),
+        }
+    };
+
+    let field_index = field_part.parse::<usize>().ok()?;
+    let (component_index, subcomponent_index) = match rest {
+        None => (0, 0),
+        Some(rest) => match rest.split_once('.') {
+            Some((component, subcomponent)) => (
+                component.parse::<usize>().ok()?,
+                subcomponent.parse::<usize>().ok()?,
+            ),
+            None => (rest.parse::<usize>().ok()?, 0),
+        },
+    };
+
+    Some((field_index, rep_index, component_index, subcomponent_index))
+}
+
+fn parse_strict_json_key(key: &str) -> Option<JsonKeyPosition> {
+    let position = parse_json_key(key)?;
+    let has_zero_index = key
+        .split('.')
+        .skip(1)
+        .any(|index| index.parse::<usize>() == Ok(0));
+    (position.0 > 0 && !has_zero_index).then_some(position)
+}
+
+fn json_keys_overlap(a: JsonKeyPosition, b: JsonKeyPosition) -> bool {
+    a.0 == b.0
+        && a.1 == b.1
+        && (a.2 == 0 || b.2 == 0 || (a.2 == b.2 && (a.3 == 0 || b.3 == 0 || a.3 == b.3)))
+}
+
+fn malformed_key_error(segment: &str, key: &str) -> Hl7Error {
+    let message = format!("invalid key \"{}\"", key);
+    let (field_part, component_part) = match key.split_once('.') {
+        Some((field_part, rest)) => (field_part, Some(rest)),
+        None => (key, None),
+    };
+
+    match (parse_json_key(field_part), component_part) {
+        (Some((field, ..)), Some(rest)) => {
+            let component = rest
+                .split('.')
+                .next()
+                .and_then(|component| component.parse::<usize>().ok())
+                .unwrap_or(0);
+            Hl7Error::component_error(segment, field, component, &message)
+        }
+        _ => {
+            let field = field_part
+                .split('[')
+                .next()
+                .and_then(|field| field.parse::<usize>().ok())
+                .unwrap_or(0);
+            Hl7Error::field_error(segment, field, &message)
+        }
+    }
+}
+
+pub fn check_json_segment(index: usize, segment_json: &Map<String, Value>) -> Vec<Hl7Error> {
+    let mut findings = Vec::new();
+    let segment = match segment_json.get("segment_name") {
+        Some(Value::String(name)) => format!("{} ({})", index, name),
+        _ => index.to_string(),
+    };
+
+    match segment_json.get("segment_name") {
+        None => findings.push(Hl7Error::field_error(&segment, 0, "missing segment_name")),
+        Some(Value::String(_)) => {}
+        Some(_) => findings.push(Hl7Error::field_error(
+            &segment,
+            0,
+            "segment_name must be a string",
+        )),
+    }
+
+    let mut positions: Vec<(&str, JsonKeyPosition)> = Vec::new();
+    for (key, value) in segment_json {
+        if key == "segment_name" {
+            continue;
+        }
+
+        let Some(position) = parse_strict_json_key(key) else {
+            findings.push(malformed_key_error(&segment, key));
+            continue;
+        };
+
+        if !(value.is_string() || value.is_null()) {
+            findings.push(Hl7Error::field_error(
+                &segment,
+                position.0,
+                &format!("value of key \"{}\" must be a string", key),
+            ));
+            continue;
+        }
+
+        if let Some((other, _)) = positions
+            .iter()
+            .find(|(_, other)| json_keys_overlap(position, *other))
+        {
+            let message = format!("key \"{}\" conflicts with \"{}\"", key, other);
+            findings.push(match position.2 {
+                0 => Hl7Error::field_error(&segment, position.0, &message),
+                component => Hl7Error::component_error(&segment, position.0, component, &message),
+            });
+        }
+        positions.push((key, position));
+    }
+
+    findings
+}
+
+pub fn check_json(json_data: &[BTreeMap<String, String>]) -> Vec<Hl7Error> {
+    json_data
+        .iter()
+        .enumerate()
+        .flat_map(|(index, segment_json)| {
+            let segment_json: Map<String, Value> = segment_json
+                .iter()
+                .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+                .collect();
+            check_json_segment(index, &segment_json)
+        })
+        .collect()
+}
+
+pub fn compare_json_keys(a: &str, b: &str) -> Ordering {
+    let rank = |key: &str| match key {
+        "segment_name" => (0, None),
+        _ => match parse_json_key(key) {
+            Some(position) => (1, Some(position)),
+            None => (2, None),
+        },
+    };
+
+    rank(a).cmp(&rank(b)).then_with(|| a.cmp(b))
+}
+
+fn join_indexed(values: &BTreeMap<usize, String>, separator: &str) -> String {
+    let max_index = values.keys().max().copied().unwrap_or(0);
+    if max_index == 0 {
+        return values.get(&0).cloned().unwrap_or_default();
+    }
+
+    (1..=max_index)
+        .map(|index| values.get(&index).cloned().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+pub fn json_segment_to_hl7(
+    segment_json: &BTreeMap<String, String>,
+    handler: &Hl7EscapeHandler,
+    options: &SerializeOptions,
+) -> String {
+    let mut fields = Vec::new();
+
+    let segment_name = segment_json.get("segment_name");
+    if let Some(segment_name) = segment_name {
+        fields.push(segment_name.clone());
+    }
+    let is_header = segment_name.is_some_and(|name| is_header_segment(name));
+    let is_standard_header = is_header && options.msh_numbering == MshNumbering::Standard;
+    let encoding_characters_index = if is_standard_header { 2 } else { 1 };
+
+    let mut field_map: BTreeMap<usize, RepetitionMap> = BTreeMap::new();
+
+    let process_value = |value: &str| -> String {
+        if options.escape {
+            handler.escape(value)
+        } else {
+            value.to_string()
+        }
+    };
+
+    for (key, value) in segment_json {
+        if key == "segment_name" {
+            continue;
+        }
+
+        if is_standard_header && key == "1" {
+            continue;
+        }
+
+        if is_header && key.parse::<usize>() == Ok(encoding_characters_index) {
+            field_map
+                .entry(encoding_characters_index)
+                .or_default()
+                .entry(0)
+                .or_default()
+                .entry(0)
+                .or_default()
+                .insert(0, value.clone());
+            continue;
+        }
+
+        if let Some((field_index, rep_index, component_index, subcomponent_index)) =
+            parse_json_key(key)
+        {
+            let processed_value = process_value(value);
+            field_map
+                .entry(field_index)
+                .or_default()
+                .entry(rep_index)
+                .or_default()
+                .entry(component_index)
+                .or_default()
+                .insert(subcomponent_index, processed_value);
+        }
+    }
+
+    let component_separator = handler.get_component_separator().to_string();
+    let repetition_separator = handler.get_repetition_separator().to_string();
+    let subcomponent_separator = handler.get_subcomponent_separator().to_string();
+
+    let max_index = field_map.keys().max().copied().unwrap_or(0);
+    let first_index = if is_standard_header { 2 } else { 1 };
+    for i in first_index..=max_index {
+        if let Some(repetitions) = field_map.get(&i) {
+            let mut rep_strings = Vec::new();
+            let max_rep = repetitions.keys().max().copied().unwrap_or(0);
+
+            for rep_index in 0..=max_rep {
+                if let Some(components) = repetitions.get(&rep_index) {
+                    let component_strings: BTreeMap<usize, String> = components
+                        .iter()
+                        .map(|(comp_index, subcomponents)| {
+                            (
+                                *comp_index,
+                                join_indexed(subcomponents, &subcomponent_separator),
+                            )
+                        })
+                        .collect();
+                    rep_strings.push(join_indexed(&component_strings, &component_separator));
+                } else {
+                    rep_strings.push(String::new());
+                }
+            }
+
+            fields.push(rep_strings.join(&repetition_separator));
+        } else {
+            fields.push(String::new());
+        }
+    }
+
+    fields.join(&handler.get_field_separator().to_string())
+}
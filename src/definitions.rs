@@ -0,0 +1,115 @@
+const MSH_FIELDS: &[&str] = &[
+    "ST", "ST", "HD", "HD", "HD", "HD", "TS", "ST", "MSG", "ST", "PT", "VID", "NM", "ST", "ID",
+    "ID", "ID", "ID", "CE", "ID", "EI",
+];
+const EVN_FIELDS: &[&str] = &["ID", "TS", "TS", "IS", "XCN", "TS", "HD"];
+const PID_FIELDS: &[&str] = &[
+    "SI", "CX", "CX", "CX", "XPN", "XPN", "TS", "IS", "XPN", "CE", "XAD", "IS", "XTN", "XTN", "CE",
+    "CE", "CE", "CX", "ST", "DLN", "CX", "CE", "ST", "ID", "NM", "CE", "CE", "CE", "TS", "ID",
+];
+const PV1_FIELDS: &[&str] = &[
+    "SI", "IS", "PL", "IS", "CX", "PL", "XCN", "XCN", "XCN", "IS", "PL", "IS", "IS", "IS", "IS",
+    "IS", "XCN", "IS", "CX",
+];
+const NK1_FIELDS: &[&str] = &["SI", "XPN", "CE", "XAD", "XTN", "XTN", "CE"];
+const ORC_FIELDS: &[&str] = &[
+    "ID", "EI", "EI", "EI", "ID", "ID", "TQ", "EIP", "TS", "XCN", "XCN", "XCN", "PL", "XTN", "TS",
+    "CE", "CE", "CE", "XCN", "CE", "XON",
+];
+const OBR_FIELDS: &[&str] = &[
+    "SI", "EI", "EI", "CE", "ID", "TS", "TS", "TS", "CQ", "XCN", "ID", "CE", "ST", "TS", "SPS",
+    "XCN", "XTN", "ST", "ST", "ST", "ST", "TS", "MOC", "ID", "ID", "PRL", "TQ", "XCN",
+];
+const OBX_FIELDS: &[&str] = &[
+    "SI", "ID", "CE", "ST", "varies", "CE", "ST", "IS", "NM", "ID", "ID", "TS", "ST", "TS", "CE",
+    "XCN", "CE", "EI", "TS",
+];
+const NTE_FIELDS: &[&str] = &["SI", "ID", "FT", "CE"];
+const MSA_FIELDS: &[&str] = &["ID", "ST", "ST", "NM", "ID", "CE"];
+const AL1_FIELDS: &[&str] = &["SI", "CE", "CE", "CE", "ST", "DT"];
+const DG1_FIELDS: &[&str] = &["SI", "ID", "CE", "ST", "TS", "IS"];
+
+const EVN_REPEATABLE: &[usize] = &[5];
+const PID_REPEATABLE: &[usize] = &[3, 4, 5, 6, 9, 10, 11, 13, 14, 21, 22, 26];
+const PV1_REPEATABLE: &[usize] = &[7, 8, 9, 15, 17];
+const NK1_REPEATABLE: &[usize] = &[2, 4, 5, 6];
+const ORC_REPEATABLE: &[usize] = &[7, 10, 11, 12, 14, 19, 21];
+const OBR_REPEATABLE: &[usize] = &[10, 16, 17, 27, 28];
+const OBX_REPEATABLE: &[usize] = &[5, 8, 10, 16, 17, 18];
+const NTE_REPEATABLE: &[usize] = &[3];
+const AL1_REPEATABLE: &[usize] = &[5];
+
+fn segment_fields(segment_name: &str) -> Option<(&'static [&'static str], &'static [usize])> {
+    Some(match segment_name {
+        "MSH" => (MSH_FIELDS, &[18]),
+        "EVN" => (EVN_FIELDS, EVN_REPEATABLE),
+        "PID" => (PID_FIELDS, PID_REPEATABLE),
+        "PV1" => (PV1_FIELDS, PV1_REPEATABLE),
+        "NK1" => (NK1_FIELDS, NK1_REPEATABLE),
+        "ORC" => (ORC_FIELDS, ORC_REPEATABLE),
+        "OBR" => (OBR_FIELDS, OBR_REPEATABLE),
+        "OBX" => (OBX_FIELDS, OBX_REPEATABLE),
+        "NTE" => (NTE_FIELDS, NTE_REPEATABLE),
+        "MSA" => (MSA_FIELDS, &[]),
+        "AL1" => (AL1_FIELDS, AL1_REPEATABLE),
+        "DG1" => (DG1_FIELDS, &[]),
+        _ => return None,
+    })
+}
+
+pub fn field_count(segment_name: &str) -> Option<usize> {
+    segment_fields(segment_name).map(|(fields, _)| fields.len())
+}
+
+pub fn field_repeatable(segment_name: &str, field: usize) -> Option<bool> {
+    let (fields, repeatable) = segment_fields(segment_name)?;
+    (field >= 1 && field <= fields.len()).then(|| repeatable.contains(&field))
+}
+
+pub fn field_data_type(segment_name: &str, field: usize) -> Option<&'static str> {
+    let (fields, _) = segment_fields(segment_name)?;
+    fields.get(field.checked_sub(1)?).copied()
+}
+
+pub fn component_data_types(data_type: &str) -> Option<&'static [&'static str]> {
+    let components: &[&str] = match data_type {
+        "CE" => &["ST", "ST", "ID", "ST", "ST", "ID"],
+        "CQ" => &["NM", "CE"],
+        "CWE" => &["ST", "ST", "ID", "ST", "ST", "ID", "ST", "ST", "ST"],
+        "CX" => &["ST", "ST", "ID", "HD", "ID", "HD", "DT", "DT", "CWE", "CWE"],
+        "DLN" => &["ST", "IS", "DT"],
+        "DR" => &["TS", "TS"],
+        "EI" => &["ST", "IS", "ST", "ID"],
+        "EIP" => &["EI", "EI"],
+        "FN" => &["ST", "ST", "ST", "ST", "ST"],
+        "HD" => &["IS", "ST", "ID"],
+        "MSG" => &["ID", "ID", "ID"],
+        "PL" => &[
+            "IS", "IS", "IS", "HD", "IS", "IS", "IS", "IS", "ST", "EI", "ST",
+        ],
+        "PT" => &["ID", "ID"],
+        "SAD" => &["ST", "ST", "ST"],
+        "TS" => &["DTM", "ID"],
+        "VID" => &["ID", "CE", "CE"],
+        "XAD" => &[
+            "SAD", "ST", "ST", "ST", "ST", "ID", "ID", "ST", "IS", "IS", "ID", "DR", "TS", "TS",
+        ],
+        "XCN" => &[
+            "ST", "FN", "ST", "ST", "ST", "ST", "IS", "IS", "HD", "ID", "ST", "ID", "ID", "HD",
+            "ID", "CE", "DR", "ID", "TS", "TS", "ST", "CWE", "CWE",
+        ],
+        "XON" => &["ST", "IS", "NM", "NM", "ID", "HD", "ID", "HD", "ID", "ST"],
+        "XPN" => &[
+            "FN", "ST", "ST", "ST", "ST", "IS", "ID", "ID", "CE", "DR", "ID", "TS", "TS", "ST",
+        ],
+        "XTN" => &[
+            "ST", "ID", "ID", "ST", "NM", "NM", "NM", "NM", "ST", "ST", "ST", "ST",
+        ],
+        _ => return None,
+    };
+    Some(components)
+}
+
+pub fn is_composite(data_type: &str) -> bool {
+    component_data_types(data_type).is_some()
+}
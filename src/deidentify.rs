@@ -0,0 +1,484 @@
+use crate::errors::Hl7Error;
+use crate::mutable::Hl7MutableMessage;
+use crate::segments::{is_header_segment, MshNumbering};
+use crate::terser::Hl7Path;
+use crate::utils;
+use hmac::{Hmac, Mac};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhiCategory {
+    Name,
+    Identifier,
+    Address,
+    Phone,
+    Date,
+}
+
+impl PhiCategory {
+    pub fn parse(category: &str) -> Result<Self, Hl7Error> {
+        match category.trim() {
+            "name" => Ok(PhiCategory::Name),
+            "identifier" => Ok(PhiCategory::Identifier),
+            "address" => Ok(PhiCategory::Address),
+            "phone" => Ok(PhiCategory::Phone),
+            "date" => Ok(PhiCategory::Date),
+            other => Err(Hl7Error::ConfigurationError(format!(
+                "unknown PHI category \"{}\", expected name, identifier, address, phone or date",
+                other
+            ))),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PhiCategory::Name => "name",
+            PhiCategory::Identifier => "identifier",
+            PhiCategory::Address => "address",
+            PhiCategory::Phone => "phone",
+            PhiCategory::Date => "date",
+        }
+    }
+
+    fn pseudonym_prefix(self) -> &'static str {
+        match self {
+            PhiCategory::Name => "NAME",
+            PhiCategory::Identifier => "ID",
+            PhiCategory::Address => "ADDRESS",
+            PhiCategory::Phone => "PHONE",
+            PhiCategory::Date => "DATE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeidStrategy {
+    Keep,
+    Redact,
+    Hash,
+    Pseudonym,
+    DateShift,
+}
+
+impl DeidStrategy {
+    pub fn parse(strategy: &str) -> Result<Self, Hl7Error> {
+        match strategy.trim() {
+            "keep" => Ok(DeidStrategy::Keep),
+            "redact" => Ok(DeidStrategy::Redact),
+            "hash" => Ok(DeidStrategy::Hash),
+            "pseudonym" => Ok(DeidStrategy::Pseudonym),
+            "date_shift" => Ok(DeidStrategy::DateShift),
+            other => Err(Hl7Error::ConfigurationError(format!(
+                "unknown de-identification strategy \"{}\", expected keep, redact, hash, pseudonym or date_shift",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeidRule {
+    pub path: String,
+    pub category: PhiCategory,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeidChange {
+    pub path: String,
+    pub category: PhiCategory,
+    pub strategy: DeidStrategy,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeidReport {
+    pub changes: Vec<DeidChange>,
+}
+
+impl DeidReport {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn paths(&self) -> Vec<&str> {
+        self.changes
+            .iter()
+            .map(|change| change.path.as_str())
+            .collect()
+    }
+}
+
+const DEFAULT_RULES: &[(&str, PhiCategory)] = &[
+    ("PID-5", PhiCategory::Name),
+    ("PID-6", PhiCategory::Name),
+    ("PID-9", PhiCategory::Name),
+    ("NK1-2", PhiCategory::Name),
+    ("PID-2", PhiCategory::Identifier),
+    ("PID-3-1", PhiCategory::Identifier),
+    ("PID-4-1", PhiCategory::Identifier),
+    ("PID-18-1", PhiCategory::Identifier),
+    ("PID-19", PhiCategory::Identifier),
+    ("PID-20-1", PhiCategory::Identifier),
+    ("PV1-19-1", PhiCategory::Identifier),
+    ("PID-11", PhiCategory::Address),
+    ("NK1-4", PhiCategory::Address),
+    ("PID-13", PhiCategory::Phone),
+    ("PID-14", PhiCategory::Phone),
+    ("NK1-5", PhiCategory::Phone),
+    ("NK1-6", PhiCategory::Phone),
+    ("PID-7", PhiCategory::Date),
+    ("PID-29", PhiCategory::Date),
+    ("EVN-2", PhiCategory::Date),
+    ("PV1-44", PhiCategory::Date),
+    ("PV1-45", PhiCategory::Date),
+    ("OBR-7", PhiCategory::Date),
+    ("OBX-14", PhiCategory::Date),
+];
+
+const HASH_LENGTH: usize = 16;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "python", pyclass(skip_from_py_object))]
+pub struct Deidentifier {
+    secret: Vec<u8>,
+    rules: Vec<DeidRule>,
+    strategies: BTreeMap<PhiCategory, DeidStrategy>,
+    max_date_shift_days: u32,
+    redaction_text: String,
+    pseudonyms: HashMap<(PhiCategory, String), String>,
+    pseudonym_counts: BTreeMap<PhiCategory, usize>,
+}
+
+impl Deidentifier {
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Deidentifier {
+            secret: secret.as_ref().to_vec(),
+            rules: DEFAULT_RULES
+                .iter()
+                .map(|(path, category)| DeidRule {
+                    path: path.to_string(),
+                    category: *category,
+                })
+                .collect(),
+            strategies: BTreeMap::from([
+                (PhiCategory::Name, DeidStrategy::Pseudonym),
+                (PhiCategory::Identifier, DeidStrategy::Hash),
+                (PhiCategory::Address, DeidStrategy::Redact),
+                (PhiCategory::Phone, DeidStrategy::Redact),
+                (PhiCategory::Date, DeidStrategy::DateShift),
+            ]),
+            max_date_shift_days: 365,
+            redaction_text: String::new(),
+            pseudonyms: HashMap::new(),
+            pseudonym_counts: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_rule(mut self, path: &str, category: PhiCategory) -> Self {
+        self.rules.push(DeidRule {
+            path: path.to_string(),
+            category,
+        });
+        self
+    }
+
+    pub fn with_strategy(mut self, category: PhiCategory, strategy: DeidStrategy) -> Self {
+        self.strategies.insert(category, strategy);
+        self
+    }
+
+    pub fn with_max_date_shift_days(mut self, days: u32) -> Self {
+        self.max_date_shift_days = days;
+        self
+    }
+
+    pub fn with_redaction_text(mut self, text: &str) -> Self {
+        self.redaction_text = text.to_string();
+        self
+    }
+
+    pub fn rules(&self) -> &[DeidRule] {
+        &self.rules
+    }
+
+    pub fn strategy(&self, category: PhiCategory) -> DeidStrategy {
+        self.strategies
+            .get(&category)
+            .copied()
+            .unwrap_or(DeidStrategy::Keep)
+    }
+
+    pub fn date_shift_days(&self, patient_key: &str) -> i64 {
+        if self.max_date_shift_days == 0 {
+            return 0;
+        }
+        let digest = self.digest(&format!("date_shift:{}", patient_key));
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        let span = 2 * u64::from(self.max_date_shift_days) + 1;
+        (u64::from_be_bytes(bytes) % span) as i64 - i64::from(self.max_date_shift_days)
+    }
+
+    pub fn deidentify(&mut self, message: &mut Hl7MutableMessage) -> Result<DeidReport, Hl7Error> {
+        let mut rules = Vec::new();
+        for rule in &self.rules {
+            let path = Hl7Path::parse(&rule.path)?;
+            let verbatim_fields = match message.msh_numbering() {
+                MshNumbering::Legacy => 1,
+                MshNumbering::Standard => 2,
+            };
+            if is_header_segment(&path.segment) && path.field <= verbatim_fields {
+                return Err(Hl7Error::invalid_path(
+                    &rule.path,
+                    "the field separator and encoding characters cannot be de-identified",
+                ));
+            }
+            let strategy = self.strategy(rule.category);
+            if strategy != DeidStrategy::Keep {
+                rules.push((path, rule.category, strategy));
+            }
+        }
+
+        let patient_key = message.get("PID-3-1")?.unwrap_or_default();
+        let date_shift = self.date_shift_days(&patient_key);
+
+        let mut report = DeidReport::default();
+        for (path, category, strategy) in rules {
+            let targets = {
+                let message = message.message_mut();
+                let mut targets = Vec::new();
+                let mut occurrence = 0;
+                for (index, segment) in message.segments.iter().enumerate() {
+                    if segment.segment_name != path.segment {
+                        continue;
+                    }
+                    occurrence += 1;
+                    if path
+                        .occurrence
+                        .is_some_and(|selected| selected + 1 != occurrence)
+                    {
+                        continue;
+                    }
+                    let Some(field) = segment.fields.get(&path.field) else {
+                        continue;
+                    };
+                    let repetitions: Vec<usize> = match path.repetition {
+                        Some(repetition) => vec![repetition],
+                        None => (0..field.to_nested_json().len()).collect(),
+                    };
+                    for repetition in repetitions {
+                        if let Some(value) = path
+                            .values(message, segment, Some(repetition))?
+                            .into_iter()
+                            .next()
+                        {
+                            targets.push((index, occurrence - 1, repetition, value));
+                        }
+                    }
+                }
+                targets
+            };
+
+            for (index, occurrence, repetition, value) in targets {
+                let delimiters = message.escape_handler().delimiters();
+                let is_blank = value
+                    .chars()
+                    .all(|ch| ch == delimiters.component || ch == delimiters.subcomponent);
+                if is_blank {
+                    continue;
+                }
+
+                let replacement = match strategy {
+                    DeidStrategy::Keep => continue,
+                    DeidStrategy::Redact => message.escape_handler().escape(&self.redaction_text),
+                    DeidStrategy::Hash => {
+                        let digest = self.digest(&value);
+                        digest
+                            .iter()
+                            .map(|byte| format!("{:02x}", byte))
+                            .collect::<String>()[..HASH_LENGTH]
+                            .to_string()
+                    }
+                    DeidStrategy::Pseudonym => self.pseudonym(category, &value),
+                    DeidStrategy::DateShift => value
+                        .split(delimiters.component)
+                        .map(|component| {
+                            component
+                                .split(delimiters.subcomponent)
+                                .map(|subcomponent| {
+                                    shift_date(subcomponent, date_shift)
+                                        .unwrap_or_else(|| subcomponent.to_string())
+                                })
+                                .collect::<Vec<_>>()
+                                .join(&delimiters.subcomponent.to_string())
+                        })
+                        .collect::<Vec<_>>()
+                        .join(&delimiters.component.to_string()),
+                };
+                if replacement == value {
+                    continue;
+                }
+
+                let target = Hl7Path {
+                    occurrence: None,
+                    repetition: Some(repetition),
+                    ..path.clone()
+                };
+                let numbering = message.msh_numbering();
+                let message = message.message_mut();
+                target.set_in_segment(
+                    &mut message.segments[index],
+                    numbering,
+                    delimiters,
+                    &replacement,
+                )?;
+
+                report.changes.push(DeidChange {
+                    path: change_path(&path, occurrence, repetition),
+                    category,
+                    strategy,
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    pub fn deidentify_hl7(
+        &mut self,
+        hl7_string: &str,
+        msh_numbering: MshNumbering,
+    ) -> Result<(String, DeidReport), Hl7Error> {
+        let mut message = Hl7MutableMessage::parse(hl7_string, msh_numbering)?;
+        let report = self.deidentify(&mut message)?;
+        Ok((message.to_er7(), report))
+    }
+
+    fn digest(&self, value: &str) -> Vec<u8> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(value.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn pseudonym(&mut self, category: PhiCategory, value: &str) -> String {
+        let key = (category, value.to_string());
+        if let Some(pseudonym) = self.pseudonyms.get(&key) {
+            return pseudonym.clone();
+        }
+        let count = self.pseudonym_counts.entry(category).or_insert(0);
+        *count += 1;
+        let pseudonym = format!("{}{:04}", category.pseudonym_prefix(), count);
+        self.pseudonyms.insert(key, pseudonym.clone());
+        pseudonym
+    }
+}
+
+fn shift_date(value: &str, days: i64) -> Option<String> {
+    let digits = value.bytes().take_while(u8::is_ascii_digit).count();
+    if digits < 8 {
+        return None;
+    }
+    let year: i64 = value[..4].parse().ok()?;
+    let month: i64 = value[4..6].parse().ok()?;
+    let day: i64 = value[6..8].parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let date = utils::days_from_civil(year, month, day);
+    if utils::civil_from_days(date) != (year, month, day) {
+        return None;
+    }
+
+    let (year, month, day) = utils::civil_from_days(date + days);
+    if !(0..=9999).contains(&year) {
+        return None;
+    }
+    Some(format!("{:04}{:02}{:02}{}", year, month, day, &value[8..]))
+}
+
+fn change_path(path: &Hl7Path, occurrence: usize, repetition: usize) -> String {
+    let mut label = path.segment.clone();
+    if occurrence > 0 {
+        label.push_str(&format!("({})", occurrence));
+    }
+    label.push_str(&format!("-{}", path.field));
+    if repetition > 0 {
+        label.push_str(&format!("({})", repetition));
+    }
+    if let Some(component) = path.component {
+        label.push_str(&format!("-{}", component));
+    }
+    if let Some(subcomponent) = path.subcomponent {
+        label.push_str(&format!("-{}", subcomponent));
+    }
+    label
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Deidentifier {
+    #[new]
+    #[pyo3(signature = (secret, strategies=None, rules=None, max_date_shift_days=None, redaction_text=None))]
+    fn py_new(
+        secret: &str,
+        strategies: Option<BTreeMap<String, String>>,
+        rules: Option<Vec<(String, String)>>,
+        max_date_shift_days: Option<u32>,
+        redaction_text: Option<String>,
+    ) -> PyResult<Self> {
+        let mut deidentifier = Deidentifier::new(secret);
+        for (category, strategy) in strategies.unwrap_or_default() {
+            deidentifier = deidentifier.with_strategy(
+                PhiCategory::parse(&category)?,
+                DeidStrategy::parse(&strategy)?,
+            );
+        }
+        for (path, category) in rules.unwrap_or_default() {
+            Hl7Path::parse(&path)?;
+            deidentifier = deidentifier.with_rule(&path, PhiCategory::parse(&category)?);
+        }
+        if let Some(days) = max_date_shift_days {
+            deidentifier = deidentifier.with_max_date_shift_days(days);
+        }
+        if let Some(text) = redaction_text {
+            deidentifier = deidentifier.with_redaction_text(&text);
+        }
+        Ok(deidentifier)
+    }
+
+    #[getter]
+    #[pyo3(name = "rules")]
+    fn py_rules(&self) -> Vec<(String, String)> {
+        self.rules
+            .iter()
+            .map(|rule| (rule.path.clone(), rule.category.as_str().to_string()))
+            .collect()
+    }
+
+    #[pyo3(name = "date_shift_days")]
+    fn py_date_shift_days(&self, patient_key: &str) -> i64 {
+        self.date_shift_days(patient_key)
+    }
+
+    #[pyo3(name = "deidentify", signature = (hl7_string, standard_msh_numbering=None))]
+    fn py_deidentify<'py>(
+        &mut self,
+        py: Python<'py>,
+        hl7_string: &str,
+        standard_msh_numbering: Option<bool>,
+    ) -> PyResult<(String, Bound<'py, PyAny>)> {
+        let numbering = MshNumbering::from_flag(standard_msh_numbering.unwrap_or(false));
+        let (hl7, report) = self.deidentify_hl7(hl7_string, numbering)?;
+        let json = serde_json::to_value(&report.changes).map_err(Hl7Error::from)?;
+        Ok((hl7, crate::py_json::value_to_py(py, &json)?))
+    }
+}
@@ -0,0 +1,286 @@
+use crate::definitions::field_data_type;
+use crate::message::Hl7Message;
+use crate::segments::{is_header_segment, Hl7Segment};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hl7Change {
+    pub kind: ChangeKind,
+    pub segment: String,
+    pub path: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageDiff {
+    pub changes: Vec<Hl7Change>,
+}
+
+impl MessageDiff {
+    pub fn between(old: &Hl7Message, new: &Hl7Message) -> Self {
+        let old_segments = located_segments(old);
+        let new_segments = located_segments(new);
+
+        let mut unmatched: HashMap<(&str, &str, usize), &LocatedSegment> = old_segments
+            .iter()
+            .map(|segment| (segment.key(), segment))
+            .collect();
+
+        let mut entries: Vec<(usize, usize, Vec<Hl7Change>)> = Vec::new();
+        for segment in &new_segments {
+            match unmatched.remove(&segment.key()) {
+                Some(old_segment) => {
+                    let changes = value_changes(old_segment, old, segment, new);
+                    entries.push((segment.index, 1, changes));
+                }
+                None => entries.push((
+                    segment.index,
+                    1,
+                    vec![Hl7Change {
+                        kind: ChangeKind::Added,
+                        segment: segment.label(),
+                        path: segment.label(),
+                        old_value: None,
+                        new_value: Some(segment_text(segment.segment, new)),
+                    }],
+                )),
+            }
+        }
+        for segment in &old_segments {
+            if unmatched.contains_key(&segment.key()) {
+                entries.push((
+                    segment.index,
+                    0,
+                    vec![Hl7Change {
+                        kind: ChangeKind::Removed,
+                        segment: segment.label(),
+                        path: segment.label(),
+                        old_value: Some(segment_text(segment.segment, old)),
+                        new_value: None,
+                    }],
+                ));
+            }
+        }
+
+        entries.sort_by_key(|(index, order, _)| (*index, *order));
+        MessageDiff {
+            changes: entries
+                .into_iter()
+                .flat_map(|(_, _, changes)| changes)
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn to_unified_text(&self) -> String {
+        let mut lines = vec!["--- old".to_string(), "+++ new".to_string()];
+        let mut hunk: Option<&str> = None;
+        for change in &self.changes {
+            if hunk != Some(change.segment.as_str()) {
+                lines.push(format!("@@ {} @@", change.segment));
+                hunk = Some(&change.segment);
+            }
+            if let Some(old_value) = &change.old_value {
+                lines.push(format!("-{}: {}", change.path, old_value));
+            }
+            if let Some(new_value) = &change.new_value {
+                lines.push(format!("+{}: {}", change.path, new_value));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl fmt::Display for MessageDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_unified_text())
+    }
+}
+
+pub fn diff_messages(old: &Hl7Message, new: &Hl7Message) -> MessageDiff {
+    MessageDiff::between(old, new)
+}
+
+struct LocatedSegment<'m> {
+    segment: &'m Hl7Segment,
+    index: usize,
+    occurrence: usize,
+    set_id: &'m str,
+    set_id_occurrence: usize,
+}
+
+impl LocatedSegment<'_> {
+    fn key(&self) -> (&str, &str, usize) {
+        (
+            &self.segment.segment_name,
+            self.set_id,
+            self.set_id_occurrence,
+        )
+    }
+
+    fn label(&self) -> String {
+        match self.occurrence {
+            0 => self.segment.segment_name.clone(),
+            occurrence => format!("{}({})", self.segment.segment_name, occurrence),
+        }
+    }
+}
+
+fn located_segments(message: &Hl7Message) -> Vec<LocatedSegment<'_>> {
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+    let mut set_id_occurrences: HashMap<(&str, &str), usize> = HashMap::new();
+
+    message
+        .segments
+        .iter()
+        .enumerate()
+        .map(|(index, segment)| {
+            let name = segment.segment_name.as_str();
+            let set_id = if field_data_type(name, 1) == Some("SI") {
+                segment
+                    .fields
+                    .get(&1)
+                    .map(|field| field.value.trim())
+                    .unwrap_or("")
+            } else {
+                ""
+            };
+
+            let occurrence = occurrences.entry(name).or_insert(0);
+            let set_id_occurrence = set_id_occurrences.entry((name, set_id)).or_insert(0);
+            let located = LocatedSegment {
+                segment,
+                index,
+                occurrence: *occurrence,
+                set_id,
+                set_id_occurrence: *set_id_occurrence,
+            };
+            *occurrence += 1;
+            *set_id_occurrence += 1;
+            located
+        })
+        .collect()
+}
+
+fn value_changes(
+    old_segment: &LocatedSegment,
+    old: &Hl7Message,
+    new_segment: &LocatedSegment,
+    new: &Hl7Message,
+) -> Vec<Hl7Change> {
+    let old_values = segment_values(old_segment.segment, old);
+    let new_values = segment_values(new_segment.segment, new);
+
+    let mut positions: Vec<&ValuePosition> = old_values.keys().chain(new_values.keys()).collect();
+    positions.sort();
+    positions.dedup();
+
+    let label = new_segment.label();
+    positions
+        .into_iter()
+        .filter_map(|position| {
+            let old_value = old_values.get(position).cloned();
+            let new_value = new_values.get(position).cloned();
+            let kind = match (&old_value, &new_value) {
+                (None, Some(_)) => ChangeKind::Added,
+                (Some(_), None) => ChangeKind::Removed,
+                (Some(old_value), Some(new_value)) if old_value != new_value => ChangeKind::Changed,
+                _ => return None,
+            };
+
+            let single_component = |values: &BTreeMap<ValuePosition, String>| {
+                values
+                    .range(
+                        ValuePosition::new(position.field, position.repetition, 2)
+                            ..ValuePosition::new(position.field, position.repetition + 1, 0),
+                    )
+                    .next()
+                    .is_none()
+            };
+            let mut path = label.clone();
+            path.push_str(&format!("-{}", position.field));
+            if position.repetition > 0 {
+                path.push_str(&format!("({})", position.repetition));
+            }
+            if !(single_component(&old_values) && single_component(&new_values)) {
+                path.push_str(&format!("-{}", position.component));
+            }
+
+            Some(Hl7Change {
+                kind,
+                segment: label.clone(),
+                path,
+                old_value,
+                new_value,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct ValuePosition {
+    field: usize,
+    repetition: usize,
+    component: usize,
+}
+
+impl ValuePosition {
+    fn new(field: usize, repetition: usize, component: usize) -> Self {
+        ValuePosition {
+            field,
+            repetition,
+            component,
+        }
+    }
+}
+
+fn segment_values(segment: &Hl7Segment, message: &Hl7Message) -> BTreeMap<ValuePosition, String> {
+    let delimiters = message.escape_handler.delimiters();
+    let is_verbatim = |field: usize| {
+        is_header_segment(&segment.segment_name)
+            && field <= message.msh_numbering.encoding_characters_field()
+    };
+
+    let mut values = BTreeMap::new();
+    for (&index, field) in &segment.fields {
+        if is_verbatim(index) {
+            values.insert(ValuePosition::new(index, 0, 1), field.value.clone());
+            continue;
+        }
+        for (repetition, components) in field.to_nested_json().iter().enumerate() {
+            for (component, subcomponents) in components.iter().enumerate() {
+                let value = subcomponents.join(&delimiters.subcomponent.to_string());
+                if !value.is_empty() {
+                    values.insert(ValuePosition::new(index, repetition, component + 1), value);
+                }
+            }
+        }
+    }
+    values
+}
+
+fn segment_text(segment: &Hl7Segment, message: &Hl7Message) -> String {
+    segment.to_er7(
+        &message.escape_handler,
+        message.msh_numbering,
+        message.unescaped,
+    )
+}
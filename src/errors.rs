@@ -1,4 +1,6 @@
+#[cfg(feature = "python")]
 use pyo3::exceptions::PyException;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 use thiserror::Error;
 
@@ -54,10 +56,10 @@ pub enum Hl7Error {
     #[error("Missing required segment: {segment}")]
     MissingRequiredSegment { segment: String },
 
-    #[error("Invalid segment name: {name} (must be exactly 3 alphabetic characters)")]
+    #[error("Invalid segment name: {name} (must be exactly 3 uppercase letters or digits, starting with a letter)")]
     InvalidSegmentName { name: String },
 
-    #[error("Invalid field separators: {separators} (must be exactly 4 characters)")]
+    #[error("Invalid field separators: {separators} (must be 4 characters, or 5 with a truncation character)")]
     InvalidFieldSeparators { separators: String },
 
     #[error("JSON parsing error: {0}")]
@@ -69,6 +71,25 @@ pub enum Hl7Error {
         supported_versions: String,
     },
 
+    #[error("HL7 {segment} count mismatch: declared {declared}, found {actual}")]
+    CountMismatch {
+        segment: String,
+        declared: usize,
+        actual: usize,
+    },
+
+    #[error("HL7 message {index}: {source}")]
+    MessageError { index: usize, source: Box<Hl7Error> },
+
+    #[error("HL7 transform rule {index}: {source}")]
+    TransformError { index: usize, source: Box<Hl7Error> },
+
+    #[error("HL7 unknown message structure: {0}")]
+    UnknownMessageStructure(String),
+
+    #[error("Invalid HL7 path {path}: {message}")]
+    InvalidPath { path: String, message: String },
+
     #[error("HL7 encoding error: {0}")]
     EncodingError(String),
 
@@ -76,6 +97,7 @@ pub enum Hl7Error {
     ConfigurationError(String),
 }
 
+#[cfg(feature = "python")]
 impl From<Hl7Error> for PyErr {
     fn from(error: Hl7Error) -> Self {
         PyException::new_err(error.to_string())
@@ -113,6 +135,27 @@ impl Hl7Error {
         }
     }
 
+    pub fn invalid_path(path: &str, message: &str) -> Self {
+        Hl7Error::InvalidPath {
+            path: path.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn in_message(self, index: usize) -> Self {
+        Hl7Error::MessageError {
+            index,
+            source: Box::new(self),
+        }
+    }
+
+    pub fn in_rule(self, index: usize) -> Self {
+        Hl7Error::TransformError {
+            index,
+            source: Box::new(self),
+        }
+    }
+
     pub fn unsupported_version(version: &str) -> Self {
         let supported_versions = [
             "2.1", "2.2", "2.3", "2.4", "2.5", "2.5.1", "2.6", "2.7", "2.8", "2.9",
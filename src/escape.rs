@@ -1,6 +1,46 @@
 use crate::errors::Hl7Error;
+use crate::segments::is_header_segment;
+use std::borrow::Cow;
 use std::collections::HashMap;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delimiters {
+    pub field: char,
+    pub component: char,
+    pub repetition: char,
+    pub escape: char,
+    pub subcomponent: char,
+}
+
+impl Default for Delimiters {
+    fn default() -> Self {
+        Self {
+            field: '|',
+            component: '^',
+            repetition: '~',
+            escape: '\\',
+            subcomponent: '&',
+        }
+    }
+}
+
+impl Delimiters {
+    pub fn encoding_characters(&self) -> [char; 4] {
+        [
+            self.component,
+            self.repetition,
+            self.escape,
+            self.subcomponent,
+        ]
+    }
+
+    pub fn contains_encoding_characters(&self, text: &str) -> bool {
+        let encoding_characters = self.encoding_characters();
+        text.char_indices()
+            .any(|(index, _)| text[index..].chars().take(4).eq(encoding_characters))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Hl7EscapeHandler {
     escape_sequences: HashMap<String, String>,
@@ -9,6 +49,7 @@ pub struct Hl7EscapeHandler {
     repetition_separator: char,
     escape_character: char,
     subcomponent_separator: char,
+    truncation_character: Option<char>,
 }
 
 impl Default for Hl7EscapeHandler {
@@ -45,18 +86,60 @@ impl Hl7EscapeHandler {
             repetition_separator,
             escape_character,
             subcomponent_separator,
+            truncation_character: None,
         }
     }
 
+    pub fn with_truncation_character(mut self, truncation_character: char) -> Self {
+        self.escape_sequences
+            .insert("P".to_string(), truncation_character.to_string());
+        self.truncation_character = Some(truncation_character);
+        self
+    }
+
     pub fn from_msh_field(field_1: &str) -> Result<Self, Hl7Error> {
-        if field_1.len() != 4 {
+        Self::from_encoding_characters('|', field_1)
+    }
+
+    pub fn from_encoding_characters(
+        field_separator: char,
+        encoding_characters: &str,
+    ) -> Result<Self, Hl7Error> {
+        let chars: Vec<char> = encoding_characters.chars().collect();
+        if !(4..=5).contains(&chars.len()) {
             return Err(Hl7Error::InvalidFieldSeparators {
-                separators: field_1.to_string(),
+                separators: encoding_characters.to_string(),
             });
         }
 
-        let chars: Vec<char> = field_1.chars().collect();
-        Ok(Self::new(chars[0], chars[1], chars[2], chars[3], '&'))
+        let handler = Self::new(field_separator, chars[0], chars[1], chars[2], chars[3]);
+        Ok(match chars.get(4) {
+            Some(&truncation_character) => handler.with_truncation_character(truncation_character),
+            None => handler,
+        })
+    }
+
+    pub fn from_msh_segment(segment_str: &str) -> Result<Self, Hl7Error> {
+        let header = segment_str.get(..3).unwrap_or_default();
+        if !is_header_segment(header) {
+            return Err(Hl7Error::InvalidSegment(segment_str.to_string()));
+        }
+
+        let mut chars = segment_str[3..].chars();
+        let field_separator = chars
+            .next()
+            .ok_or_else(|| Hl7Error::InvalidSegment(segment_str.to_string()))?;
+        let encoding_characters: String = chars.take_while(|ch| *ch != field_separator).collect();
+
+        Self::from_encoding_characters(field_separator, &encoding_characters)
+    }
+
+    pub fn unescape_cow<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if text.contains(self.escape_character) {
+            Cow::Owned(self.unescape(text))
+        } else {
+            Cow::Borrowed(text)
+        }
     }
 
     pub fn unescape(&self, text: &str) -> String {
@@ -81,16 +164,14 @@ impl Hl7EscapeHandler {
                             }
                         }
 
+                        let closed = chars.next_if_eq(&escape_char).is_some();
                         if let Some(replacement) = self.escape_sequences.get(&sequence) {
                             result.push_str(replacement);
                         } else {
                             result.push(escape_char);
                             result.push_str(&sequence);
-                        }
-
-                        if let Some(&ch) = chars.peek() {
-                            if ch == escape_char {
-                                chars.next();
+                            if closed {
+                                result.push(escape_char);
                             }
                         }
                     }
@@ -133,6 +214,12 @@ impl Hl7EscapeHandler {
                 }
                 c if c == escape_char => {
                     result.push(escape_char);
+                    result.push('E');
+                    result.push(escape_char);
+                }
+                c if Some(c) == self.truncation_character => {
+                    result.push(escape_char);
+                    result.push('P');
                     result.push(escape_char);
                 }
                 '\n' => {
@@ -145,16 +232,6 @@ impl Hl7EscapeHandler {
                     result.push_str("X0D");
                     result.push(escape_char);
                 }
-                ' ' => {
-                    result.push(escape_char);
-                    result.push_str("X20");
-                    result.push(escape_char);
-                }
-                '\t' => {
-                    result.push(escape_char);
-                    result.push_str("X09");
-                    result.push(escape_char);
-                }
                 _ => result.push(ch),
             }
         }
@@ -162,20 +239,26 @@ impl Hl7EscapeHandler {
         result
     }
 
-    pub fn parse_field_with_escaping(&self, field_text: &str) -> Vec<String> {
-        let unescaped = self.unescape(field_text);
-        unescaped
-            .split(self.component_separator)
-            .map(|s| s.to_string())
+    pub fn encoding_characters(&self) -> String {
+        self.delimiters()
+            .encoding_characters()
+            .iter()
+            .chain(&self.truncation_character)
             .collect()
     }
 
-    pub fn format_field_with_escaping(&self, components: &[String]) -> String {
-        components
-            .iter()
-            .map(|component| self.escape(component))
-            .collect::<Vec<_>>()
-            .join(&self.component_separator.to_string())
+    pub fn delimiters(&self) -> Delimiters {
+        Delimiters {
+            field: self.field_separator,
+            component: self.component_separator,
+            repetition: self.repetition_separator,
+            escape: self.escape_character,
+            subcomponent: self.subcomponent_separator,
+        }
+    }
+
+    pub fn get_field_separator(&self) -> char {
+        self.field_separator
     }
 
     pub fn get_component_separator(&self) -> char {
@@ -185,8 +268,30 @@ impl Hl7EscapeHandler {
     pub fn get_repetition_separator(&self) -> char {
         self.repetition_separator
     }
+
+    pub fn get_subcomponent_separator(&self) -> char {
+        self.subcomponent_separator
+    }
+
+    pub fn get_escape_character(&self) -> char {
+        self.escape_character
+    }
+
+    pub fn get_truncation_character(&self) -> Option<char> {
+        self.truncation_character
+    }
 }
 
 pub fn create_default_escape_handler() -> Hl7EscapeHandler {
     Hl7EscapeHandler::default()
 }
+
+pub fn create_escape_handler_for_message(hl7_string: &str) -> Result<Hl7EscapeHandler, Hl7Error> {
+    match hl7_string
+        .split(['\r', '\n'])
+        .find(|line| line.get(..3).is_some_and(is_header_segment))
+    {
+        Some(header) => Hl7EscapeHandler::from_msh_segment(header),
+        None => Ok(Hl7EscapeHandler::default()),
+    }
+}
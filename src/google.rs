@@ -0,0 +1,146 @@
+use crate::conversion::{json_to_hl7, SerializeOptions};
+use crate::definitions::field_repeatable;
+use crate::errors::Hl7Error;
+use crate::message::{Hl7Message, ParseOptions};
+use crate::segments::{is_header_segment, Hl7Segment, MshNumbering};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+const SET_ID_SEGMENTS: [&str; 17] = [
+    "AL1", "DG1", "FT1", "GT1", "IAM", "IN1", "NK1", "NTE", "OBR", "OBX", "PID", "PR1", "PV1",
+    "ROL", "SPM", "TQ1", "TXA",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GoogleParsedData {
+    #[serde(default)]
+    pub segments: Vec<GoogleSegment>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoogleSegment {
+    pub segment_id: String,
+    #[serde(default)]
+    pub set_id: String,
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleMessage {
+    parsed_data: GoogleParsedData,
+}
+
+impl GoogleParsedData {
+    pub fn from_message(message: &Hl7Message) -> Self {
+        GoogleParsedData {
+            segments: message
+                .segments
+                .iter()
+                .map(|segment| GoogleSegment::from_segment(segment, message.msh_numbering))
+                .collect(),
+        }
+    }
+
+    pub fn from_value(value: Value) -> Result<Self, Hl7Error> {
+        if value.get("parsedData").is_some() {
+            let message: GoogleMessage = serde_json::from_value(value)?;
+            Ok(message.parsed_data)
+        } else {
+            Ok(serde_json::from_value(value)?)
+        }
+    }
+
+    pub fn to_json(&self) -> Vec<BTreeMap<String, String>> {
+        self.segments.iter().map(GoogleSegment::to_json).collect()
+    }
+
+    pub fn to_hl7(&self, options: &SerializeOptions) -> String {
+        let options = options.with_msh_numbering(MshNumbering::Legacy);
+        json_to_hl7(&self.to_json(), &options)
+    }
+}
+
+impl GoogleSegment {
+    pub fn from_segment(segment: &Hl7Segment, numbering: MshNumbering) -> Self {
+        let is_standard_header =
+            numbering == MshNumbering::Standard && is_header_segment(&segment.segment_name);
+        let legacy_segment = Hl7Segment {
+            segment_name: segment.segment_name.clone(),
+            fields: segment
+                .fields
+                .iter()
+                .filter(|(index, _)| !is_standard_header || **index > 1)
+                .map(|(index, field)| {
+                    let index = if is_standard_header {
+                        index - 1
+                    } else {
+                        *index
+                    };
+                    (index, field.clone())
+                })
+                .collect(),
+        };
+
+        let is_header = is_header_segment(&segment.segment_name);
+        let mut fields: BTreeMap<String, String> = legacy_segment
+            .to_json()
+            .into_iter()
+            .filter(|(key, value)| key != "segment_name" && !value.is_empty())
+            .map(|(key, value)| {
+                if is_header || key.contains('[') {
+                    return (key, value);
+                }
+                let (field, rest) = key.split_at(key.find('.').unwrap_or(key.len()));
+                let is_repeatable = field
+                    .parse()
+                    .ok()
+                    .and_then(|field| field_repeatable(&segment.segment_name, field));
+                if is_repeatable.unwrap_or(!rest.is_empty()) {
+                    (format!("{}[0]{}", field, rest), value)
+                } else {
+                    (key, value)
+                }
+            })
+            .collect();
+        fields.insert("0".to_string(), segment.segment_name.clone());
+
+        let set_id = if SET_ID_SEGMENTS.contains(&segment.segment_name.as_str()) {
+            legacy_segment
+                .fields
+                .get(&1)
+                .map(|field| field.value.clone())
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+
+        GoogleSegment {
+            segment_id: segment.segment_name.clone(),
+            set_id,
+            fields,
+        }
+    }
+
+    pub fn to_json(&self) -> BTreeMap<String, String> {
+        let mut json: BTreeMap<String, String> = self
+            .fields
+            .iter()
+            .filter(|(key, _)| key.as_str() != "0")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        json.insert("segment_name".to_string(), self.segment_id.clone());
+        json
+    }
+}
+
+pub fn hl7_to_google_parsed_data(
+    hl7_string: &str,
+    options: &ParseOptions,
+) -> Result<GoogleParsedData, Hl7Error> {
+    let message = Hl7Message::parse(hl7_string, options)?;
+    Ok(GoogleParsedData::from_message(&message))
+}
@@ -1,13 +1,41 @@
+use crate::batch::Hl7BatchFile;
+#[cfg(feature = "arrow")]
+use crate::columnar::{self, SegmentTables};
+use crate::diff::MessageDiff;
 use crate::errors::Hl7Error;
-use crate::escape::{create_default_escape_handler, Hl7EscapeHandler};
-use crate::segments;
+use crate::escape::{create_escape_handler_for_message, Hl7EscapeHandler};
+use crate::google::GoogleParsedData;
+use crate::message::{self, Hl7Message, ParseOptions};
+use crate::mutable::Hl7MutableMessage;
+use crate::py_json;
+use crate::query::{QueryOperator, SegmentQuery};
+use crate::segments::{self, Hl7NestedSegment};
+#[cfg(feature = "streaming")]
+use crate::streaming::{MessageReader, NdjsonWriter};
 use crate::utils;
+#[cfg(feature = "validation")]
 use crate::validation::Hl7Validator;
+#[cfg(feature = "xml")]
+use crate::xml;
+#[cfg(feature = "arrow")]
+use arrow::array::{RecordBatch, RecordBatchIterator};
+#[cfg(feature = "arrow")]
+use arrow::ffi::FFI_ArrowSchema;
+#[cfg(feature = "arrow")]
+use arrow::ffi_stream::FFI_ArrowArrayStream;
 use pyo3::prelude::*;
-use pyo3::types::PyType;
+#[cfg(feature = "arrow")]
+use pyo3::types::PyCapsule;
+#[cfg(feature = "streaming")]
+use pyo3::types::{PyBytes, PyString};
+use pyo3::types::{PyDict, PyType};
 use pyo3::PyResult;
 use std::collections::BTreeMap;
 use std::fs;
+#[cfg(feature = "streaming")]
+use std::fs::File;
+#[cfg(feature = "streaming")]
+use std::io::{self, BufReader, Read};
 
 #[pyclass]
 pub struct Hl7Json {
@@ -19,36 +47,44 @@ pub struct Hl7Json {
     pub strict_validation: bool,
     #[pyo3(get, set)]
     pub escaping_enabled: bool,
+    #[pyo3(get, set)]
+    pub standard_msh_numbering: bool,
     pub escape_handler: Hl7EscapeHandler,
 }
 
 #[pymethods]
 impl Hl7Json {
     #[new]
-    #[pyo3(signature = (hl7_string, validation_enabled=None, strict_validation=None, escaping_enabled=None))]
+    #[pyo3(signature = (hl7_string, validation_enabled=None, strict_validation=None, escaping_enabled=None, standard_msh_numbering=None))]
     pub fn new(
         hl7_string: String,
         validation_enabled: Option<bool>,
         strict_validation: Option<bool>,
         escaping_enabled: Option<bool>,
+        standard_msh_numbering: Option<bool>,
     ) -> Self {
+        let hl7_string = utils::replace_eof(hl7_string);
+        let escape_handler = create_escape_handler_for_message(&hl7_string).unwrap_or_default();
+
         Hl7Json {
-            hl7_string: utils::replace_eof(hl7_string),
+            hl7_string,
             validation_enabled: validation_enabled.unwrap_or(false),
             strict_validation: strict_validation.unwrap_or(false),
             escaping_enabled: escaping_enabled.unwrap_or(true),
-            escape_handler: create_default_escape_handler(),
+            standard_msh_numbering: standard_msh_numbering.unwrap_or(false),
+            escape_handler,
         }
     }
 
     #[classmethod]
-    #[pyo3(signature = (path, validation_enabled=None, strict_validation=None, escaping_enabled=None))]
+    #[pyo3(signature = (path, validation_enabled=None, strict_validation=None, escaping_enabled=None, standard_msh_numbering=None))]
     fn from_file(
         _cls: &Bound<PyType>,
         path: String,
         validation_enabled: Option<bool>,
         strict_validation: Option<bool>,
         escaping_enabled: Option<bool>,
+        standard_msh_numbering: Option<bool>,
     ) -> PyResult<Self> {
         let contents = fs::read_to_string(&path).map_err(Hl7Error::IoError)?;
 
@@ -56,22 +92,102 @@ impl Hl7Json {
             return Err(Hl7Error::EmptyMessage.into());
         }
 
-        Ok(Hl7Json {
-            hl7_string: utils::replace_eof(contents),
-            validation_enabled: validation_enabled.unwrap_or(false),
-            strict_validation: strict_validation.unwrap_or(false),
-            escaping_enabled: escaping_enabled.unwrap_or(true),
-            escape_handler: create_default_escape_handler(),
+        Ok(Hl7Json::new(
+            contents,
+            validation_enabled,
+            strict_validation,
+            escaping_enabled,
+            standard_msh_numbering,
+        ))
+    }
+
+    #[cfg(feature = "xml")]
+    #[classmethod]
+    #[pyo3(signature = (xml, validation_enabled=None, strict_validation=None, escaping_enabled=None, standard_msh_numbering=None))]
+    fn from_xml(
+        _cls: &Bound<PyType>,
+        xml: &str,
+        validation_enabled: Option<bool>,
+        strict_validation: Option<bool>,
+        escaping_enabled: Option<bool>,
+        standard_msh_numbering: Option<bool>,
+    ) -> PyResult<Self> {
+        Ok(Hl7Json::new(
+            xml::xml_to_hl7(xml)?,
+            validation_enabled,
+            strict_validation,
+            escaping_enabled,
+            standard_msh_numbering,
+        ))
+    }
+
+    #[cfg(feature = "streaming")]
+    #[classmethod]
+    #[pyo3(signature = (path, validation_enabled=None, strict_validation=None, escaping_enabled=None, standard_msh_numbering=None))]
+    fn iter_file(
+        _cls: &Bound<PyType>,
+        path: String,
+        validation_enabled: Option<bool>,
+        strict_validation: Option<bool>,
+        escaping_enabled: Option<bool>,
+        standard_msh_numbering: Option<bool>,
+    ) -> PyResult<Hl7JsonFileIterator> {
+        let reader = MessageReader::open(&path, ParseOptions::default())?;
+
+        Ok(Hl7JsonFileIterator {
+            reader,
+            validation_enabled,
+            strict_validation,
+            escaping_enabled,
+            standard_msh_numbering,
         })
     }
 
+    #[classmethod]
+    #[pyo3(signature = (text, validation_enabled=None, strict_validation=None, escaping_enabled=None, standard_msh_numbering=None))]
+    fn split_messages(
+        _cls: &Bound<PyType>,
+        text: &str,
+        validation_enabled: Option<bool>,
+        strict_validation: Option<bool>,
+        escaping_enabled: Option<bool>,
+        standard_msh_numbering: Option<bool>,
+    ) -> PyResult<Vec<Hl7Json>> {
+        if text.trim().is_empty() {
+            return Err(Hl7Error::EmptyMessage.into());
+        }
+
+        Ok(message::split_messages(text)
+            .into_iter()
+            .map(|hl7_string| {
+                Hl7Json::new(
+                    hl7_string.to_string(),
+                    validation_enabled,
+                    strict_validation,
+                    escaping_enabled,
+                    standard_msh_numbering,
+                )
+            })
+            .collect())
+    }
+
     pub fn _split_hl7_seg_to_json(&self, seg: &str) -> BTreeMap<String, String> {
-        let segment = segments::Hl7Segment::from_string(seg, None);
+        let segment = segments::Hl7Segment::parse_with_numbering(
+            seg,
+            &self.escape_handler,
+            false,
+            self.msh_numbering(),
+        );
         segment.to_json()
     }
 
     pub fn _split_hl7_seg_to_json_with_escaping(&self, seg: &str) -> BTreeMap<String, String> {
-        let segment = segments::Hl7Segment::from_string(seg, Some(&self.escape_handler));
+        let segment = segments::Hl7Segment::parse_with_numbering(
+            seg,
+            &self.escape_handler,
+            true,
+            self.msh_numbering(),
+        );
         segment.to_json()
     }
 
@@ -80,21 +196,87 @@ impl Hl7Json {
             self.validate(Some(self.strict_validation), Some(false))?;
         }
 
-        let mut message_json: Vec<BTreeMap<String, String>> = Vec::new();
-        for seg in utils::split_segments(self.hl7_string.clone()) {
-            let seg_json = if self.escaping_enabled {
-                self._split_hl7_seg_to_json_with_escaping(&seg)
-            } else {
-                self._split_hl7_seg_to_json(&seg)
-            };
-            message_json.push(seg_json);
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let message = Hl7Message::parse(&self.hl7_string, &options)?;
+        Ok(message.to_json())
+    }
+
+    #[getter]
+    fn hl7_json<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        py_json::segments_to_py(py, &self._convert_hl7_to_json()?)
+    }
+
+    #[getter]
+    fn hl7_typed_json<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        if self.validation_enabled {
+            self.validate(Some(self.strict_validation), Some(false))?;
+        }
+
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let message = Hl7Message::parse(&self.hl7_string, &options)?;
+        py_json::segments_to_py(py, &message.to_typed_json())
+    }
+
+    #[getter]
+    fn hl7_nested_json(&self) -> PyResult<Vec<Hl7NestedSegment>> {
+        if self.validation_enabled {
+            self.validate(Some(self.strict_validation), Some(false))?;
+        }
+
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let message = Hl7Message::parse(&self.hl7_string, &options)?;
+        Ok(message.to_nested_json())
+    }
+
+    #[getter]
+    fn hl7_grouped_json<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        if self.validation_enabled {
+            self.validate(Some(self.strict_validation), Some(false))?;
         }
-        Ok(message_json)
+
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let message = Hl7Message::parse(&self.hl7_string, &options)?;
+        let json = serde_json::to_value(message.to_grouped_json()?).map_err(Hl7Error::from)?;
+        py_json::value_to_py(py, &json)
     }
 
+    #[cfg(feature = "xml")]
     #[getter]
-    fn hl7_json(&self) -> PyResult<Vec<BTreeMap<String, String>>> {
-        self._convert_hl7_to_json()
+    fn hl7_xml(&self) -> PyResult<String> {
+        if self.validation_enabled {
+            self.validate(Some(self.strict_validation), Some(false))?;
+        }
+
+        Ok(xml::hl7_to_xml(&self.hl7_string)?)
+    }
+
+    #[getter]
+    fn google_parsed_data<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        if self.validation_enabled {
+            self.validate(Some(self.strict_validation), Some(false))?;
+        }
+
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let message = Hl7Message::parse(&self.hl7_string, &options)?;
+        let parsed_data = GoogleParsedData::from_message(&message);
+        let json = serde_json::to_value(parsed_data).map_err(Hl7Error::from)?;
+        py_json::value_to_py(py, &json)
+    }
+
+    #[getter]
+    fn hl7_batch_json<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let batch_file = Hl7BatchFile::parse(&self.hl7_string, &options)?;
+
+        if self.validation_enabled {
+            for (index, message) in batch_file.messages().enumerate() {
+                self.validate_message(message, Some(self.strict_validation), Some(false))
+                    .map_err(|e| e.in_message(index))?;
+            }
+        }
+
+        let json = serde_json::to_value(batch_file.to_json()).map_err(Hl7Error::from)?;
+        py_json::value_to_py(py, &json)
     }
 
     pub fn validate(
@@ -102,11 +284,173 @@ impl Hl7Json {
         strict_mode: Option<bool>,
         validate_required_fields: Option<bool>,
     ) -> PyResult<()> {
-        let segments: Vec<segments::Hl7Segment> = utils::split_segments(self.hl7_string.clone())
+        let options = self.parse_options().with_unescaping(false);
+        let message = Hl7Message::parse(&self.hl7_string, &options)?;
+
+        Ok(self.validate_message(&message, strict_mode, validate_required_fields)?)
+    }
+
+    fn get(&self, path: &str) -> PyResult<Option<String>> {
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let message = Hl7Message::parse(&self.hl7_string, &options)?;
+        Ok(message.get(path)?)
+    }
+
+    fn get_all(&self, path: &str) -> PyResult<Vec<String>> {
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let message = Hl7Message::parse(&self.hl7_string, &options)?;
+        Ok(message.get_all(path)?)
+    }
+
+    fn set(&mut self, path: &str, value: &str) -> PyResult<()> {
+        let mut message = Hl7MutableMessage::parse(&self.hl7_string, self.msh_numbering())?;
+        if self.escaping_enabled {
+            message.set(path, value)?;
+        } else {
+            message.set_encoded(path, value)?;
+        }
+        self.hl7_string = message.to_er7();
+        Ok(())
+    }
+
+    fn diff<'py>(&self, py: Python<'py>, other: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let diff = self.message_diff(other)?;
+        let json = serde_json::to_value(&diff.changes).map_err(Hl7Error::from)?;
+        py_json::value_to_py(py, &json)
+    }
+
+    fn diff_text(&self, other: &Bound<'_, PyAny>) -> PyResult<String> {
+        Ok(self.message_diff(other)?.to_unified_text())
+    }
+
+    #[pyo3(signature = (segment_name, conditions=None, select=None))]
+    fn query<'py>(
+        &self,
+        py: Python<'py>,
+        segment_name: &str,
+        conditions: Option<&Bound<'py, PyAny>>,
+        select: Option<Vec<String>>,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let mut query = SegmentQuery::new(segment_name);
+        if let Some(conditions) = conditions {
+            if let Ok(equals) = conditions.extract::<BTreeMap<String, String>>() {
+                for (key, value) in &equals {
+                    query = query.with_equals(key, value);
+                }
+            } else {
+                for (key, operator, value) in
+                    conditions.extract::<Vec<(String, String, String)>>()?
+                {
+                    query = query.with_condition(&key, QueryOperator::parse(&operator)?, &value);
+                }
+            }
+        }
+
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let message = Hl7Message::parse(&self.hl7_string, &options)?;
+        match select {
+            None => py_json::segments_to_py(py, &query.select_json(&message)?),
+            Some(keys) => {
+                let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+                query
+                    .project(&message, &keys)?
+                    .into_iter()
+                    .map(|row| {
+                        let dict = PyDict::new(py);
+                        for key in &keys {
+                            dict.set_item(key, row.get(*key).cloned().flatten())?;
+                        }
+                        Ok(dict)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    #[getter]
+    fn hl7_messages_json<'py>(&self, py: Python<'py>) -> PyResult<Vec<Vec<Bound<'py, PyDict>>>> {
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let messages = message::parse_messages(&self.hl7_string, &options)?;
+
+        if self.validation_enabled {
+            let validation_options = self.parse_options().with_unescaping(false);
+            let unescaped = message::parse_messages(&self.hl7_string, &validation_options)?;
+            for (index, message) in unescaped.iter().enumerate() {
+                self.validate_message(message, Some(self.strict_validation), Some(false))
+                    .map_err(|e| e.in_message(index))?;
+            }
+        }
+
+        messages
             .iter()
-            .map(|seg| segments::Hl7Segment::from_string(seg, None))
-            .collect();
+            .map(|message| py_json::segments_to_py(py, &message.to_json()))
+            .collect()
+    }
 
+    #[cfg(feature = "arrow")]
+    #[getter]
+    fn arrow_tables(&self) -> PyResult<BTreeMap<String, Hl7ArrowTable>> {
+        Ok(self
+            .record_batches()?
+            .into_iter()
+            .map(|(name, batch)| (name, Hl7ArrowTable { batch }))
+            .collect())
+    }
+
+    #[cfg(feature = "arrow")]
+    fn write_parquet(&self, directory: &str) -> PyResult<()> {
+        let batches = self.record_batches()?;
+        Ok(columnar::write_parquet_dir(
+            &batches,
+            std::path::Path::new(directory),
+        )?)
+    }
+}
+
+impl Hl7Json {
+    #[cfg(feature = "arrow")]
+    fn record_batches(&self) -> PyResult<BTreeMap<String, RecordBatch>> {
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let messages = message::parse_messages(&self.hl7_string, &options)?;
+
+        let mut tables = SegmentTables::new();
+        for (index, message) in messages.iter().enumerate() {
+            if self.validation_enabled {
+                self.validate_message(message, Some(self.strict_validation), Some(false))
+                    .map_err(|e| e.in_message(index))?;
+            }
+            tables.add_message(message);
+        }
+        Ok(tables.finish()?)
+    }
+
+    fn message_diff(&self, other: &Bound<'_, PyAny>) -> PyResult<MessageDiff> {
+        let other_string = match other.extract::<PyRef<Hl7Json>>() {
+            Ok(other) => other.hl7_string.clone(),
+            Err(_) => utils::replace_eof(other.extract::<String>()?),
+        };
+
+        let options = self.parse_options().with_unescaping(self.escaping_enabled);
+        let old = Hl7Message::parse(&self.hl7_string, &options)?;
+        let new = Hl7Message::parse(&other_string, &options)?;
+        Ok(MessageDiff::between(&old, &new))
+    }
+
+    fn msh_numbering(&self) -> segments::MshNumbering {
+        segments::MshNumbering::from_flag(self.standard_msh_numbering)
+    }
+
+    fn parse_options(&self) -> ParseOptions {
+        ParseOptions::new().with_msh_numbering(self.msh_numbering())
+    }
+
+    #[cfg(feature = "validation")]
+    fn validate_message(
+        &self,
+        message: &Hl7Message,
+        strict_mode: Option<bool>,
+        validate_required_fields: Option<bool>,
+    ) -> Result<(), Hl7Error> {
         let use_strict_mode = strict_mode.unwrap_or(self.strict_validation);
         let use_required_fields_validation = validate_required_fields.unwrap_or(true);
 
@@ -114,6 +458,181 @@ impl Hl7Json {
             .with_strict_mode(use_strict_mode)
             .with_required_fields_validation(use_required_fields_validation);
 
-        validator.validate_message(&segments).map_err(|e| e.into())
+        message.validate(&validator)
+    }
+
+    #[cfg(not(feature = "validation"))]
+    fn validate_message(
+        &self,
+        _message: &Hl7Message,
+        _strict_mode: Option<bool>,
+        _validate_required_fields: Option<bool>,
+    ) -> Result<(), Hl7Error> {
+        Err(Hl7Error::ConfigurationError(
+            "hl7conv2 was built without the validation feature".to_string(),
+        ))
+    }
+}
+
+#[cfg(feature = "streaming")]
+#[pyclass]
+pub struct Hl7JsonFileIterator {
+    reader: MessageReader<BufReader<File>>,
+    validation_enabled: Option<bool>,
+    strict_validation: Option<bool>,
+    escaping_enabled: Option<bool>,
+    standard_msh_numbering: Option<bool>,
+}
+
+#[cfg(feature = "streaming")]
+#[pymethods]
+impl Hl7JsonFileIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    pub fn __next__(&mut self) -> PyResult<Option<Hl7Json>> {
+        let message = self.reader.next_raw()?;
+
+        Ok(message.map(|hl7_string| {
+            Hl7Json::new(
+                hl7_string,
+                self.validation_enabled,
+                self.strict_validation,
+                self.escaping_enabled,
+                self.standard_msh_numbering,
+            )
+        }))
+    }
+}
+
+#[cfg(feature = "streaming")]
+struct PyStreamReader {
+    stream: Py<PyAny>,
+    chunk: Vec<u8>,
+    offset: usize,
+}
+
+#[cfg(feature = "streaming")]
+impl Read for PyStreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.offset >= self.chunk.len() {
+            self.chunk = Python::attach(|py| -> PyResult<Vec<u8>> {
+                let chunk = self.stream.call_method1(py, "read", (buf.len(),))?;
+                let chunk = chunk.bind(py);
+                match chunk.cast::<PyString>() {
+                    Ok(text) => Ok(text.to_str()?.as_bytes().to_vec()),
+                    Err(_) => Ok(chunk.cast::<PyBytes>()?.as_bytes().to_vec()),
+                }
+            })
+            .map_err(io::Error::other)?;
+            self.offset = 0;
+        }
+
+        let length = buf.len().min(self.chunk.len() - self.offset);
+        buf[..length].copy_from_slice(&self.chunk[self.offset..self.offset + length]);
+        self.offset += length;
+        Ok(length)
+    }
+}
+
+#[cfg(feature = "streaming")]
+#[pyfunction]
+#[pyo3(signature = (source, output_path, validation_enabled=None, strict_validation=None, escaping_enabled=None, standard_msh_numbering=None))]
+pub fn hl7_to_ndjson<'py>(
+    py: Python<'py>,
+    source: &Bound<'py, PyAny>,
+    output_path: &str,
+    validation_enabled: Option<bool>,
+    strict_validation: Option<bool>,
+    escaping_enabled: Option<bool>,
+    standard_msh_numbering: Option<bool>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let numbering = segments::MshNumbering::from_flag(standard_msh_numbering.unwrap_or(false));
+    let options = ParseOptions::new()
+        .with_unescaping(escaping_enabled.unwrap_or(true))
+        .with_msh_numbering(numbering);
+
+    let input: Box<dyn Read + Send> = if source.hasattr("read")? {
+        Box::new(PyStreamReader {
+            stream: source.clone().unbind(),
+            chunk: Vec::new(),
+            offset: 0,
+        })
+    } else {
+        let path: std::path::PathBuf = source.extract()?;
+        Box::new(File::open(path).map_err(Hl7Error::from)?)
+    };
+
+    let mut writer = NdjsonWriter::create(output_path)?;
+    #[cfg(feature = "validation")]
+    if validation_enabled.unwrap_or(false) {
+        writer = writer.with_validator(
+            Hl7Validator::new()
+                .with_strict_mode(strict_validation.unwrap_or(false))
+                .with_required_fields_validation(false),
+        );
+    }
+    #[cfg(not(feature = "validation"))]
+    let _ = (validation_enabled, strict_validation);
+
+    let summary = py.detach(|| {
+        let mut reader = MessageReader::new(BufReader::new(input), options);
+        writer.write_reader(&mut reader)
+    })?;
+
+    let json = serde_json::to_value(summary).map_err(Hl7Error::from)?;
+    py_json::value_to_py(py, &json)
+}
+
+#[cfg(feature = "arrow")]
+#[pyclass]
+pub struct Hl7ArrowTable {
+    batch: RecordBatch,
+}
+
+#[cfg(feature = "arrow")]
+#[pymethods]
+impl Hl7ArrowTable {
+    #[getter]
+    fn num_rows(&self) -> usize {
+        self.batch.num_rows()
+    }
+
+    #[getter]
+    fn column_names(&self) -> Vec<String> {
+        self.batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.batch.num_rows()
+    }
+
+    fn __arrow_c_schema__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyCapsule>> {
+        let schema = FFI_ArrowSchema::try_from(self.batch.schema().as_ref())
+            .map_err(|e| Hl7Error::EncodingError(e.to_string()))?;
+        PyCapsule::new(py, schema, Some(c"arrow_schema".to_owned()))
+    }
+
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyCapsule>> {
+        let _ = requested_schema;
+        let reader = RecordBatchIterator::new([Ok(self.batch.clone())], self.batch.schema());
+        let stream = FFI_ArrowArrayStream::new(Box::new(reader));
+        PyCapsule::new(py, stream, Some(c"arrow_array_stream".to_owned()))
+    }
+
+    fn write_parquet(&self, path: &str) -> PyResult<()> {
+        let file = fs::File::create(path).map_err(Hl7Error::from)?;
+        Ok(columnar::write_parquet(&self.batch, file)?)
     }
 }
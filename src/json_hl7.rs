@@ -1,42 +1,153 @@
+use crate::batch::Hl7BatchFileJson;
+use crate::conversion::{self, SerializeOptions};
 use crate::errors::Hl7Error;
-use crate::escape::{create_default_escape_handler, Hl7EscapeHandler};
+use crate::escape::Hl7EscapeHandler;
+use crate::google::GoogleParsedData;
+use crate::py_json;
+use crate::schema;
+use crate::segments::{Hl7NestedSegment, MshNumbering, TypedSegmentJson};
+#[cfg(feature = "xml")]
+use crate::xml;
 use pyo3::prelude::*;
 use pyo3::types::PyType;
 use pyo3::PyResult;
+use serde::Deserialize;
+use serde_json::Value;
 use std::collections::BTreeMap;
 
+#[derive(FromPyObject, Deserialize)]
+#[serde(untagged)]
+pub enum JsonInput {
+    Flat(Vec<BTreeMap<String, String>>),
+    Typed(Vec<TypedSegmentJson>),
+    Nested(Vec<Hl7NestedSegment>),
+}
+
+impl JsonInput {
+    pub fn into_flat(self) -> Vec<BTreeMap<String, String>> {
+        match self {
+            JsonInput::Flat(json_data) => json_data,
+            JsonInput::Typed(json_data) => json_data
+                .iter()
+                .map(conversion::typed_segment_to_flat_json)
+                .collect(),
+            JsonInput::Nested(json_data) => json_data
+                .iter()
+                .map(Hl7NestedSegment::to_flat_json)
+                .collect(),
+        }
+    }
+}
+
 #[pyclass]
 pub struct JsonHl7 {
     #[pyo3(get)]
     pub json_data: Vec<BTreeMap<String, String>>,
+    #[pyo3(get)]
+    pub standard_msh_numbering: bool,
+    #[pyo3(get)]
+    pub strict_keys: bool,
+    #[pyo3(get)]
+    pub key_warnings: Vec<String>,
     pub escape_handler: Hl7EscapeHandler,
 }
 
 #[pymethods]
 impl JsonHl7 {
     #[new]
-    pub fn new(json_data: Vec<BTreeMap<String, String>>) -> Self {
-        JsonHl7 {
+    #[pyo3(signature = (json_data, standard_msh_numbering=None, strict_keys=None, validate_schema=None))]
+    fn py_new(
+        json_data: &Bound<PyAny>,
+        standard_msh_numbering: Option<bool>,
+        strict_keys: Option<bool>,
+        validate_schema: Option<bool>,
+    ) -> PyResult<Self> {
+        let json_data = py_json::py_to_value(json_data)?;
+        Ok(JsonHl7::from_value(
             json_data,
-            escape_handler: create_default_escape_handler(),
-        }
+            standard_msh_numbering,
+            strict_keys,
+            validate_schema,
+        )?)
     }
 
     #[classmethod]
-    pub fn from_file(_cls: &Bound<PyType>, path: String) -> PyResult<Self> {
+    #[pyo3(signature = (path, standard_msh_numbering=None, strict_keys=None, validate_schema=None))]
+    pub fn from_file(
+        _cls: &Bound<PyType>,
+        path: String,
+        standard_msh_numbering: Option<bool>,
+        strict_keys: Option<bool>,
+        validate_schema: Option<bool>,
+    ) -> PyResult<Self> {
         let contents = std::fs::read_to_string(&path).map_err(Hl7Error::IoError)?;
 
         if contents.trim().is_empty() {
             return Err(Hl7Error::EmptyMessage.into());
         }
 
-        let json_data: Vec<BTreeMap<String, String>> = serde_json::from_str(&contents)
+        let json_data: Value = serde_json::from_str(&contents)
             .map_err(|e| Hl7Error::InvalidFormat(format!("Invalid JSON: {}", e)))?;
 
-        Ok(JsonHl7 {
+        Ok(JsonHl7::from_value(
             json_data,
-            escape_handler: create_default_escape_handler(),
-        })
+            standard_msh_numbering,
+            strict_keys,
+            validate_schema,
+        )?)
+    }
+
+    #[classmethod]
+    pub fn from_google_parsed_data(
+        _cls: &Bound<PyType>,
+        parsed_data: &Bound<PyAny>,
+    ) -> PyResult<Self> {
+        let parsed_data = GoogleParsedData::from_value(py_json::py_to_value(parsed_data)?)?;
+        Ok(JsonHl7::new(parsed_data.to_json(), Some(false)))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (batch_json, standard_msh_numbering=None, escape=None))]
+    pub fn batch_json_to_hl7(
+        batch_json: &Bound<PyAny>,
+        standard_msh_numbering: Option<bool>,
+        escape: Option<bool>,
+    ) -> PyResult<String> {
+        let batch_json: Hl7BatchFileJson =
+            serde_json::from_value(py_json::py_to_value(batch_json)?).map_err(Hl7Error::from)?;
+        let options = SerializeOptions::new()
+            .with_escaping(escape.unwrap_or(true))
+            .with_msh_numbering(MshNumbering::from_flag(
+                standard_msh_numbering.unwrap_or(false),
+            ));
+
+        Ok(batch_json.to_hl7(&options))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (standard_msh_numbering=None))]
+    pub fn json_schema<'py>(
+        py: Python<'py>,
+        standard_msh_numbering: Option<bool>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let numbering = MshNumbering::from_flag(standard_msh_numbering.unwrap_or(false));
+        py_json::value_to_py(py, &schema::json_schema(numbering))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (segment_name, standard_msh_numbering=None))]
+    pub fn segment_json_schema<'py>(
+        py: Python<'py>,
+        segment_name: &str,
+        standard_msh_numbering: Option<bool>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let numbering = MshNumbering::from_flag(standard_msh_numbering.unwrap_or(false));
+        py_json::value_to_py(py, &schema::segment_json_schema(segment_name, numbering))
+    }
+
+    #[pyo3(name = "validate_schema")]
+    fn py_validate_schema(&self) -> PyResult<()> {
+        Ok(self.validate_schema()?)
     }
 
     #[getter]
@@ -49,6 +160,12 @@ impl JsonHl7 {
         Ok(self._convert_json_to_hl7(false))
     }
 
+    #[cfg(feature = "xml")]
+    #[getter]
+    fn hl7_xml(&self) -> PyResult<String> {
+        Ok(xml::hl7_to_xml(&self._convert_json_to_hl7(true))?)
+    }
+
     pub fn _convert_json_to_hl7(&self, escape: bool) -> String {
         let mut hl7_segments = Vec::new();
 
@@ -62,130 +179,78 @@ impl JsonHl7 {
 }
 
 impl JsonHl7 {
-    pub fn _convert_segment_json_to_hl7(
-        &self,
-        segment_json: &BTreeMap<String, String>,
-        escape: bool,
-    ) -> String {
-        let mut fields = Vec::new();
-
-        if let Some(segment_name) = segment_json.get("segment_name") {
-            fields.push(segment_name.clone());
+    pub fn new(
+        json_data: Vec<BTreeMap<String, String>>,
+        standard_msh_numbering: Option<bool>,
+    ) -> Self {
+        let standard_msh_numbering = standard_msh_numbering.unwrap_or(false);
+        let escape_handler = conversion::escape_handler_for_json(
+            &json_data,
+            MshNumbering::from_flag(standard_msh_numbering),
+        );
+        JsonHl7 {
+            json_data,
+            standard_msh_numbering,
+            strict_keys: false,
+            key_warnings: Vec::new(),
+            escape_handler,
         }
+    }
 
-        let mut field_map: BTreeMap<usize, BTreeMap<usize, BTreeMap<usize, String>>> =
-            BTreeMap::new();
+    pub fn from_value(
+        mut json_data: Value,
+        standard_msh_numbering: Option<bool>,
+        strict_keys: Option<bool>,
+        validate_schema: Option<bool>,
+    ) -> Result<Self, Hl7Error> {
+        let strict_keys = strict_keys.unwrap_or(false);
+        if validate_schema.unwrap_or(false) {
+            let numbering = MshNumbering::from_flag(standard_msh_numbering.unwrap_or(false));
+            schema::validate_json_schema(&json_data, &schema::json_schema(numbering))?;
+        }
+        let mut findings = Vec::new();
 
-        let process_value = |value: &str| -> String {
-            if escape {
-                self.escape_handler.escape(value)
-            } else {
-                value.to_string()
-            }
-        };
+        if let Value::Array(segments) = &mut json_data {
+            for (index, segment) in segments.iter_mut().enumerate() {
+                let Value::Object(segment_json) = segment else {
+                    continue;
+                };
+                if segment_json.get("fields").is_some_and(Value::is_array) {
+                    continue;
+                }
 
-        for (key, value) in segment_json {
-            if key == "segment_name" {
-                continue;
+                findings.extend(conversion::check_json_segment(index, segment_json));
+                segment_json.retain(|_, value| value.is_string() || value.is_null());
             }
+        }
 
-            if let Ok(field_index) = key.parse::<usize>() {
-                let processed_value = process_value(value);
-                field_map
-                    .entry(field_index)
-                    .or_default()
-                    .entry(0)
-                    .or_default()
-                    .insert(0, processed_value);
-            } else if key.contains('[') && key.contains(']') {
-                if let Some(bracket_start) = key.find('[') {
-                    if let Some(bracket_end) = key.find(']') {
-                        let field_part = &key[..bracket_start];
-                        let rep_part = &key[bracket_start + 1..bracket_end];
-                        let component_part = &key[bracket_end + 1..];
-
-                        if let (Ok(field_index), Ok(rep_index)) =
-                            (field_part.parse::<usize>(), rep_part.parse::<usize>())
-                        {
-                            if let Some(component_str) = component_part.strip_prefix('.') {
-                                if let Ok(component_index) = component_str.parse::<usize>() {
-                                    let processed_value = process_value(value);
-                                    field_map
-                                        .entry(field_index)
-                                        .or_default()
-                                        .entry(rep_index)
-                                        .or_default()
-                                        .insert(component_index, processed_value);
-                                }
-                            } else {
-                                let processed_value = process_value(value);
-                                field_map
-                                    .entry(field_index)
-                                    .or_default()
-                                    .entry(rep_index)
-                                    .or_default()
-                                    .insert(0, processed_value);
-                            }
-                        }
-                    }
-                }
-            } else if key.contains('.') {
-                let parts: Vec<&str> = key.split('.').collect();
-                if parts.len() == 2 {
-                    if let (Ok(parent_index), Ok(component_index)) =
-                        (parts[0].parse::<usize>(), parts[1].parse::<usize>())
-                    {
-                        let processed_value = process_value(value);
-                        field_map
-                            .entry(parent_index)
-                            .or_default()
-                            .entry(0)
-                            .or_default()
-                            .insert(component_index, processed_value);
-                    }
-                }
-            }
+        if strict_keys && !findings.is_empty() {
+            return Err(findings.remove(0));
         }
 
-        let max_index = field_map.keys().max().copied().unwrap_or(0);
-        for i in 1..=max_index {
-            if let Some(repetitions) = field_map.get(&i) {
-                let mut rep_strings = Vec::new();
-                let max_rep = repetitions.keys().max().copied().unwrap_or(0);
-
-                for rep_index in 0..=max_rep {
-                    if let Some(components) = repetitions.get(&rep_index) {
-                        let max_comp = components.keys().max().copied().unwrap_or(0);
-                        let mut comp_strings = Vec::new();
-
-                        for comp_index in 1..=max_comp {
-                            if let Some(comp_value) = components.get(&comp_index) {
-                                comp_strings.push(comp_value.clone());
-                            } else {
-                                comp_strings.push(String::new());
-                            }
-                        }
-
-                        if comp_strings.is_empty() {
-                            if let Some(value) = components.get(&0) {
-                                rep_strings.push(value.clone());
-                            } else {
-                                rep_strings.push(String::new());
-                            }
-                        } else {
-                            rep_strings.push(comp_strings.join("^"));
-                        }
-                    } else {
-                        rep_strings.push(String::new());
-                    }
-                }
+        let json_input: JsonInput = serde_json::from_value(json_data)
+            .map_err(|e| Hl7Error::InvalidFormat(format!("Invalid JSON: {}", e)))?;
 
-                fields.push(rep_strings.join("~"));
-            } else {
-                fields.push(String::new());
-            }
-        }
+        let mut json_hl7 = JsonHl7::new(json_input.into_flat(), standard_msh_numbering);
+        json_hl7.strict_keys = strict_keys;
+        json_hl7.key_warnings = findings.iter().map(ToString::to_string).collect();
+        Ok(json_hl7)
+    }
 
-        fields.join("|")
+    pub fn validate_schema(&self) -> Result<(), Hl7Error> {
+        let json_data = serde_json::to_value(&self.json_data)?;
+        let numbering = MshNumbering::from_flag(self.standard_msh_numbering);
+        schema::validate_json_schema(&json_data, &schema::json_schema(numbering))
+    }
+
+    pub fn _convert_segment_json_to_hl7(
+        &self,
+        segment_json: &BTreeMap<String, String>,
+        escape: bool,
+    ) -> String {
+        let options = SerializeOptions::new()
+            .with_escaping(escape)
+            .with_msh_numbering(MshNumbering::from_flag(self.standard_msh_numbering));
+        conversion::json_segment_to_hl7(segment_json, &self.escape_handler, &options)
     }
 }
@@ -1,19 +1,108 @@
-mod errors;
-mod escape;
-mod hl7_json;
-mod json_hl7;
-mod segments;
+pub mod batch;
+pub mod borrowed;
+pub mod builder;
+#[cfg(feature = "arrow")]
+pub mod columnar;
+pub mod conversion;
+pub mod definitions;
+#[cfg(feature = "deidentify")]
+pub mod deidentify;
+pub mod diff;
+pub mod errors;
+pub mod escape;
+pub mod google;
+#[cfg(feature = "python")]
+pub mod hl7_json;
+#[cfg(feature = "python")]
+pub mod json_hl7;
+pub mod message;
+pub mod mutable;
+#[cfg(feature = "python")]
+mod py_json;
+pub mod query;
+pub mod schema;
+pub mod segments;
+#[cfg(feature = "streaming")]
+pub mod streaming;
+pub mod structure;
+pub mod terser;
+#[cfg(feature = "transform")]
+pub mod transform;
 mod utils;
-mod validation;
+#[cfg(feature = "validation")]
+pub mod validation;
+#[cfg(feature = "xml")]
+pub mod xml;
 
 #[cfg(test)]
 mod tests;
 
+pub use batch::{Hl7Batch, Hl7BatchFile, Hl7BatchFileJson, Hl7BatchJson};
+pub use borrowed::{FieldRef, MessageRef, SegmentRef};
+pub use builder::{
+    MessageBuilder, NteBuilder, ObrBuilder, ObxBuilder, PidBuilder, Pv1Builder, SegmentBuilder,
+};
+#[cfg(feature = "arrow")]
+pub use columnar::{
+    hl7_to_record_batches, messages_to_record_batches, write_parquet, write_parquet_dir,
+    SegmentTables,
+};
+pub use conversion::{
+    check_json, hl7_to_json, hl7_to_nested_json, hl7_to_typed_json, json_to_hl7,
+    nested_json_to_hl7, try_json_to_hl7, typed_json_to_hl7, SerializeOptions,
+};
+#[cfg(feature = "deidentify")]
+pub use deidentify::{DeidChange, DeidReport, DeidRule, DeidStrategy, Deidentifier, PhiCategory};
+pub use diff::{diff_messages, ChangeKind, Hl7Change, MessageDiff};
+pub use errors::Hl7Error;
+pub use escape::Hl7EscapeHandler;
+pub use google::{hl7_to_google_parsed_data, GoogleParsedData, GoogleSegment};
+pub use message::{parse_message, parse_messages, split_messages, Hl7Message, ParseOptions};
+pub use mutable::Hl7MutableMessage;
+pub use query::{QueryCondition, QueryOperator, SegmentQuery};
+pub use schema::{json_schema, segment_json_schema, validate_json_schema};
+pub use segments::{
+    Hl7Field, Hl7NestedSegment, Hl7Segment, MshNumbering, NestedField, TypedSegmentJson, HL7_NULL,
+};
+#[cfg(feature = "streaming")]
+pub use streaming::{MessageReader, NdjsonErrorRecord, NdjsonSummary, NdjsonWriter};
+pub use structure::{
+    GroupedNode, Hl7GroupJson, Hl7GroupedJson, MessageStructure, StructureDiagnostic,
+    StructureElement,
+};
+pub use terser::Hl7Path;
+#[cfg(feature = "transform")]
+pub use transform::{Hl7Transform, RuleCondition, TransformAction, TransformRule};
+#[cfg(feature = "validation")]
+pub use validation::Hl7Validator;
+#[cfg(feature = "xml")]
+pub use xml::{hl7_to_xml, xml_to_hl7, xml_to_message};
+
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
+#[cfg(feature = "python")]
 #[pymodule]
 pub(crate) fn hl7conv2(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<hl7_json::Hl7Json>()?;
     m.add_class::<json_hl7::JsonHl7>()?;
+    m.add_class::<mutable::Hl7MutableMessage>()?;
+    m.add_class::<builder::MessageBuilder>()?;
+    m.add_class::<builder::SegmentBuilder>()?;
+    m.add_class::<builder::PidBuilder>()?;
+    m.add_class::<builder::Pv1Builder>()?;
+    m.add_class::<builder::ObrBuilder>()?;
+    m.add_class::<builder::ObxBuilder>()?;
+    m.add_class::<builder::NteBuilder>()?;
+    #[cfg(feature = "transform")]
+    m.add_class::<transform::Hl7Transform>()?;
+    #[cfg(feature = "deidentify")]
+    m.add_class::<deidentify::Deidentifier>()?;
+    #[cfg(feature = "streaming")]
+    m.add_class::<hl7_json::Hl7JsonFileIterator>()?;
+    #[cfg(feature = "streaming")]
+    m.add_function(wrap_pyfunction!(hl7_json::hl7_to_ndjson, m)?)?;
+    #[cfg(feature = "arrow")]
+    m.add_class::<hl7_json::Hl7ArrowTable>()?;
     Ok(())
 }
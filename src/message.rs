@@ -0,0 +1,158 @@
+use crate::borrowed::MessageRef;
+use crate::errors::Hl7Error;
+use crate::escape::Hl7EscapeHandler;
+use crate::segments::{Hl7NestedSegment, Hl7Segment, MshNumbering, TypedSegmentJson};
+use crate::structure::{Hl7GroupedJson, MessageStructure};
+use crate::terser::Hl7Path;
+#[cfg(feature = "validation")]
+use crate::validation::Hl7Validator;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub unescape: bool,
+    pub msh_numbering: MshNumbering,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            unescape: true,
+            msh_numbering: MshNumbering::Legacy,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lossless() -> Self {
+        Self::default().with_unescaping(false)
+    }
+
+    pub fn with_unescaping(mut self, unescape: bool) -> Self {
+        self.unescape = unescape;
+        self
+    }
+
+    pub fn with_msh_numbering(mut self, numbering: MshNumbering) -> Self {
+        self.msh_numbering = numbering;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Hl7Message {
+    pub segments: Vec<Hl7Segment>,
+    pub escape_handler: Hl7EscapeHandler,
+    pub msh_numbering: MshNumbering,
+    pub unescaped: bool,
+}
+
+impl Hl7Message {
+    pub fn parse(hl7_string: &str, options: &ParseOptions) -> Result<Self, Hl7Error> {
+        let message = MessageRef::parse(hl7_string, options.msh_numbering)?;
+        Ok(message.to_owned_message(options.unescape))
+    }
+
+    pub fn to_json(&self) -> Vec<BTreeMap<String, String>> {
+        self.segments.iter().map(Hl7Segment::to_json).collect()
+    }
+
+    pub fn to_typed_json(&self) -> Vec<TypedSegmentJson> {
+        self.segments
+            .iter()
+            .map(Hl7Segment::to_typed_json)
+            .collect()
+    }
+
+    pub fn to_nested_json(&self) -> Vec<Hl7NestedSegment> {
+        self.segments
+            .iter()
+            .map(Hl7Segment::to_nested_json)
+            .collect()
+    }
+
+    pub fn to_grouped_json(&self) -> Result<Hl7GroupedJson, Hl7Error> {
+        let structure = MessageStructure::for_message(self)?;
+        Ok(structure.group(&self.segments))
+    }
+
+    pub fn get(&self, path: &str) -> Result<Option<String>, Hl7Error> {
+        Hl7Path::parse(path)?.get(self)
+    }
+
+    pub fn get_all(&self, path: &str) -> Result<Vec<String>, Hl7Error> {
+        Hl7Path::parse(path)?.get_all(self)
+    }
+
+    pub fn set(&mut self, path: &str, value: &str) -> Result<(), Hl7Error> {
+        Hl7Path::parse(path)?.set(self, value)
+    }
+
+    #[cfg(feature = "validation")]
+    pub fn validate(&self, validator: &Hl7Validator) -> Result<(), Hl7Error> {
+        validator
+            .clone()
+            .with_msh_numbering(self.msh_numbering)
+            .validate_message(&self.segments)
+    }
+}
+
+pub fn parse_message(hl7_string: &str, options: &ParseOptions) -> Result<Hl7Message, Hl7Error> {
+    Hl7Message::parse(hl7_string, options)
+}
+
+pub fn split_messages(hl7_string: &str) -> Vec<&str> {
+    let mut headers = Vec::new();
+    let mut offset = 0;
+    for line in hl7_string.split(['\r', '\n']) {
+        if line.starts_with("MSH") {
+            headers.push(offset);
+        }
+        offset += line.len() + 1;
+    }
+
+    let mut starts = vec![0];
+    starts.extend(headers.into_iter().skip(1));
+    let ends = starts.iter().skip(1).copied().chain([hl7_string.len()]);
+
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| hl7_string[start..end].trim_matches(['\r', '\n']))
+        .filter(|message| !message.trim().is_empty())
+        .collect()
+}
+
+pub fn parse_messages(
+    hl7_string: &str,
+    options: &ParseOptions,
+) -> Result<Vec<Hl7Message>, Hl7Error> {
+    if hl7_string.trim().is_empty() {
+        return Err(Hl7Error::EmptyMessage);
+    }
+
+    split_messages(hl7_string)
+        .into_iter()
+        .enumerate()
+        .map(|(index, message)| {
+            Hl7Message::parse(message, options).map_err(|e| e.in_message(index))
+        })
+        .collect()
+}
+
+#[cfg(feature = "validation")]
+pub fn validate_messages(
+    messages: &[Hl7Message],
+    validator: &Hl7Validator,
+) -> Result<(), Hl7Error> {
+    messages
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, message)| {
+            message.validate(validator).map_err(|e| e.in_message(index))
+        })
+}
@@ -0,0 +1,387 @@
+use crate::errors::Hl7Error;
+use crate::escape::Hl7EscapeHandler;
+use crate::message::{Hl7Message, ParseOptions};
+use crate::segments::{Hl7Segment, MshNumbering};
+use crate::terser::Hl7Path;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(skip_from_py_object))]
+pub struct Hl7MutableMessage {
+    message: Hl7Message,
+}
+
+impl Hl7MutableMessage {
+    pub fn parse(hl7_string: &str, msh_numbering: MshNumbering) -> Result<Self, Hl7Error> {
+        let options = ParseOptions::lossless().with_msh_numbering(msh_numbering);
+        Ok(Hl7MutableMessage {
+            message: Hl7Message::parse(hl7_string, &options)?,
+        })
+    }
+
+    pub fn msh_numbering(&self) -> MshNumbering {
+        self.message.msh_numbering
+    }
+
+    pub fn escape_handler(&self) -> &Hl7EscapeHandler {
+        &self.message.escape_handler
+    }
+
+    pub fn segments(&self) -> &[Hl7Segment] {
+        &self.message.segments
+    }
+
+    #[cfg(feature = "deidentify")]
+    pub(crate) fn message_mut(&mut self) -> &mut Hl7Message {
+        &mut self.message
+    }
+
+    pub fn segment_names(&self) -> Vec<&str> {
+        self.message
+            .segments
+            .iter()
+            .map(|segment| segment.segment_name.as_str())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.message.segments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.message.segments.is_empty()
+    }
+
+    pub fn insert_segment(&mut self, index: usize, segment: &str) -> Result<(), Hl7Error> {
+        if index > self.len() {
+            return Err(self.out_of_range(index));
+        }
+        let segment = self.parse_segment(segment)?;
+        self.message.segments.insert(index, segment);
+        Ok(())
+    }
+
+    pub fn append_segment(&mut self, segment: &str) -> Result<(), Hl7Error> {
+        self.insert_segment(self.len(), segment)
+    }
+
+    pub fn remove_segment(&mut self, index: usize) -> Result<Hl7Segment, Hl7Error> {
+        if index >= self.len() {
+            return Err(self.out_of_range(index));
+        }
+        Ok(self.message.segments.remove(index))
+    }
+
+    pub fn move_segment(&mut self, from: usize, to: usize) -> Result<(), Hl7Error> {
+        if from >= self.len() {
+            return Err(self.out_of_range(from));
+        }
+        if to >= self.len() {
+            return Err(self.out_of_range(to));
+        }
+        let segment = self.message.segments.remove(from);
+        self.message.segments.insert(to, segment);
+        Ok(())
+    }
+
+    pub fn set_field_literal(
+        &mut self,
+        index: usize,
+        field: usize,
+        value: &str,
+    ) -> Result<(), Hl7Error> {
+        self.set_at(index, field, None, None, None, value)
+    }
+
+    pub fn set_repetition_literal(
+        &mut self,
+        index: usize,
+        field: usize,
+        repetition: usize,
+        value: &str,
+    ) -> Result<(), Hl7Error> {
+        self.set_at(index, field, Some(repetition), None, None, value)
+    }
+
+    pub fn set_component_literal(
+        &mut self,
+        index: usize,
+        field: usize,
+        repetition: usize,
+        component: usize,
+        value: &str,
+    ) -> Result<(), Hl7Error> {
+        self.set_at(index, field, Some(repetition), Some(component), None, value)
+    }
+
+    pub fn set_subcomponent_literal(
+        &mut self,
+        index: usize,
+        field: usize,
+        repetition: usize,
+        component: usize,
+        subcomponent: usize,
+        value: &str,
+    ) -> Result<(), Hl7Error> {
+        self.set_at(
+            index,
+            field,
+            Some(repetition),
+            Some(component),
+            Some(subcomponent),
+            value,
+        )
+    }
+
+    pub fn get(&self, path: &str) -> Result<Option<String>, Hl7Error> {
+        Ok(self
+            .message
+            .get(path)?
+            .map(|value| self.message.escape_handler.unescape(&value)))
+    }
+
+    pub fn get_all(&self, path: &str) -> Result<Vec<String>, Hl7Error> {
+        Ok(self
+            .message
+            .get_all(path)?
+            .iter()
+            .map(|value| self.message.escape_handler.unescape(value))
+            .collect())
+    }
+
+    pub fn set(&mut self, path: &str, value: &str) -> Result<(), Hl7Error> {
+        let path = Hl7Path::parse(path)?;
+        let value = path.escape_value(value, &self.message.escape_handler);
+        path.set(&mut self.message, &value)
+    }
+
+    pub fn get_encoded(&self, path: &str) -> Result<Option<String>, Hl7Error> {
+        self.message.get(path)
+    }
+
+    pub fn set_encoded(&mut self, path: &str, value: &str) -> Result<(), Hl7Error> {
+        self.message.set(path, value)
+    }
+
+    pub fn to_er7(&self) -> String {
+        let message = &self.message;
+        message
+            .segments
+            .iter()
+            .map(|segment| {
+                segment.to_er7(
+                    &message.escape_handler,
+                    message.msh_numbering,
+                    message.unescaped,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn to_message(&self, options: &ParseOptions) -> Result<Hl7Message, Hl7Error> {
+        let options = options.with_msh_numbering(self.message.msh_numbering);
+        Hl7Message::parse(&self.to_er7(), &options)
+    }
+
+    pub fn to_json(&self) -> Result<Vec<BTreeMap<String, String>>, Hl7Error> {
+        Ok(self.to_message(&ParseOptions::default())?.to_json())
+    }
+
+    fn set_at(
+        &mut self,
+        index: usize,
+        field: usize,
+        repetition: Option<usize>,
+        component: Option<usize>,
+        subcomponent: Option<usize>,
+        value: &str,
+    ) -> Result<(), Hl7Error> {
+        if index >= self.len() {
+            return Err(self.out_of_range(index));
+        }
+        if field == 0 || component == Some(0) || subcomponent == Some(0) {
+            return Err(Hl7Error::field_error(
+                &self.message.segments[index].segment_name,
+                field,
+                "field, component and subcomponent numbers start at 1",
+            ));
+        }
+
+        let numbering = self.message.msh_numbering;
+        let handler = &self.message.escape_handler;
+        let segment = &mut self.message.segments[index];
+        let path = Hl7Path {
+            segment: segment.segment_name.clone(),
+            occurrence: None,
+            field,
+            repetition,
+            component,
+            subcomponent,
+        };
+        path.set_in_segment(
+            segment,
+            numbering,
+            handler.delimiters(),
+            &handler.escape(value),
+        )
+    }
+
+    fn parse_segment(&self, segment: &str) -> Result<Hl7Segment, Hl7Error> {
+        let segment = Hl7Segment::parse_with_numbering(
+            segment,
+            &self.message.escape_handler,
+            false,
+            self.message.msh_numbering,
+        );
+
+        let is_segment_name = segment.segment_name.len() == 3
+            && segment
+                .segment_name
+                .chars()
+                .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit());
+        if !is_segment_name {
+            return Err(Hl7Error::InvalidSegmentName {
+                name: segment.segment_name,
+            });
+        }
+        Ok(segment)
+    }
+
+    fn out_of_range(&self, index: usize) -> Hl7Error {
+        Hl7Error::InvalidSegment(format!(
+            "segment index {} is out of range for a message with {} segments",
+            index,
+            self.len()
+        ))
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Hl7MutableMessage {
+    #[new]
+    #[pyo3(signature = (hl7_string, standard_msh_numbering=None))]
+    fn py_new(hl7_string: &str, standard_msh_numbering: Option<bool>) -> PyResult<Self> {
+        let numbering = MshNumbering::from_flag(standard_msh_numbering.unwrap_or(false));
+        Ok(Hl7MutableMessage::parse(hl7_string, numbering)?)
+    }
+
+    #[getter]
+    fn hl7_string(&self) -> String {
+        self.to_er7()
+    }
+
+    #[getter]
+    #[pyo3(name = "segment_names")]
+    fn py_segment_names(&self) -> Vec<String> {
+        self.segment_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[getter]
+    #[pyo3(name = "hl7_json")]
+    fn py_hl7_json<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, pyo3::types::PyDict>>> {
+        crate::py_json::segments_to_py(py, &self.to_json()?)
+    }
+
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    #[pyo3(name = "insert_segment")]
+    fn py_insert_segment(&mut self, index: usize, segment: &str) -> PyResult<()> {
+        Ok(self.insert_segment(index, segment)?)
+    }
+
+    #[pyo3(name = "append_segment")]
+    fn py_append_segment(&mut self, segment: &str) -> PyResult<()> {
+        Ok(self.append_segment(segment)?)
+    }
+
+    #[pyo3(name = "remove_segment")]
+    fn py_remove_segment(&mut self, index: usize) -> PyResult<String> {
+        let segment = self.remove_segment(index)?;
+        let removed = Hl7MutableMessage {
+            message: Hl7Message {
+                segments: vec![segment],
+                escape_handler: self.message.escape_handler.clone(),
+                msh_numbering: self.message.msh_numbering,
+                unescaped: self.message.unescaped,
+            },
+        };
+        Ok(removed.to_er7())
+    }
+
+    #[pyo3(name = "move_segment")]
+    fn py_move_segment(&mut self, from_index: usize, to_index: usize) -> PyResult<()> {
+        Ok(self.move_segment(from_index, to_index)?)
+    }
+
+    #[pyo3(name = "set_field_literal")]
+    fn py_set_field(&mut self, index: usize, field: usize, value: &str) -> PyResult<()> {
+        Ok(self.set_field_literal(index, field, value)?)
+    }
+
+    #[pyo3(name = "set_repetition_literal")]
+    fn py_set_repetition(
+        &mut self,
+        index: usize,
+        field: usize,
+        repetition: usize,
+        value: &str,
+    ) -> PyResult<()> {
+        Ok(self.set_repetition_literal(index, field, repetition, value)?)
+    }
+
+    #[pyo3(name = "set_component_literal")]
+    fn py_set_component(
+        &mut self,
+        index: usize,
+        field: usize,
+        repetition: usize,
+        component: usize,
+        value: &str,
+    ) -> PyResult<()> {
+        Ok(self.set_component_literal(index, field, repetition, component, value)?)
+    }
+
+    #[pyo3(name = "set_subcomponent_literal")]
+    fn py_set_subcomponent(
+        &mut self,
+        index: usize,
+        field: usize,
+        repetition: usize,
+        component: usize,
+        subcomponent: usize,
+        value: &str,
+    ) -> PyResult<()> {
+        Ok(self.set_subcomponent_literal(
+            index,
+            field,
+            repetition,
+            component,
+            subcomponent,
+            value,
+        )?)
+    }
+
+    #[pyo3(name = "get")]
+    fn py_get(&self, path: &str) -> PyResult<Option<String>> {
+        Ok(self.get(path)?)
+    }
+
+    #[pyo3(name = "get_all")]
+    fn py_get_all(&self, path: &str) -> PyResult<Vec<String>> {
+        Ok(self.get_all(path)?)
+    }
+
+    #[pyo3(name = "set")]
+    fn py_set(&mut self, path: &str, value: &str) -> PyResult<()> {
+        Ok(self.set(path, value)?)
+    }
+}
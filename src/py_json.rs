@@ -0,0 +1,94 @@
+use crate::conversion::compare_json_keys;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString};
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+
+pub(crate) fn segment_to_py<'py, V>(
+    py: Python<'py>,
+    segment: &BTreeMap<String, V>,
+) -> PyResult<Bound<'py, PyDict>>
+where
+    V: IntoPyObject<'py> + Clone,
+{
+    let mut entries: Vec<_> = segment.iter().collect();
+    entries.sort_by(|(a, _), (b, _)| compare_json_keys(a, b));
+
+    let dict = PyDict::new(py);
+    for (key, value) in entries {
+        dict.set_item(key, value.clone())?;
+    }
+    Ok(dict)
+}
+
+pub(crate) fn segments_to_py<'py, V>(
+    py: Python<'py>,
+    segments: &[BTreeMap<String, V>],
+) -> PyResult<Vec<Bound<'py, PyDict>>>
+where
+    V: IntoPyObject<'py> + Clone,
+{
+    segments
+        .iter()
+        .map(|segment| segment_to_py(py, segment))
+        .collect()
+}
+
+pub(crate) fn value_to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    match value {
+        Value::Null => Ok(py.None().into_bound(py)),
+        Value::Bool(b) => Ok(PyBool::new(py, *b).to_owned().into_any()),
+        Value::Number(n) => match (n.as_i64(), n.as_u64(), n.as_f64()) {
+            (Some(i), _, _) => Ok(i.into_pyobject(py)?.into_any()),
+            (None, Some(u), _) => Ok(u.into_pyobject(py)?.into_any()),
+            (None, None, f) => Ok(f.unwrap_or_default().into_pyobject(py)?.into_any()),
+        },
+        Value::String(s) => Ok(PyString::new(py, s).into_any()),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(value_to_py(py, item)?)?;
+            }
+            Ok(list.into_any())
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, value_to_py(py, item)?)?;
+            }
+            Ok(dict.into_any())
+        }
+    }
+}
+
+pub(crate) fn py_to_value(object: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if object.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(b) = object.cast::<PyBool>() {
+        Ok(Value::Bool(b.is_true()))
+    } else if object.is_instance_of::<PyInt>() {
+        match object.extract::<i64>() {
+            Ok(i) => Ok(Value::from(i)),
+            Err(_) => Ok(Value::from(object.extract::<u64>()?)),
+        }
+    } else if object.is_instance_of::<PyFloat>() {
+        let f = object.extract::<f64>()?;
+        Ok(Number::from_f64(f)
+            .map(Value::Number)
+            .unwrap_or(Value::Null))
+    } else if let Ok(s) = object.cast::<PyString>() {
+        Ok(Value::String(s.to_str()?.to_string()))
+    } else if let Ok(dict) = object.cast::<PyDict>() {
+        let mut map = Map::new();
+        for (key, item) in dict.iter() {
+            map.insert(key.str()?.to_str()?.to_string(), py_to_value(&item)?);
+        }
+        Ok(Value::Object(map))
+    } else {
+        let items = object
+            .try_iter()?
+            .map(|item| py_to_value(&item?))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Value::Array(items))
+    }
+}
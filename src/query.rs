@@ -0,0 +1,171 @@
+use crate::errors::Hl7Error;
+use crate::message::Hl7Message;
+use crate::segments::Hl7Segment;
+use crate::terser::Hl7Path;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryOperator {
+    Equals,
+    NotEquals,
+    Contains,
+    StartsWith,
+}
+
+impl QueryOperator {
+    pub fn parse(operator: &str) -> Result<Self, Hl7Error> {
+        match operator.trim() {
+            "==" | "=" => Ok(QueryOperator::Equals),
+            "!=" => Ok(QueryOperator::NotEquals),
+            "contains" => Ok(QueryOperator::Contains),
+            "startswith" | "starts_with" => Ok(QueryOperator::StartsWith),
+            other => Err(Hl7Error::InvalidFormat(format!(
+                "unknown query operator \"{}\", expected ==, !=, contains or startswith",
+                other
+            ))),
+        }
+    }
+
+    fn matches(self, values: &[String], expected: &str) -> bool {
+        match self {
+            QueryOperator::Equals => values.iter().any(|value| value == expected),
+            QueryOperator::NotEquals => values.iter().all(|value| value != expected),
+            QueryOperator::Contains => values.iter().any(|value| value.contains(expected)),
+            QueryOperator::StartsWith => values.iter().any(|value| value.starts_with(expected)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryCondition {
+    pub key: String,
+    pub operator: QueryOperator,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentQuery {
+    pub segment: String,
+    pub conditions: Vec<QueryCondition>,
+}
+
+impl SegmentQuery {
+    pub fn new(segment: &str) -> Self {
+        SegmentQuery {
+            segment: segment.to_string(),
+            conditions: Vec::new(),
+        }
+    }
+
+    pub fn with_condition(mut self, key: &str, operator: QueryOperator, value: &str) -> Self {
+        self.conditions.push(QueryCondition {
+            key: key.to_string(),
+            operator,
+            value: value.to_string(),
+        });
+        self
+    }
+
+    pub fn with_equals(self, key: &str, value: &str) -> Self {
+        self.with_condition(key, QueryOperator::Equals, value)
+    }
+
+    pub fn with_not_equals(self, key: &str, value: &str) -> Self {
+        self.with_condition(key, QueryOperator::NotEquals, value)
+    }
+
+    pub fn select<'m>(&self, message: &'m Hl7Message) -> Result<Vec<&'m Hl7Segment>, Hl7Error> {
+        let conditions = self
+            .conditions
+            .iter()
+            .map(|condition| Ok((self.key_path(&condition.key)?, condition)))
+            .collect::<Result<Vec<_>, Hl7Error>>()?;
+
+        let mut selected = Vec::new();
+        for segment in &message.segments {
+            if segment.segment_name != self.segment {
+                continue;
+            }
+            let mut is_match = true;
+            for (path, condition) in &conditions {
+                let values = path.values(message, segment, path.repetition)?;
+                if !condition.operator.matches(&values, &condition.value) {
+                    is_match = false;
+                    break;
+                }
+            }
+            if is_match {
+                selected.push(segment);
+            }
+        }
+        Ok(selected)
+    }
+
+    pub fn select_json(
+        &self,
+        message: &Hl7Message,
+    ) -> Result<Vec<BTreeMap<String, String>>, Hl7Error> {
+        Ok(self
+            .select(message)?
+            .into_iter()
+            .map(Hl7Segment::to_json)
+            .collect())
+    }
+
+    pub fn project(
+        &self,
+        message: &Hl7Message,
+        keys: &[&str],
+    ) -> Result<Vec<BTreeMap<String, Option<String>>>, Hl7Error> {
+        let paths = keys
+            .iter()
+            .map(|key| Ok((key.to_string(), self.key_path(key)?)))
+            .collect::<Result<Vec<_>, Hl7Error>>()?;
+
+        self.select(message)?
+            .into_iter()
+            .map(|segment| {
+                paths
+                    .iter()
+                    .map(|(key, path)| {
+                        let repetition = Some(path.repetition.unwrap_or(0));
+                        let value = path
+                            .values(message, segment, repetition)?
+                            .into_iter()
+                            .next();
+                        Ok((key.clone(), value))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn key_path(&self, key: &str) -> Result<Hl7Path, Hl7Error> {
+        let trimmed = key.trim();
+        let relative = match trimmed.split_once('-') {
+            Some((prefix, rest)) if prefix == self.segment => rest,
+            Some((prefix, _)) if prefix.starts_with(&format!("{}(", self.segment)) => {
+                return Err(Hl7Error::invalid_path(
+                    key,
+                    "segment occurrences cannot be used in query keys",
+                ));
+            }
+            Some((prefix, _)) if prefix.starts_with(|ch: char| ch.is_ascii_uppercase()) => {
+                return Err(Hl7Error::invalid_path(
+                    key,
+                    &format!("key belongs to segment {}, not {}", prefix, self.segment),
+                ));
+            }
+            _ => trimmed,
+        };
+        let relative = relative
+            .replace('[', "(")
+            .replace(']', ")")
+            .replace('.', "-");
+
+        Hl7Path::parse(&format!("{}-{}", self.segment, relative)).map_err(|error| match error {
+            Hl7Error::InvalidPath { message, .. } => Hl7Error::invalid_path(key, &message),
+            error => error,
+        })
+    }
+}
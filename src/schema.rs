@@ -0,0 +1,204 @@
+use crate::definitions::{component_data_types, field_count, field_data_type, field_repeatable};
+use crate::errors::Hl7Error;
+use crate::segments::{is_header_segment, MshNumbering, HEADER_SEGMENTS};
+use serde_json::{json, Map, Value};
+
+pub const SEGMENT_NAME_PATTERN: &str = "^[A-Z][A-Z0-9]{2}$";
+pub const FIELD_KEY_PATTERN: &str = "^[1-9][0-9]*(\\[[0-9]+\\])?(\\.[1-9][0-9]*){0,2}$";
+
+const SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+fn segment_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["segment_name"],
+        "properties": {
+            "segment_name": {"type": "string", "pattern": SEGMENT_NAME_PATTERN}
+        },
+        "patternProperties": {
+            FIELD_KEY_PATTERN: {"type": "string"}
+        },
+        "additionalProperties": false
+    })
+}
+
+fn header_properties(numbering: MshNumbering) -> Map<String, Value> {
+    let encoding_characters = json!({"type": "string", "minLength": 4, "maxLength": 5});
+    let properties = match numbering {
+        MshNumbering::Legacy => json!({"1": encoding_characters}),
+        MshNumbering::Standard => json!({
+            "1": {"type": "string", "minLength": 1, "maxLength": 1},
+            "2": encoding_characters
+        }),
+    };
+    properties.as_object().cloned().unwrap_or_default()
+}
+
+pub fn json_schema(numbering: MshNumbering) -> Value {
+    let header_properties = header_properties(numbering);
+    let header_required: Vec<&String> = header_properties.keys().collect();
+
+    json!({
+        "$schema": SCHEMA_DRAFT,
+        "title": "HL7 v2 flat JSON",
+        "type": "array",
+        "items": {
+            "allOf": [
+                {"$ref": "#/$defs/segment"},
+                {
+                    "if": {
+                        "properties": {"segment_name": {"enum": HEADER_SEGMENTS}},
+                        "required": ["segment_name"]
+                    },
+                    "then": {"$ref": "#/$defs/header"}
+                }
+            ]
+        },
+        "$defs": {
+            "segment": segment_schema(),
+            "header": {
+                "required": header_required,
+                "properties": header_properties
+            }
+        }
+    })
+}
+
+pub fn segment_json_schema(segment_name: &str, numbering: MshNumbering) -> Value {
+    let mut schema = segment_schema();
+    schema["$schema"] = json!(SCHEMA_DRAFT);
+    schema["title"] = json!(format!("HL7 v2 {} segment", segment_name));
+    schema["properties"]["segment_name"] = json!({"const": segment_name});
+    if let Some(field_properties) = field_properties(segment_name, numbering) {
+        schema["patternProperties"] = Value::Object(field_properties);
+    }
+
+    if is_header_segment(segment_name) {
+        let header_properties = header_properties(numbering);
+        let mut required = vec!["segment_name".to_string()];
+        required.extend(header_properties.keys().cloned());
+        schema["required"] = json!(required);
+        if let Some(properties) = schema["properties"].as_object_mut() {
+            properties.extend(header_properties);
+        }
+    }
+
+    schema
+}
+
+fn field_properties(segment_name: &str, numbering: MshNumbering) -> Option<Map<String, Value>> {
+    let count = field_count(segment_name)?;
+    let offset = usize::from(is_header_segment(segment_name) && numbering == MshNumbering::Legacy);
+
+    let mut properties = Map::new();
+    for field in (1 + offset)..=count {
+        let data_type = field_data_type(segment_name, field)?;
+        let key = field - offset;
+        let repetition = if field_repeatable(segment_name, field) == Some(true) {
+            "(\\[[0-9]+\\])?"
+        } else {
+            ""
+        };
+        let pattern = format!("^{}{}{}$", key, repetition, component_pattern(data_type));
+        properties.insert(
+            pattern,
+            json!({
+                "type": "string",
+                "description": format!("{}-{} {}", segment_name, field, data_type)
+            }),
+        );
+    }
+
+    let pattern = format!(
+        "^{}(\\[[0-9]+\\])?(\\.[1-9][0-9]*){{0,2}}$",
+        greater_than_pattern(count - offset)
+    );
+    properties.insert(pattern, json!({"type": "string"}));
+    Some(properties)
+}
+
+fn component_pattern(data_type: &str) -> String {
+    if data_type == "varies" {
+        return "(\\.[1-9][0-9]*){0,2}".to_string();
+    }
+    let Some(component_types) = component_data_types(data_type) else {
+        return String::new();
+    };
+
+    let mut primitive = Vec::new();
+    let mut composite: Vec<(usize, Vec<usize>)> = Vec::new();
+    for (index, component_type) in component_types.iter().enumerate() {
+        match component_data_types(component_type) {
+            Some(subcomponents) => match composite
+                .iter_mut()
+                .find(|(count, _)| *count == subcomponents.len())
+            {
+                Some((_, components)) => components.push(index + 1),
+                None => composite.push((subcomponents.len(), vec![index + 1])),
+            },
+            None => primitive.push(index + 1),
+        }
+    }
+
+    let mut alternatives = Vec::new();
+    if !primitive.is_empty() {
+        alternatives.push(format!("\\.{}", number_pattern(&primitive)));
+    }
+    for (count, components) in composite {
+        alternatives.push(format!(
+            "\\.{}(\\.{})?",
+            number_pattern(&components),
+            number_pattern(&(1..=count).collect::<Vec<_>>())
+        ));
+    }
+    format!("({})?", alternatives.join("|"))
+}
+
+fn number_pattern(numbers: &[usize]) -> String {
+    let numbers: Vec<String> = numbers.iter().map(usize::to_string).collect();
+    format!("({})", numbers.join("|"))
+}
+
+fn greater_than_pattern(number: usize) -> String {
+    let digits = number.to_string();
+    let mut alternatives = vec![format!("[1-9][0-9]{{{},}}", digits.len())];
+    for (index, digit) in digits.bytes().enumerate() {
+        let digit = digit - b'0';
+        if digit < 9 {
+            let remaining = digits.len() - index - 1;
+            let tail = if remaining > 0 {
+                format!("[0-9]{{{}}}", remaining)
+            } else {
+                String::new()
+            };
+            alternatives.push(format!("{}[{}-9]{}", &digits[..index], digit + 1, tail));
+        }
+    }
+    format!("({})", alternatives.join("|"))
+}
+
+#[cfg(feature = "schema")]
+pub fn validate_json_schema(instance: &Value, schema: &Value) -> Result<(), Hl7Error> {
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|e| Hl7Error::ConfigurationError(format!("Invalid JSON schema: {}", e)))?;
+
+    let details: Vec<String> = validator
+        .iter_errors(instance)
+        .map(|error| format!("{}: {}", error.instance_path, error))
+        .collect();
+
+    if details.is_empty() {
+        Ok(())
+    } else {
+        Err(Hl7Error::ValidationFailed {
+            details: details.join("; "),
+        })
+    }
+}
+
+#[cfg(not(feature = "schema"))]
+pub fn validate_json_schema(_instance: &Value, _schema: &Value) -> Result<(), Hl7Error> {
+    Err(Hl7Error::ConfigurationError(
+        "hl7conv2 was built without the schema feature".to_string(),
+    ))
+}
@@ -1,4 +1,7 @@
-use crate::escape::Hl7EscapeHandler;
+use crate::borrowed::{FieldRef, SegmentRef};
+use crate::escape::{Delimiters, Hl7EscapeHandler};
+#[cfg(feature = "python")]
+use pyo3::{FromPyObject, IntoPyObject};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -12,29 +15,88 @@ pub struct Hl7Segment {
 pub struct Hl7Field {
     pub value: String,
     pub components: Option<Vec<String>>,
+    #[serde(default)]
+    pub subcomponents: Option<Vec<Vec<String>>>,
     pub repetitions: Option<Vec<Hl7Field>>,
 }
 
-impl Hl7Segment {
-    pub fn from_string(segment_str: &str, escape_handler: Option<&Hl7EscapeHandler>) -> Self {
-        let parts: Vec<&str> = segment_str.split('|').collect();
-        let segment_name = parts.first().unwrap_or(&"").to_string();
+pub const HL7_NULL: &str = "\"\"";
 
-        let mut fields = BTreeMap::new();
-        for (index, part) in parts.iter().enumerate() {
-            if index == 0 {
-                continue;
-            }
+pub type NestedField = Vec<Vec<Vec<String>>>;
 
-            let field = Hl7Field::from_string(part, escape_handler);
-            fields.insert(index, field);
+pub type TypedSegmentJson = BTreeMap<String, Option<String>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", derive(FromPyObject, IntoPyObject))]
+#[cfg_attr(feature = "python", pyo3(from_item_all))]
+pub struct Hl7NestedSegment {
+    pub segment_name: String,
+    pub fields: Vec<NestedField>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MshNumbering {
+    #[default]
+    Legacy,
+    Standard,
+}
+
+impl MshNumbering {
+    pub fn from_flag(standard: bool) -> Self {
+        if standard {
+            MshNumbering::Standard
+        } else {
+            MshNumbering::Legacy
         }
+    }
 
-        Hl7Segment {
-            segment_name,
-            fields,
+    pub fn encoding_characters_field(self) -> usize {
+        match self {
+            MshNumbering::Legacy => 1,
+            MshNumbering::Standard => 2,
+        }
+    }
+
+    pub fn message_type_field(self) -> usize {
+        match self {
+            MshNumbering::Legacy => 8,
+            MshNumbering::Standard => 9,
+        }
+    }
+
+    pub fn control_id_field(self) -> usize {
+        self.message_type_field() + 1
+    }
+
+    pub fn version_field(self) -> usize {
+        match self {
+            MshNumbering::Legacy => 11,
+            MshNumbering::Standard => 12,
         }
     }
+}
+
+impl Hl7Segment {
+    pub fn from_string(segment_str: &str, escape_handler: Option<&Hl7EscapeHandler>) -> Self {
+        match escape_handler {
+            Some(handler) => Self::parse(segment_str, handler, true),
+            None => Self::parse(segment_str, &Hl7EscapeHandler::default(), false),
+        }
+    }
+
+    pub fn parse(segment_str: &str, handler: &Hl7EscapeHandler, unescape: bool) -> Self {
+        Self::parse_with_numbering(segment_str, handler, unescape, MshNumbering::Legacy)
+    }
+
+    pub fn parse_with_numbering(
+        segment_str: &str,
+        handler: &Hl7EscapeHandler,
+        unescape: bool,
+        numbering: MshNumbering,
+    ) -> Self {
+        SegmentRef::new(segment_str, handler.delimiters(), numbering)
+            .to_owned_segment(handler, unescape)
+    }
 
     pub fn to_json(&self) -> BTreeMap<String, String> {
         let mut json = BTreeMap::new();
@@ -43,21 +105,15 @@ impl Hl7Segment {
         for (index, field) in &self.fields {
             if let Some(ref repetitions) = field.repetitions {
                 for (rep_index, repetition) in repetitions.iter().enumerate() {
-                    if let Some(ref components) = repetition.components {
-                        for (comp_index, component) in components.iter().enumerate() {
-                            let key = format!("{}[{}].{}", index, rep_index, comp_index + 1);
-                            json.insert(key, component.clone());
-                        }
+                    let prefix = format!("{}[{}]", index, rep_index);
+                    if repetition.components.is_some() {
+                        repetition.insert_components_json(&mut json, &prefix);
                     } else {
-                        let key = format!("{}[{}]", index, rep_index);
-                        json.insert(key, repetition.value.clone());
+                        json.insert(prefix, repetition.value.clone());
                     }
                 }
-            } else if let Some(ref components) = field.components {
-                for (comp_index, component) in components.iter().enumerate() {
-                    let key = format!("{}.{}", index, comp_index + 1);
-                    json.insert(key, component.clone());
-                }
+            } else if field.components.is_some() {
+                field.insert_components_json(&mut json, &index.to_string());
             } else {
                 json.insert(index.to_string(), field.value.clone());
             }
@@ -65,75 +121,247 @@ impl Hl7Segment {
 
         json
     }
-}
 
-impl Hl7Field {
-    pub fn from_string(field_str: &str, escape_handler: Option<&Hl7EscapeHandler>) -> Self {
-        let repetition_separator = if let Some(handler) = escape_handler {
-            handler.get_repetition_separator()
+    pub fn to_typed_json(&self) -> TypedSegmentJson {
+        self.to_json()
+            .into_iter()
+            .map(|(key, value)| {
+                let value = if key != "segment_name" && value == HL7_NULL {
+                    None
+                } else {
+                    Some(value)
+                };
+                (key, value)
+            })
+            .collect()
+    }
+
+    pub fn to_er7(
+        &self,
+        handler: &Hl7EscapeHandler,
+        numbering: MshNumbering,
+        escape: bool,
+    ) -> String {
+        let is_header = is_header_segment(&self.segment_name);
+        let first_index = if is_header && numbering == MshNumbering::Standard {
+            2
         } else {
-            '~'
+            1
         };
+        let max_index = self.fields.keys().max().copied().unwrap_or(0);
 
-        if field_str.contains(repetition_separator) && !field_str.contains("^~\\&") {
-            let repetitions: Vec<Hl7Field> = field_str
-                .split(repetition_separator)
-                .map(|rep_str| {
-                    let (value, components) = if let Some(handler) = escape_handler {
-                        let unescaped_value = handler.unescape(rep_str);
-                        let components =
-                            if unescaped_value.contains(handler.get_component_separator()) {
-                                Some(handler.parse_field_with_escaping(rep_str))
-                            } else {
-                                None
-                            };
-                        (unescaped_value, components)
-                    } else {
-                        let value = rep_str.to_string();
-                        let components = if rep_str.contains('^') && !rep_str.contains("^~\\&") {
-                            Some(rep_str.split('^').map(|s| s.to_string()).collect())
-                        } else {
-                            None
-                        };
-                        (value, components)
-                    };
-                    Hl7Field {
-                        value,
-                        components,
-                        repetitions: None,
+        let mut er7 = self.segment_name.clone();
+        for index in first_index..=max_index {
+            er7.push(handler.get_field_separator());
+            let Some(field) = self.fields.get(&index) else {
+                continue;
+            };
+            if escape && !(is_header && index <= numbering.encoding_characters_field()) {
+                er7.push_str(&field.to_escaped_er7(handler));
+            } else {
+                er7.push_str(&field.value);
+            }
+        }
+        er7
+    }
+
+    pub fn to_nested_json(&self) -> Hl7NestedSegment {
+        let max_index = self.fields.keys().max().copied().unwrap_or(0);
+        let fields = (1..=max_index)
+            .map(|index| {
+                self.fields
+                    .get(&index)
+                    .map(Hl7Field::to_nested_json)
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        Hl7NestedSegment {
+            segment_name: self.segment_name.clone(),
+            fields,
+        }
+    }
+}
+
+impl Hl7NestedSegment {
+    pub fn to_flat_json(&self) -> BTreeMap<String, String> {
+        let mut json = BTreeMap::new();
+        json.insert("segment_name".to_string(), self.segment_name.clone());
+
+        for (field_index, repetitions) in self.fields.iter().enumerate() {
+            let field_index = field_index + 1;
+            match repetitions.as_slice() {
+                [] => {}
+                [components] if components.len() == 1 && components[0].len() == 1 => {
+                    json.insert(field_index.to_string(), components[0][0].clone());
+                }
+                _ => {
+                    for (rep_index, components) in repetitions.iter().enumerate() {
+                        for (comp_index, subcomponents) in components.iter().enumerate() {
+                            for (sub_index, subcomponent) in subcomponents.iter().enumerate() {
+                                let key = format!(
+                                    "{}[{}].{}.{}",
+                                    field_index,
+                                    rep_index,
+                                    comp_index + 1,
+                                    sub_index + 1
+                                );
+                                json.insert(key, subcomponent.clone());
+                            }
+                        }
                     }
-                })
-                .collect();
+                }
+            }
+        }
 
-            Hl7Field {
-                value: field_str.to_string(),
-                components: None,
-                repetitions: Some(repetitions),
+        json
+    }
+}
+
+impl Hl7Field {
+    pub fn from_string(field_str: &str, escape_handler: Option<&Hl7EscapeHandler>) -> Self {
+        match escape_handler {
+            Some(handler) => Self::parse(field_str, handler, true),
+            None => Self::parse(field_str, &Hl7EscapeHandler::default(), false),
+        }
+    }
+
+    pub fn parse(field_str: &str, handler: &Hl7EscapeHandler, unescape: bool) -> Self {
+        FieldRef::new(field_str, handler.delimiters()).to_owned_field(handler, unescape)
+    }
+
+    pub fn plain(value: &str) -> Self {
+        Hl7Field {
+            value: value.to_string(),
+            components: None,
+            subcomponents: None,
+            repetitions: None,
+        }
+    }
+
+    pub fn to_nested_json(&self) -> NestedField {
+        match self.repetitions {
+            Some(ref repetitions) => repetitions
+                .iter()
+                .map(Hl7Field::nested_components)
+                .collect(),
+            None if self.value.is_empty() => Vec::new(),
+            None => vec![self.nested_components()],
+        }
+    }
+
+    pub fn from_nested(field: &NestedField, delimiters: Delimiters) -> Self {
+        match field.as_slice() {
+            [] => Hl7Field::plain(""),
+            [components] => Hl7Field::from_nested_components(components, delimiters),
+            repetitions => {
+                let repetitions: Vec<Hl7Field> = repetitions
+                    .iter()
+                    .map(|components| Hl7Field::from_nested_components(components, delimiters))
+                    .collect();
+                Hl7Field {
+                    value: repetitions
+                        .iter()
+                        .map(|repetition| repetition.value.as_str())
+                        .collect::<Vec<_>>()
+                        .join(&delimiters.repetition.to_string()),
+                    components: None,
+                    subcomponents: None,
+                    repetitions: Some(repetitions),
+                }
             }
-        } else {
-            let (value, components) = if let Some(handler) = escape_handler {
-                let unescaped_value = handler.unescape(field_str);
-                let components = if unescaped_value.contains(handler.get_component_separator()) {
-                    Some(handler.parse_field_with_escaping(field_str))
-                } else {
-                    None
-                };
-                (unescaped_value, components)
-            } else {
-                let value = field_str.to_string();
-                let components = if field_str.contains('^') && !field_str.contains("^~\\&") {
-                    Some(field_str.split('^').map(|s| s.to_string()).collect())
-                } else {
-                    None
-                };
-                (value, components)
-            };
+        }
+    }
 
+    fn from_nested_components(components: &[Vec<String>], delimiters: Delimiters) -> Self {
+        let subcomponent_separator = delimiters.subcomponent.to_string();
+        let joined: Vec<String> = components
+            .iter()
+            .map(|subcomponents| subcomponents.join(&subcomponent_separator))
+            .collect();
+        let value = joined.join(&delimiters.component.to_string());
+
+        let has_subcomponents = components
+            .iter()
+            .any(|subcomponents| subcomponents.len() > 1);
+        if has_subcomponents {
             Hl7Field {
                 value,
-                components,
+                components: Some(joined),
+                subcomponents: Some(components.to_vec()),
                 repetitions: None,
             }
+        } else if components.len() > 1 {
+            Hl7Field {
+                value,
+                components: Some(joined),
+                subcomponents: None,
+                repetitions: None,
+            }
+        } else {
+            Hl7Field::plain(&value)
         }
     }
+
+    fn to_escaped_er7(&self, handler: &Hl7EscapeHandler) -> String {
+        let delimiters = handler.delimiters();
+        self.to_nested_json()
+            .iter()
+            .map(|components| {
+                components
+                    .iter()
+                    .map(|subcomponents| {
+                        subcomponents
+                            .iter()
+                            .map(|subcomponent| handler.escape(subcomponent))
+                            .collect::<Vec<_>>()
+                            .join(&delimiters.subcomponent.to_string())
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&delimiters.component.to_string())
+            })
+            .collect::<Vec<_>>()
+            .join(&delimiters.repetition.to_string())
+    }
+
+    fn nested_components(&self) -> Vec<Vec<String>> {
+        match (&self.subcomponents, &self.components) {
+            (Some(subcomponents), _) => subcomponents.clone(),
+            (None, Some(components)) => components
+                .iter()
+                .map(|component| vec![component.clone()])
+                .collect(),
+            (None, None) => vec![vec![self.value.clone()]],
+        }
+    }
+
+    fn insert_components_json(&self, json: &mut BTreeMap<String, String>, prefix: &str) {
+        let Some(ref components) = self.components else {
+            return;
+        };
+
+        for (comp_index, component) in components.iter().enumerate() {
+            let subcomponents = self
+                .subcomponents
+                .as_ref()
+                .and_then(|subcomponents| subcomponents.get(comp_index))
+                .filter(|subcomponents| subcomponents.len() > 1);
+
+            if let Some(subcomponents) = subcomponents {
+                for (sub_index, subcomponent) in subcomponents.iter().enumerate() {
+                    let key = format!("{}.{}.{}", prefix, comp_index + 1, sub_index + 1);
+                    json.insert(key, subcomponent.clone());
+                }
+            } else {
+                let key = format!("{}.{}", prefix, comp_index + 1);
+                json.insert(key, component.clone());
+            }
+        }
+    }
+}
+
+pub(crate) const HEADER_SEGMENTS: [&str; 3] = ["MSH", "FHS", "BHS"];
+
+pub(crate) fn is_header_segment(segment_name: &str) -> bool {
+    HEADER_SEGMENTS.contains(&segment_name)
 }
@@ -0,0 +1,254 @@
+use crate::conversion::compare_json_keys;
+use crate::errors::Hl7Error;
+use crate::message::{Hl7Message, ParseOptions};
+use crate::segments::Hl7Segment;
+#[cfg(feature = "validation")]
+use crate::validation::Hl7Validator;
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+pub struct MessageReader<R: BufRead> {
+    reader: R,
+    options: ParseOptions,
+    pending: Option<String>,
+    finished: bool,
+}
+
+impl MessageReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P, options: ParseOptions) -> Result<Self, Hl7Error> {
+        let file = File::open(path)?;
+        Ok(MessageReader::new(BufReader::new(file), options))
+    }
+}
+
+impl<R: BufRead> MessageReader<R> {
+    pub fn new(reader: R, options: ParseOptions) -> Self {
+        MessageReader {
+            reader,
+            options,
+            pending: None,
+            finished: false,
+        }
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    pub fn next_raw(&mut self) -> Result<Option<String>, Hl7Error> {
+        let mut message = self.pending.take().unwrap_or_default();
+        let mut has_header = message.starts_with("MSH");
+
+        while let Some(segment) = self.read_segment()? {
+            if segment.trim().is_empty() {
+                continue;
+            }
+
+            if segment.starts_with("MSH") {
+                if has_header {
+                    self.pending = Some(segment);
+                    return Ok(Some(message));
+                }
+                has_header = true;
+            }
+
+            if !message.is_empty() {
+                message.push('\n');
+            }
+            message.push_str(&segment);
+        }
+
+        if message.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(message))
+        }
+    }
+
+    fn read_segment(&mut self) -> Result<Option<String>, Hl7Error> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let mut bytes = Vec::new();
+        loop {
+            let buffer = match self.reader.fill_buf() {
+                Ok(buffer) => buffer,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            if buffer.is_empty() {
+                self.finished = true;
+                break;
+            }
+
+            match buffer.iter().position(|&b| b == b'\r' || b == b'\n') {
+                Some(position) => {
+                    bytes.extend_from_slice(&buffer[..position]);
+                    self.reader.consume(position + 1);
+                    break;
+                }
+                None => {
+                    let length = buffer.len();
+                    bytes.extend_from_slice(buffer);
+                    self.reader.consume(length);
+                }
+            }
+        }
+
+        if bytes.is_empty() && self.finished {
+            return Ok(None);
+        }
+
+        String::from_utf8(bytes)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e).into())
+    }
+}
+
+impl<R: BufRead> Iterator for MessageReader<R> {
+    type Item = Result<Hl7Message, Hl7Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_raw() {
+            Ok(Some(message)) => Some(Hl7Message::parse(&message, &self.options)),
+            Ok(None) => None,
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NdjsonSummary {
+    pub messages: usize,
+    pub errors: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NdjsonErrorRecord {
+    pub message_index: usize,
+    pub error: String,
+}
+
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+    #[cfg(feature = "validation")]
+    validator: Option<Hl7Validator>,
+    summary: NdjsonSummary,
+}
+
+impl NdjsonWriter<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Hl7Error> {
+        let file = File::create(path)?;
+        Ok(NdjsonWriter::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        NdjsonWriter {
+            writer,
+            #[cfg(feature = "validation")]
+            validator: None,
+            summary: NdjsonSummary::default(),
+        }
+    }
+
+    #[cfg(feature = "validation")]
+    pub fn with_validator(mut self, validator: Hl7Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    pub fn summary(&self) -> NdjsonSummary {
+        self.summary
+    }
+
+    pub fn write_message(
+        &mut self,
+        hl7_string: &str,
+        options: &ParseOptions,
+    ) -> Result<(), Hl7Error> {
+        let message_index = self.summary.messages;
+        self.summary.messages += 1;
+
+        match self.convert(hl7_string, options) {
+            Ok(message) => self.write_segments(&message.segments),
+            Err(e) => {
+                self.summary.errors += 1;
+                let record = NdjsonErrorRecord {
+                    message_index,
+                    error: e.to_string(),
+                };
+                serde_json::to_writer(&mut self.writer, &record)?;
+                self.writer.write_all(b"\n")?;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn write_reader<R: BufRead>(
+        &mut self,
+        reader: &mut MessageReader<R>,
+    ) -> Result<NdjsonSummary, Hl7Error> {
+        let options = *reader.options();
+        while let Some(hl7_string) = reader.next_raw()? {
+            self.write_message(&hl7_string, &options)?;
+        }
+        self.writer.flush()?;
+        Ok(self.summary)
+    }
+
+    pub fn into_inner(mut self) -> Result<W, Hl7Error> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn convert(&self, hl7_string: &str, options: &ParseOptions) -> Result<Hl7Message, Hl7Error> {
+        #[cfg(feature = "validation")]
+        if let Some(validator) = &self.validator {
+            Hl7Message::parse(hl7_string, &options.with_unescaping(false))?.validate(validator)?;
+        }
+
+        Hl7Message::parse(hl7_string, options)
+    }
+
+    fn write_segments(&mut self, segments: &[Hl7Segment]) -> Result<(), Hl7Error> {
+        self.writer.write_all(b"[")?;
+        for (index, segment) in segments.iter().enumerate() {
+            if index > 0 {
+                self.writer.write_all(b",")?;
+            }
+            serde_json::to_writer(&mut self.writer, &OrderedSegment(&segment.to_json()))?;
+        }
+        self.writer.write_all(b"]\n")?;
+        Ok(())
+    }
+}
+
+struct OrderedSegment<'a>(&'a BTreeMap<String, String>);
+
+impl Serialize for OrderedSegment<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<_> = self.0.iter().collect();
+        entries.sort_by(|(a, _), (b, _)| compare_json_keys(a, b));
+
+        let mut map = serializer.serialize_map(Some(entries.len()))?;
+        for (key, value) in entries {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
@@ -0,0 +1,443 @@
+use crate::errors::Hl7Error;
+use crate::message::Hl7Message;
+use crate::segments::Hl7Segment;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructureElement {
+    Segment {
+        name: String,
+        optional: bool,
+        repeating: bool,
+    },
+    Group {
+        name: String,
+        optional: bool,
+        repeating: bool,
+        elements: Vec<StructureElement>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageStructure {
+    pub name: String,
+    pub elements: Vec<StructureElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GroupedNode {
+    Group(Hl7GroupJson),
+    Segment(BTreeMap<String, String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hl7GroupJson {
+    pub group_name: String,
+    pub children: Vec<GroupedNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructureDiagnostic {
+    pub index: usize,
+    pub segment_name: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hl7GroupedJson {
+    pub structure: String,
+    pub children: Vec<GroupedNode>,
+    #[serde(default)]
+    pub diagnostics: Vec<StructureDiagnostic>,
+}
+
+impl StructureElement {
+    pub fn segment(name: &str) -> Self {
+        StructureElement::Segment {
+            name: name.to_string(),
+            optional: false,
+            repeating: false,
+        }
+    }
+
+    pub fn group(name: &str, elements: Vec<StructureElement>) -> Self {
+        StructureElement::Group {
+            name: name.to_string(),
+            optional: false,
+            repeating: false,
+            elements,
+        }
+    }
+
+    pub fn optional(mut self) -> Self {
+        match &mut self {
+            StructureElement::Segment { optional, .. }
+            | StructureElement::Group { optional, .. } => *optional = true,
+        }
+        self
+    }
+
+    pub fn repeating(mut self) -> Self {
+        match &mut self {
+            StructureElement::Segment { repeating, .. }
+            | StructureElement::Group { repeating, .. } => *repeating = true,
+        }
+        self
+    }
+
+    fn is_optional(&self) -> bool {
+        match self {
+            StructureElement::Segment { optional, .. }
+            | StructureElement::Group { optional, .. } => *optional,
+        }
+    }
+
+    fn is_repeating(&self) -> bool {
+        match self {
+            StructureElement::Segment { repeating, .. }
+            | StructureElement::Group { repeating, .. } => *repeating,
+        }
+    }
+
+    fn starts_with(&self, segment_name: &str) -> bool {
+        match self {
+            StructureElement::Segment { name, .. } => name == segment_name,
+            StructureElement::Group { elements, .. } => {
+                for element in elements {
+                    if element.starts_with(segment_name) {
+                        return true;
+                    }
+                    if !element.is_optional() {
+                        return false;
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    fn contains(&self, segment_name: &str) -> bool {
+        match self {
+            StructureElement::Segment { name, .. } => name == segment_name,
+            StructureElement::Group { elements, .. } => elements
+                .iter()
+                .any(|element| element.contains(segment_name)),
+        }
+    }
+}
+
+fn seg(name: &str) -> StructureElement {
+    StructureElement::segment(name)
+}
+
+fn opt(name: &str) -> StructureElement {
+    StructureElement::segment(name).optional()
+}
+
+fn opt_rep(name: &str) -> StructureElement {
+    StructureElement::segment(name).optional().repeating()
+}
+
+impl MessageStructure {
+    pub fn new(name: &str, elements: Vec<StructureElement>) -> Self {
+        MessageStructure {
+            name: name.to_string(),
+            elements,
+        }
+    }
+
+    pub fn adt_a01() -> Self {
+        MessageStructure::new(
+            "ADT_A01",
+            vec![
+                seg("MSH"),
+                opt_rep("SFT"),
+                seg("EVN"),
+                seg("PID"),
+                opt("PD1"),
+                opt_rep("ROL"),
+                opt_rep("NK1"),
+                seg("PV1"),
+                opt("PV2"),
+                opt_rep("ROL"),
+                opt_rep("DB1"),
+                opt_rep("OBX"),
+                opt_rep("AL1"),
+                opt_rep("DG1"),
+                opt("DRG"),
+                StructureElement::group("PROCEDURE", vec![seg("PR1"), opt_rep("ROL")])
+                    .optional()
+                    .repeating(),
+                opt_rep("GT1"),
+                StructureElement::group(
+                    "INSURANCE",
+                    vec![seg("IN1"), opt("IN2"), opt_rep("IN3"), opt_rep("ROL")],
+                )
+                .optional()
+                .repeating(),
+                opt("ACC"),
+                opt("UB1"),
+                opt("UB2"),
+                opt("PDA"),
+            ],
+        )
+    }
+
+    pub fn oru_r01() -> Self {
+        let patient = StructureElement::group(
+            "PATIENT",
+            vec![
+                seg("PID"),
+                opt("PD1"),
+                opt_rep("NTE"),
+                opt_rep("NK1"),
+                StructureElement::group("VISIT", vec![seg("PV1"), opt("PV2")]).optional(),
+            ],
+        )
+        .optional();
+        let order_observation = StructureElement::group(
+            "ORDER_OBSERVATION",
+            vec![
+                opt("ORC"),
+                seg("OBR"),
+                opt_rep("NTE"),
+                StructureElement::group("TIMING_QTY", vec![seg("TQ1"), opt_rep("TQ2")])
+                    .optional()
+                    .repeating(),
+                opt("CTD"),
+                StructureElement::group("OBSERVATION", vec![seg("OBX"), opt_rep("NTE")])
+                    .optional()
+                    .repeating(),
+                opt_rep("FT1"),
+                opt_rep("CTI"),
+                StructureElement::group("SPECIMEN", vec![seg("SPM"), opt_rep("OBX")])
+                    .optional()
+                    .repeating(),
+            ],
+        )
+        .repeating();
+
+        MessageStructure::new(
+            "ORU_R01",
+            vec![
+                seg("MSH"),
+                opt_rep("SFT"),
+                StructureElement::group("PATIENT_RESULT", vec![patient, order_observation])
+                    .repeating(),
+                opt("DSC"),
+            ],
+        )
+    }
+
+    pub fn ack() -> Self {
+        MessageStructure::new(
+            "ACK",
+            vec![seg("MSH"), opt_rep("SFT"), seg("MSA"), opt_rep("ERR")],
+        )
+    }
+
+    pub fn find(structure_name: &str) -> Option<Self> {
+        match structure_name {
+            "ADT_A01" => Some(MessageStructure::adt_a01()),
+            "ORU_R01" => Some(MessageStructure::oru_r01()),
+            "ACK" => Some(MessageStructure::ack()),
+            _ => None,
+        }
+    }
+
+    pub fn for_message_type(message_code: &str, trigger_event: &str) -> Option<Self> {
+        let structure_name = match (message_code, trigger_event) {
+            ("ADT", "A01" | "A04" | "A08" | "A13") => "ADT_A01",
+            ("ORU", "R01") => "ORU_R01",
+            ("ACK", _) => "ACK",
+            _ => return None,
+        };
+        MessageStructure::find(structure_name)
+    }
+
+    pub fn for_message(message: &Hl7Message) -> Result<Self, Hl7Error> {
+        let message_type = message
+            .segments
+            .iter()
+            .find(|segment| segment.segment_name == "MSH")
+            .and_then(|msh| msh.fields.get(&message.msh_numbering.message_type_field()))
+            .ok_or_else(|| Hl7Error::ValidationError("Message type is required".to_string()))?;
+
+        let components = message_type
+            .components
+            .clone()
+            .unwrap_or_else(|| vec![message_type.value.clone()]);
+        let component = |index: usize| components.get(index).map(String::as_str).unwrap_or("");
+
+        MessageStructure::find(component(2))
+            .or_else(|| MessageStructure::for_message_type(component(0), component(1)))
+            .ok_or_else(|| {
+                Hl7Error::UnknownMessageStructure(
+                    [component(0), component(1), component(2)]
+                        .iter()
+                        .filter(|part| !part.is_empty())
+                        .copied()
+                        .collect::<Vec<_>>()
+                        .join("^"),
+                )
+            })
+    }
+
+    pub fn group(&self, segments: &[Hl7Segment]) -> Hl7GroupedJson {
+        let (children, diagnostics) = self.layout(segments);
+
+        Hl7GroupedJson {
+            structure: self.name.clone(),
+            children: children
+                .into_iter()
+                .map(|node| node.to_json(segments))
+                .collect(),
+            diagnostics,
+        }
+    }
+
+    pub(crate) fn layout(
+        &self,
+        segments: &[Hl7Segment],
+    ) -> (Vec<StructureNode>, Vec<StructureDiagnostic>) {
+        let mut grouper = Grouper {
+            structure: self,
+            segments,
+            position: 0,
+            diagnostics: Vec::new(),
+        };
+
+        let children = grouper.fill(&self.elements, true);
+        (children, grouper.diagnostics)
+    }
+
+    fn contains(&self, segment_name: &str) -> bool {
+        self.elements
+            .iter()
+            .any(|element| element.contains(segment_name))
+    }
+}
+
+pub(crate) enum StructureNode {
+    Segment(usize),
+    Group {
+        name: String,
+        children: Vec<StructureNode>,
+    },
+}
+
+impl StructureNode {
+    #[cfg(feature = "xml")]
+    pub(crate) fn segment_count(&self) -> usize {
+        match self {
+            StructureNode::Segment(_) => 1,
+            StructureNode::Group { children, .. } => {
+                children.iter().map(StructureNode::segment_count).sum()
+            }
+        }
+    }
+
+    fn to_json(&self, segments: &[Hl7Segment]) -> GroupedNode {
+        match self {
+            StructureNode::Segment(index) => GroupedNode::Segment(segments[*index].to_json()),
+            StructureNode::Group { name, children } => GroupedNode::Group(Hl7GroupJson {
+                group_name: name.clone(),
+                children: children
+                    .iter()
+                    .map(|child| child.to_json(segments))
+                    .collect(),
+            }),
+        }
+    }
+}
+
+struct Grouper<'a> {
+    structure: &'a MessageStructure,
+    segments: &'a [Hl7Segment],
+    position: usize,
+    diagnostics: Vec<StructureDiagnostic>,
+}
+
+impl Grouper<'_> {
+    fn skip(&mut self, segment: &Hl7Segment, message: &str) {
+        self.diagnostics.push(StructureDiagnostic {
+            index: self.position,
+            segment_name: segment.segment_name.clone(),
+            message: message.to_string(),
+        });
+        self.position += 1;
+    }
+
+    fn check_required(&mut self, elements: &[StructureElement]) {
+        for element in elements.iter().filter(|element| !element.is_optional()) {
+            let (kind, name) = match element {
+                StructureElement::Segment { name, .. } => ("segment", name),
+                StructureElement::Group { name, .. } => ("group", name),
+            };
+            self.diagnostics.push(StructureDiagnostic {
+                index: self.position,
+                segment_name: name.clone(),
+                message: format!("required {} is missing", kind),
+            });
+        }
+    }
+
+    fn fill(&mut self, elements: &[StructureElement], root: bool) -> Vec<StructureNode> {
+        let mut nodes = Vec::new();
+        let mut current = 0;
+        let mut used = false;
+
+        while let Some(segment) = self.segments.get(self.position) {
+            let name = segment.segment_name.as_str();
+            if !self.structure.contains(name) {
+                self.skip(segment, "segment is not part of this message structure");
+                continue;
+            }
+
+            let start = if used && !elements[current].is_repeating() {
+                current + 1
+            } else {
+                current
+            };
+            let Some(found) = (start..elements.len()).find(|&i| elements[i].starts_with(name))
+            else {
+                if root {
+                    self.skip(
+                        segment,
+                        "segment is out of order for this message structure",
+                    );
+                    continue;
+                }
+                break;
+            };
+            if let Some(skipped) = elements.get(current + usize::from(used)..found) {
+                self.check_required(skipped);
+            }
+
+            match &elements[found] {
+                StructureElement::Segment { .. } => {
+                    nodes.push(StructureNode::Segment(self.position));
+                    self.position += 1;
+                }
+                StructureElement::Group {
+                    name: group_name,
+                    elements: group_elements,
+                    ..
+                } => {
+                    let children = self.fill(group_elements, false);
+                    nodes.push(StructureNode::Group {
+                        name: group_name.clone(),
+                        children,
+                    });
+                }
+            }
+            current = found;
+            used = true;
+        }
+
+        self.check_required(&elements[current + usize::from(used)..]);
+        nodes
+    }
+}
@@ -0,0 +1,330 @@
+use crate::errors::Hl7Error;
+use crate::escape::{Delimiters, Hl7EscapeHandler};
+use crate::message::Hl7Message;
+use crate::segments::{is_header_segment, Hl7Field, Hl7Segment, MshNumbering, NestedField};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hl7Path {
+    pub segment: String,
+    pub occurrence: Option<usize>,
+    pub field: usize,
+    pub repetition: Option<usize>,
+    pub component: Option<usize>,
+    pub subcomponent: Option<usize>,
+}
+
+impl Hl7Path {
+    pub fn parse(path: &str) -> Result<Self, Hl7Error> {
+        let invalid = |message: &str| Hl7Error::invalid_path(path, message);
+
+        let trimmed = path.trim();
+        let trimmed = trimmed.strip_prefix('/').unwrap_or(trimmed);
+        let parts: Vec<&str> = trimmed.split('-').collect();
+        if parts.len() < 2 {
+            return Err(invalid("expected SEG-field, e.g. PID-5"));
+        }
+        if parts.len() > 4 {
+            return Err(invalid("expected at most SEG-field-component-subcomponent"));
+        }
+
+        let (segment, occurrence) = split_index(parts[0]).map_err(|message| invalid(&message))?;
+        let is_segment_name = segment.len() == 3
+            && segment
+                .chars()
+                .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit());
+        if !is_segment_name {
+            return Err(invalid(&format!(
+                "segment name \"{}\" must be 3 uppercase letters or digits",
+                segment
+            )));
+        }
+
+        let (field, repetition) = split_index(parts[1]).map_err(|message| invalid(&message))?;
+        let field = parse_position(field, "field").map_err(|message| invalid(&message))?;
+
+        let position = |index: usize, name: &str| -> Result<Option<usize>, Hl7Error> {
+            parts
+                .get(index)
+                .map(|part| parse_position(part, name).map_err(|message| invalid(&message)))
+                .transpose()
+        };
+        let component = position(2, "component")?;
+        let subcomponent = position(3, "subcomponent")?;
+
+        Ok(Hl7Path {
+            segment: segment.to_string(),
+            occurrence,
+            field,
+            repetition,
+            component,
+            subcomponent,
+        })
+    }
+
+    pub fn get(&self, message: &Hl7Message) -> Result<Option<String>, Hl7Error> {
+        let occurrence = self.occurrence.unwrap_or(0);
+        let Some(segment) = self.segments(message).nth(occurrence) else {
+            return Ok(None);
+        };
+
+        let repetition = self.repetition.unwrap_or(0);
+        self.values(message, segment, Some(repetition))
+            .map(|values| values.into_iter().next())
+    }
+
+    pub fn get_all(&self, message: &Hl7Message) -> Result<Vec<String>, Hl7Error> {
+        let segments: Vec<&Hl7Segment> = match self.occurrence {
+            Some(occurrence) => self.segments(message).nth(occurrence).into_iter().collect(),
+            None => self.segments(message).collect(),
+        };
+
+        let mut values = Vec::new();
+        for segment in segments {
+            values.extend(self.values(message, segment, self.repetition)?);
+        }
+        Ok(values)
+    }
+
+    pub fn set(&self, message: &mut Hl7Message, value: &str) -> Result<(), Hl7Error> {
+        let occurrence = self.occurrence.unwrap_or(0);
+        let numbering = message.msh_numbering;
+        let delimiters = message.escape_handler.delimiters();
+        let segment = message
+            .segments
+            .iter_mut()
+            .filter(|segment| segment.segment_name == self.segment)
+            .nth(occurrence)
+            .ok_or_else(|| {
+                Hl7Error::invalid_path(
+                    &self.to_string(),
+                    &format!("segment {}({}) does not exist", self.segment, occurrence),
+                )
+            })?;
+
+        self.set_in_segment(segment, numbering, delimiters, value)
+    }
+
+    pub(crate) fn set_in_segment(
+        &self,
+        segment: &mut Hl7Segment,
+        numbering: MshNumbering,
+        delimiters: Delimiters,
+        value: &str,
+    ) -> Result<(), Hl7Error> {
+        self.check_positions()?;
+        if self.is_verbatim(numbering) {
+            return Err(Hl7Error::invalid_path(
+                &self.to_string(),
+                "the field separator and encoding characters cannot be set",
+            ));
+        }
+
+        let mut field = segment
+            .fields
+            .get(&self.field)
+            .map(Hl7Field::to_nested_json)
+            .unwrap_or_default();
+
+        let repetition = self.repetition.unwrap_or(0);
+        pad(&mut field, repetition + 1, || vec![vec![String::new()]]);
+        let components = &mut field[repetition];
+
+        let split = |text: &str, separator: char| -> Vec<String> {
+            text.split(separator).map(str::to_string).collect()
+        };
+        match (self.component, self.subcomponent) {
+            (None, _) => {
+                *components = value
+                    .split(delimiters.component)
+                    .map(|component| split(component, delimiters.subcomponent))
+                    .collect();
+            }
+            (Some(component), None) => {
+                pad(components, component, || vec![String::new()]);
+                components[component - 1] = split(value, delimiters.subcomponent);
+            }
+            (Some(component), Some(subcomponent)) => {
+                pad(components, component, || vec![String::new()]);
+                let subcomponents = &mut components[component - 1];
+                pad(subcomponents, subcomponent, String::new);
+                subcomponents[subcomponent - 1] = value.to_string();
+            }
+        }
+
+        for index in 1..self.field {
+            segment
+                .fields
+                .entry(index)
+                .or_insert_with(|| Hl7Field::plain(""));
+        }
+        segment
+            .fields
+            .insert(self.field, Hl7Field::from_nested(&field, delimiters));
+        Ok(())
+    }
+
+    pub fn escape_value(&self, value: &str, handler: &Hl7EscapeHandler) -> String {
+        let component_separator = handler.get_component_separator();
+        let subcomponent_separator = handler.get_subcomponent_separator();
+        let escape_component = |component: &str| {
+            component
+                .split(subcomponent_separator)
+                .map(|subcomponent| handler.escape(subcomponent))
+                .collect::<Vec<_>>()
+                .join(&subcomponent_separator.to_string())
+        };
+
+        match (self.component, self.subcomponent) {
+            (None, _) => value
+                .split(component_separator)
+                .map(escape_component)
+                .collect::<Vec<_>>()
+                .join(&component_separator.to_string()),
+            (Some(_), None) => escape_component(value),
+            (Some(_), Some(_)) => handler.escape(value),
+        }
+    }
+
+    fn segments<'m>(&self, message: &'m Hl7Message) -> impl Iterator<Item = &'m Hl7Segment> {
+        let name = self.segment.clone();
+        message
+            .segments
+            .iter()
+            .filter(move |segment| segment.segment_name == name)
+    }
+
+    fn check_positions(&self) -> Result<(), Hl7Error> {
+        let positions = [
+            ("field", Some(self.field)),
+            ("component", self.component),
+            ("subcomponent", self.subcomponent),
+        ];
+        for (name, position) in positions {
+            if position == Some(0) {
+                return Err(Hl7Error::invalid_path(
+                    &self.to_string(),
+                    &format!("{} numbers start at 1", name),
+                ));
+            }
+        }
+        if self.subcomponent.is_some() && self.component.is_none() {
+            return Err(Hl7Error::invalid_path(
+                &self.to_string(),
+                "a subcomponent needs a component",
+            ));
+        }
+        Ok(())
+    }
+
+    fn is_verbatim(&self, numbering: MshNumbering) -> bool {
+        is_header_segment(&self.segment)
+            && match numbering {
+                MshNumbering::Legacy => self.field == 1,
+                MshNumbering::Standard => self.field <= 2,
+            }
+    }
+
+    pub(crate) fn values(
+        &self,
+        message: &Hl7Message,
+        segment: &Hl7Segment,
+        repetition: Option<usize>,
+    ) -> Result<Vec<String>, Hl7Error> {
+        self.check_positions()?;
+        let Some(field) = segment.fields.get(&self.field) else {
+            return Ok(Vec::new());
+        };
+
+        if self.is_verbatim(message.msh_numbering) {
+            if self.component.is_some() {
+                return Err(Hl7Error::invalid_path(
+                    &self.to_string(),
+                    "the field separator and encoding characters have no components",
+                ));
+            }
+            return Ok(match repetition {
+                None | Some(0) => vec![field.value.clone()],
+                Some(_) => Vec::new(),
+            });
+        }
+
+        let mut repetitions: NestedField = field.to_nested_json();
+        if repetitions.is_empty() {
+            repetitions.push(vec![vec![String::new()]]);
+        }
+        let selected: Vec<&Vec<Vec<String>>> = match repetition {
+            Some(repetition) => repetitions.get(repetition).into_iter().collect(),
+            None => repetitions.iter().collect(),
+        };
+
+        let delimiters = message.escape_handler.delimiters();
+        let join_subcomponents =
+            |subcomponents: &Vec<String>| subcomponents.join(&delimiters.subcomponent.to_string());
+
+        Ok(selected
+            .into_iter()
+            .filter_map(|components| match (self.component, self.subcomponent) {
+                (None, _) => Some(
+                    components
+                        .iter()
+                        .map(join_subcomponents)
+                        .collect::<Vec<_>>()
+                        .join(&delimiters.component.to_string()),
+                ),
+                (Some(component), None) => components.get(component - 1).map(join_subcomponents),
+                (Some(component), Some(subcomponent)) => components
+                    .get(component - 1)
+                    .and_then(|subcomponents| subcomponents.get(subcomponent - 1))
+                    .cloned(),
+            })
+            .collect())
+    }
+}
+
+impl fmt::Display for Hl7Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.segment)?;
+        if let Some(occurrence) = self.occurrence {
+            write!(f, "({})", occurrence)?;
+        }
+        write!(f, "-{}", self.field)?;
+        if let Some(repetition) = self.repetition {
+            write!(f, "({})", repetition)?;
+        }
+        if let Some(component) = self.component {
+            write!(f, "-{}", component)?;
+        }
+        if let Some(subcomponent) = self.subcomponent {
+            write!(f, "-{}", subcomponent)?;
+        }
+        Ok(())
+    }
+}
+
+fn split_index(part: &str) -> Result<(&str, Option<usize>), String> {
+    let Some(open) = part.find('(') else {
+        return Ok((part, None));
+    };
+    let index = part[open + 1..]
+        .strip_suffix(')')
+        .ok_or_else(|| format!("unclosed index in \"{}\"", part))?;
+    let index = index
+        .parse()
+        .map_err(|_| format!("index \"{}\" is not a number", index))?;
+    Ok((&part[..open], Some(index)))
+}
+
+fn parse_position(part: &str, name: &str) -> Result<usize, String> {
+    match part.parse::<usize>() {
+        Ok(0) => Err(format!("{} numbers start at 1", name)),
+        Ok(position) => Ok(position),
+        Err(_) => Err(format!("{} \"{}\" is not a number", name, part)),
+    }
+}
+
+fn pad<T>(values: &mut Vec<T>, length: usize, fill: impl Fn() -> T) {
+    while values.len() < length {
+        values.push(fill());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::errors::Hl7Error;
+use crate::{Hl7BatchFile, Hl7BatchFileJson, MshNumbering, ParseOptions, SerializeOptions};
+
+const BATCH_FILE: &str = "FHS|^~\\&|SENDER|FAC|RECEIVER|FAC|20240101\rBHS|^~\\&|SENDER|FAC|RECEIVER|FAC|20240101\rMSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1\rPID|1||111||DOE^JOHN\rMSH|^~\\&|A|B|C|D|20240101||ADT^A08^ADT_A01|2|T|2.5.1\rPID|1||222||ROE^JANE\rBTS|2\rBHS|^~\\&|SENDER|FAC|RECEIVER|FAC|20240102\rMSH|^~\\&|A|B|C|D|20240102||ORU^R01^ORU_R01|3|T|2.5.1\rOBX|1|ST|CODE||Fish \\F\\ Chips\rBTS|1\rFTS|2";
+
+#[test]
+fn test_batch_file_structure() {
+    let batch_file = Hl7BatchFile::parse(BATCH_FILE, &ParseOptions::default()).unwrap();
+
+    assert_eq!(batch_file.header.as_ref().unwrap().segment_name, "FHS");
+    assert_eq!(batch_file.batches.len(), 2);
+    assert_eq!(batch_file.batches[0].messages.len(), 2);
+    assert_eq!(batch_file.batches[1].messages.len(), 1);
+    assert_eq!(
+        batch_file.batches[0].messages[1].segments[1]
+            .fields
+            .get(&3)
+            .unwrap()
+            .value,
+        "222"
+    );
+    assert_eq!(
+        batch_file.batches[1].trailer.as_ref().unwrap().segment_name,
+        "BTS"
+    );
+    assert_eq!(batch_file.trailer.as_ref().unwrap().segment_name, "FTS");
+    assert_eq!(batch_file.messages().count(), 3);
+}
+
+#[test]
+fn test_batch_file_json_nesting() {
+    let batch_file = Hl7BatchFile::parse(BATCH_FILE, &ParseOptions::default()).unwrap();
+    let json = batch_file.to_json();
+
+    assert_eq!(json.header.as_ref().unwrap().get("1").unwrap(), "^~\\&");
+    assert_eq!(json.batches[0].messages[0][0].get("8.2").unwrap(), "A01");
+    assert_eq!(
+        json.batches[1].messages[0][1].get("5").unwrap(),
+        "Fish | Chips"
+    );
+    assert_eq!(
+        json.batches[1].trailer.as_ref().unwrap().get("1").unwrap(),
+        "1"
+    );
+    assert_eq!(json.trailer.as_ref().unwrap().get("1").unwrap(), "2");
+}
+
+#[test]
+fn test_batch_file_roundtrip() {
+    for numbering in [MshNumbering::Legacy, MshNumbering::Standard] {
+        let parse_options = ParseOptions::new().with_msh_numbering(numbering);
+        let serialize_options = SerializeOptions::new().with_msh_numbering(numbering);
+
+        let input = BATCH_FILE.replace("Fish \\F\\ Chips", "Chips");
+        let json = Hl7BatchFile::parse(&input, &parse_options)
+            .unwrap()
+            .to_json();
+        let serialized: Hl7BatchFileJson =
+            serde_json::from_str(&serde_json::to_string(&json).unwrap()).unwrap();
+
+        assert_eq!(
+            serialized.to_hl7(&serialize_options),
+            input.replace('\r', "\n")
+        );
+    }
+}
+
+#[test]
+fn test_batch_count_mismatch() {
+    let input = BATCH_FILE.replace("BTS|2", "BTS|3");
+    let result = Hl7BatchFile::parse(&input, &ParseOptions::default());
+    assert!(matches!(
+        result,
+        Err(Hl7Error::CountMismatch { ref segment, declared: 3, actual: 2 }) if segment == "BTS"
+    ));
+
+    let input = BATCH_FILE.replace("FTS|2", "FTS|1");
+    let result = Hl7BatchFile::parse(&input, &ParseOptions::default());
+    assert!(matches!(
+        result,
+        Err(Hl7Error::CountMismatch { ref segment, declared: 1, actual: 2 }) if segment == "FTS"
+    ));
+
+    let input = BATCH_FILE.replace("FTS|2", "FTS|two");
+    let result = Hl7BatchFile::parse(&input, &ParseOptions::default());
+    assert!(matches!(result, Err(Hl7Error::FieldError { .. })));
+}
+
+#[test]
+fn test_batch_without_envelope() {
+    let input = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|T|2.5.1\rPID|1\rMSH|^~\\&|A|B|C|D|20240101||ADT^A01|2|T|2.5.1\rPID|2";
+    let batch_file = Hl7BatchFile::parse(input, &ParseOptions::default()).unwrap();
+
+    assert!(batch_file.header.is_none());
+    assert_eq!(batch_file.batches.len(), 1);
+    assert!(batch_file.batches[0].header.is_none());
+    assert_eq!(batch_file.batches[0].messages.len(), 2);
+}
+
+#[test]
+fn test_batch_invalid_structure() {
+    let result = Hl7BatchFile::parse("BHS|^~\\&|A\rPID|1", &ParseOptions::default());
+    assert!(matches!(result, Err(Hl7Error::InvalidFormat(_))));
+
+    let result = Hl7BatchFile::parse("FHS|^~\\&|A\rFTS|0\rMSH|^~\\&|A", &ParseOptions::default());
+    assert!(matches!(result, Err(Hl7Error::InvalidFormat(_))));
+
+    let result = Hl7BatchFile::parse("BHS|^~\\&|A\rFHS|^~\\&|A", &ParseOptions::default());
+    assert!(matches!(result, Err(Hl7Error::InvalidFormat(_))));
+
+    let result = Hl7BatchFile::parse(" \r\n", &ParseOptions::default());
+    assert!(matches!(result, Err(Hl7Error::EmptyMessage)));
+}
@@ -0,0 +1,99 @@
+use crate::errors::Hl7Error;
+use crate::escape::create_default_escape_handler;
+use crate::{Hl7Segment, MessageRef, MshNumbering, ParseOptions};
+use std::borrow::Cow;
+
+const MESSAGE: &str = "MSH|^~\\&|ADT1|HOSPITAL|LAB|HOSPITAL|20240101120000||ADT^A01^ADT_A01|MSG00001|T|2.5.1\rPID|1||PATID1234~PATID5678||DOE&VAN^JOHN\rNTE|1||Fish \\T\\ Chips\r";
+
+#[test]
+fn test_message_ref_segments_borrow_input() {
+    let message = MessageRef::parse(MESSAGE, MshNumbering::Legacy).unwrap();
+    let names: Vec<&str> = message.segments().iter().map(|seg| seg.name()).collect();
+
+    assert_eq!(names, vec!["MSH", "PID", "NTE"]);
+
+    let pid = message.segments_named("PID").next().unwrap();
+    let input_range = MESSAGE.as_bytes().as_ptr_range();
+    assert!(input_range.contains(&pid.as_str().as_ptr()));
+}
+
+#[test]
+fn test_message_ref_empty() {
+    assert!(MessageRef::parse("\r\n", MshNumbering::Legacy).is_err());
+}
+
+#[test]
+fn test_message_ref_malformed_encoding_characters() {
+    assert!(matches!(
+        MessageRef::parse("MSH|^~|ADT1\rPID|1", MshNumbering::Legacy),
+        Err(Hl7Error::InvalidFieldSeparators { .. })
+    ));
+
+    let headerless = MessageRef::parse("PID|1||A^B", MshNumbering::Legacy).unwrap();
+    assert_eq!(
+        headerless.segments()[0].field(3).unwrap().component(2),
+        Some("B")
+    );
+}
+
+#[test]
+fn test_field_ref_accessors() {
+    let message = MessageRef::parse(MESSAGE, MshNumbering::Legacy).unwrap();
+    let pid = message.segments_named("PID").next().unwrap();
+
+    let identifiers = pid.field(3).unwrap();
+    assert!(identifiers.has_repetitions());
+    let repetitions: Vec<&str> = identifiers.repetitions().map(|r| r.as_str()).collect();
+    assert_eq!(repetitions, vec!["PATID1234", "PATID5678"]);
+
+    let name = pid.field(5).unwrap();
+    assert_eq!(name.component(1), Some("DOE&VAN"));
+    assert_eq!(name.component(2), Some("JOHN"));
+    assert_eq!(name.component(3), None);
+    assert_eq!(name.subcomponent(1, 2), Some("VAN"));
+}
+
+#[test]
+fn test_field_ref_value_only_allocates_when_unescaping() {
+    let message = MessageRef::parse(MESSAGE, MshNumbering::Legacy).unwrap();
+    let handler = message.escape_handler();
+
+    let plain = message.segments()[1].field(1).unwrap().value(handler);
+    assert!(matches!(plain, Cow::Borrowed("1")));
+
+    let escaped = message.segments()[2].field(3).unwrap().value(handler);
+    assert!(matches!(escaped, Cow::Owned(_)));
+    assert_eq!(escaped, "Fish & Chips");
+}
+
+#[test]
+fn test_segment_ref_header_fields() {
+    let legacy = MessageRef::parse(MESSAGE, MshNumbering::Legacy).unwrap();
+    let msh = legacy.segments()[0];
+    assert_eq!(msh.field(1).unwrap().as_str(), "^~\\&");
+    assert_eq!(msh.field(1).unwrap().components().count(), 1);
+    assert_eq!(msh.field(8).unwrap().component(2), Some("A01"));
+
+    let standard = MessageRef::parse(MESSAGE, MshNumbering::Standard).unwrap();
+    let msh = standard.segments()[0];
+    assert_eq!(msh.field(1).unwrap().as_str(), "|");
+    assert_eq!(msh.field(2).unwrap().as_str(), "^~\\&");
+    assert_eq!(msh.field(9).unwrap().component(2), Some("A01"));
+}
+
+#[test]
+fn test_borrowed_matches_owned_parse() {
+    let handler = create_default_escape_handler();
+    for segment in MESSAGE.split('\r').filter(|seg| !seg.is_empty()) {
+        let message = MessageRef::parse(segment, MshNumbering::Legacy).unwrap();
+        let owned = message.segments()[0].to_owned_segment(&handler, true);
+        assert_eq!(owned, Hl7Segment::from_string(segment, Some(&handler)));
+    }
+
+    let message = MessageRef::parse(MESSAGE, MshNumbering::Standard).unwrap();
+    let options = ParseOptions::new().with_msh_numbering(MshNumbering::Standard);
+    assert_eq!(
+        message.to_json(true),
+        crate::hl7_to_json(MESSAGE, &options).unwrap()
+    );
+}
@@ -0,0 +1,180 @@
+use crate::errors::Hl7Error;
+use crate::{
+    Hl7Message, MessageBuilder, MshNumbering, NteBuilder, ObrBuilder, ObxBuilder, ParseOptions,
+    PidBuilder, Pv1Builder, SegmentBuilder,
+};
+
+fn adt() -> MessageBuilder {
+    MessageBuilder::new("ADT", "A04", "2.5.1")
+        .with_sending_application("REG")
+        .with_sending_facility("HOSP")
+        .with_timestamp("20240101120000")
+        .with_control_id("MSG1")
+}
+
+#[test]
+fn test_header_auto_fill() {
+    let builder = MessageBuilder::new("ADT", "A04", "2.5.1");
+    assert_eq!(builder.timestamp().len(), 14);
+    assert!(builder.timestamp().starts_with("20"));
+    assert!(builder.control_id().starts_with(builder.timestamp()));
+    assert_ne!(
+        builder.control_id(),
+        MessageBuilder::new("ADT", "A04", "2.5.1").control_id()
+    );
+
+    let hl7 = builder.build().unwrap();
+    let expected = format!(
+        "MSH|^~\\&|||||{}||ADT^A04^ADT_A01|{}|P|2.5.1",
+        builder.timestamp(),
+        builder.control_id()
+    );
+    assert_eq!(hl7, expected);
+}
+
+#[test]
+fn test_typed_segments() {
+    let hl7 = adt()
+        .with_message_structure("ADT_A01")
+        .with_segment(
+            PidBuilder::new()
+                .with_patient_identifier("111", "HOSP", "MR")
+                .with_patient_identifier("222", "", "")
+                .with_patient_name("DOE", "JOHN")
+                .with_date_of_birth("19700101")
+                .with_administrative_sex("M")
+                .with_address("1 Main St", "Springfield", "IL", "62701"),
+        )
+        .with_segment(
+            Pv1Builder::new()
+                .with_patient_class("O")
+                .with_assigned_location("CLINIC", "101", "")
+                .with_attending_doctor("123", "SMITH", "ANN"),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        hl7,
+        "MSH|^~\\&|REG|HOSP|||20240101120000||ADT^A04^ADT_A01|MSG1|P|2.5.1\nPID|1||111^^^HOSP^MR~222||DOE^JOHN||19700101|M|||1 Main St^^Springfield^IL^62701\nPV1|1|O|CLINIC^101||||123^SMITH^ANN"
+    );
+}
+
+#[test]
+fn test_set_ids_and_escaping() {
+    let hl7 = MessageBuilder::new("ORU", "R01", "2.5.1")
+        .with_timestamp("20240101120000")
+        .with_control_id("MSG2")
+        .with_segment(PidBuilder::new().with_patient_name("O|BRIEN", "PAT^RICK"))
+        .with_segment(ObrBuilder::new().with_universal_service_id("CBC", "Blood count", "L"))
+        .with_segment(
+            ObxBuilder::new()
+                .with_value_type("ST")
+                .with_value("Fish & Chips"),
+        )
+        .with_segment(NteBuilder::new().with_comment("first"))
+        .with_segment(NteBuilder::new().with_comment("second"))
+        .with_segment(ObxBuilder::new().with_value_type("NM").with_value("7.1"))
+        .with_segment(NteBuilder::new().with_comment("third"))
+        .with_segment(ObrBuilder::new().with_set_id("9"))
+        .with_segment(ObxBuilder::new().with_value_type("NM").with_value("4.5~5"))
+        .build()
+        .unwrap();
+
+    let lines: Vec<&str> = hl7.lines().collect();
+    assert_eq!(lines[1], "PID|1||||O\\F\\BRIEN^PAT\\S\\RICK");
+    assert_eq!(lines[2], "OBR|1|||CBC^Blood count^L");
+    assert_eq!(lines[3], "OBX|1|ST|||Fish \\T\\ Chips");
+    assert_eq!(lines[4], "NTE|1||first");
+    assert_eq!(lines[5], "NTE|2||second");
+    assert_eq!(lines[6], "OBX|2|NM|||7.1");
+    assert_eq!(lines[7], "NTE|1||third");
+    assert_eq!(lines[8], "OBR|9");
+    assert_eq!(lines[9], "OBX|1|NM|||4.5\\R\\5");
+
+    let message = Hl7Message::parse(&hl7, &ParseOptions::default()).unwrap();
+    assert_eq!(message.get("PID-5-1").unwrap().as_deref(), Some("O|BRIEN"));
+    assert_eq!(
+        message.get("OBX-5").unwrap().as_deref(),
+        Some("Fish & Chips")
+    );
+}
+
+#[test]
+fn test_custom_segments() {
+    let hl7 = adt()
+        .with_segment(
+            SegmentBuilder::new("ZPI")
+                .with_field(1, "custom")
+                .with_components(3, &["A", "", "C", ""])
+                .with_repetition(4, &["x"])
+                .with_repetition(4, &["y"]),
+        )
+        .build()
+        .unwrap();
+    assert!(hl7.ends_with("\nZPI|custom||A^^C|x~y"));
+}
+
+#[test]
+fn test_message_structure_defaults() {
+    let message_type = |builder: MessageBuilder| {
+        let hl7 = builder.build().unwrap();
+        let options = ParseOptions::new().with_msh_numbering(MshNumbering::Standard);
+        let message = Hl7Message::parse(&hl7, &options).unwrap();
+        message.get("MSH-9").unwrap().unwrap_or_default()
+    };
+    assert_eq!(
+        message_type(MessageBuilder::new("ADT", "A08", "2.5.1")),
+        "ADT^A08^ADT_A01"
+    );
+    assert_eq!(
+        message_type(MessageBuilder::new("ZZZ", "Z01", "2.5.1")),
+        "ZZZ^Z01^ZZZ_Z01"
+    );
+    assert_eq!(
+        message_type(MessageBuilder::new("ORU", "R01", "2.5.1").with_message_structure("ORU_R30")),
+        "ORU^R01^ORU_R30"
+    );
+}
+
+#[cfg(feature = "validation")]
+#[test]
+fn test_built_messages_pass_validation() {
+    use crate::Hl7Validator;
+
+    let adt = MessageBuilder::new("ADT", "A04", "2.5.1")
+        .with_segment(SegmentBuilder::new("EVN").with_field(1, "A04"))
+        .with_segment(PidBuilder::new().with_patient_identifier("111", "HOSP", "MR"))
+        .with_segment(Pv1Builder::new().with_patient_class("O"));
+    let oru = MessageBuilder::new("ORU", "R01", "2.5.1")
+        .with_segment(PidBuilder::new().with_patient_identifier("111", "HOSP", "MR"))
+        .with_segment(ObrBuilder::new().with_universal_service_id("CBC", "Blood count", "L"))
+        .with_segment(ObxBuilder::new().with_value_type("NM").with_value("7.1"));
+
+    for builder in [adt, oru] {
+        let hl7 = builder.build().unwrap();
+        let message = Hl7Message::parse(&hl7, &ParseOptions::default()).unwrap();
+        Hl7Validator::new()
+            .validate_message(&message.segments)
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_build_errors() {
+    assert!(matches!(
+        adt().with_segment(SegmentBuilder::new("zpi")).build(),
+        Err(Hl7Error::InvalidSegmentName { .. })
+    ));
+    assert!(matches!(
+        adt().with_segment(SegmentBuilder::new("MSH")).build(),
+        Err(Hl7Error::InvalidSegmentName { .. })
+    ));
+    assert_eq!(
+        MessageBuilder::new("ADT", "", "2.5.1")
+            .build()
+            .unwrap_err()
+            .to_string(),
+        "HL7 invalid format: MSH trigger event must not be empty"
+    );
+}
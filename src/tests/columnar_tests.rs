@@ -0,0 +1,106 @@
+use crate::columnar::{MESSAGE_CONTROL_ID_COLUMN, SEGMENT_SEQUENCE_COLUMN};
+use crate::{
+    hl7_to_record_batches, write_parquet, write_parquet_dir, MshNumbering, ParseOptions,
+    SegmentTables,
+};
+use arrow::array::{Array, StringArray, UInt32Array};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::File;
+
+const CORPUS: &str = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|MSG1|P|2.5\nEVN|A01\nPID|1||111~222||DOE^JOHN\nNK1|1|DOE^JANE\nNK1|2|DOE^JIM\nMSH|^~\\&|A|B|C|D|20240102||ADT^A08|MSG2|P|2.5\nEVN|A08\nPID|1||333||ROE^RICHARD^Q|||||||||||||||||||||||||||||||||Y";
+
+fn strings(batch: &arrow::array::RecordBatch, column: &str) -> Vec<Option<String>> {
+    let array = batch
+        .column_by_name(column)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    (0..array.len())
+        .map(|i| (!array.is_null(i)).then(|| array.value(i).to_string()))
+        .collect()
+}
+
+#[test]
+fn test_record_batches_per_segment() {
+    let batches = hl7_to_record_batches(CORPUS, &ParseOptions::default()).unwrap();
+    assert_eq!(
+        batches.keys().cloned().collect::<Vec<_>>(),
+        vec!["EVN", "MSH", "NK1", "PID"]
+    );
+
+    let pid = &batches["PID"];
+    assert_eq!(pid.num_rows(), 2);
+    assert_eq!(
+        strings(pid, MESSAGE_CONTROL_ID_COLUMN),
+        vec![Some("MSG1".to_string()), Some("MSG2".to_string())]
+    );
+    assert_eq!(strings(pid, "3[0]"), vec![Some("111".to_string()), None]);
+    assert_eq!(strings(pid, "3"), vec![None, Some("333".to_string())]);
+    assert_eq!(strings(pid, "5.3"), vec![None, Some("Q".to_string())]);
+
+    let names: Vec<String> = pid
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect();
+    assert_eq!(
+        &names[..2],
+        [MESSAGE_CONTROL_ID_COLUMN, SEGMENT_SEQUENCE_COLUMN]
+    );
+    assert_eq!(names[2..6], ["1", "2", "3", "3[0]"]);
+    assert_eq!(names.last().unwrap(), "38");
+
+    let nk1 = &batches["NK1"];
+    let sequences = nk1
+        .column_by_name(SEGMENT_SEQUENCE_COLUMN)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<UInt32Array>()
+        .unwrap();
+    assert_eq!(sequences.values().to_vec(), vec![4, 5]);
+    assert_eq!(
+        strings(nk1, "2.2"),
+        vec![Some("JANE".to_string()), Some("JIM".to_string())]
+    );
+}
+
+#[test]
+fn test_record_batches_standard_numbering() {
+    let options = ParseOptions::default().with_msh_numbering(MshNumbering::Standard);
+    let messages = crate::parse_messages(CORPUS, &options).unwrap();
+
+    let mut tables = SegmentTables::new();
+    tables.add_message(&messages[1]);
+    let batches = tables.finish().unwrap();
+
+    assert_eq!(
+        strings(&batches["MSH"], MESSAGE_CONTROL_ID_COLUMN),
+        vec![Some("MSG2".to_string())]
+    );
+    assert_eq!(
+        strings(&batches["MSH"], "9.2"),
+        vec![Some("A08".to_string())]
+    );
+}
+
+#[test]
+fn test_write_parquet_roundtrip() {
+    let batches = hl7_to_record_batches(CORPUS, &ParseOptions::default()).unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    write_parquet_dir(&batches, directory.path()).unwrap();
+
+    let file = File::open(directory.path().join("PID.parquet")).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap();
+    let read: Vec<_> = reader.map(Result::unwrap).collect();
+    assert_eq!(read.len(), 1);
+    assert_eq!(read[0], batches["PID"]);
+
+    let mut buffer = Vec::new();
+    write_parquet(&batches["EVN"], &mut buffer).unwrap();
+    assert_eq!(&buffer[..4], b"PAR1");
+}
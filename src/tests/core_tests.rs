@@ -1,7 +1,11 @@
 use crate::escape::Hl7EscapeHandler;
-use crate::segments::{Hl7Field, Hl7Segment};
+use crate::segments::Hl7Field;
+#[cfg(feature = "validation")]
+use crate::segments::Hl7Segment;
+#[cfg(feature = "validation")]
 use crate::validation::Hl7Validator;
 
+#[cfg(feature = "validation")]
 #[test]
 fn test_validation_with_valid_message() {
     let segments = vec![
@@ -14,6 +18,7 @@ fn test_validation_with_valid_message() {
     assert!(result.is_ok());
 }
 
+#[cfg(feature = "validation")]
 #[test]
 fn test_validation_with_invalid_message() {
     let segments = vec![Hl7Segment::from_string("INVALID|SEGMENT", None)];
@@ -23,6 +28,7 @@ fn test_validation_with_invalid_message() {
     assert!(result.is_err());
 }
 
+#[cfg(feature = "validation")]
 #[test]
 fn test_validation_lenient_mode() {
     let segments = vec![
@@ -36,6 +42,7 @@ fn test_validation_lenient_mode() {
     assert!(result.is_ok());
 }
 
+#[cfg(feature = "validation")]
 #[test]
 fn test_validator_custom_settings() {
     let segments = vec![
@@ -67,7 +74,48 @@ fn test_escape_handler_basic() {
 fn test_escape_handler_from_msh() {
     let handler = Hl7EscapeHandler::from_msh_field("^~\\&").unwrap();
 
-    assert_eq!(handler.get_component_separator(), '~');
+    assert_eq!(handler.get_field_separator(), '|');
+    assert_eq!(handler.get_component_separator(), '^');
+    assert_eq!(handler.get_repetition_separator(), '~');
+    assert_eq!(handler.get_subcomponent_separator(), '&');
+}
+
+#[test]
+fn test_escape_handler_from_msh_segment() {
+    let handler = Hl7EscapeHandler::from_msh_segment("MSH#!~\\&#ADT1#HOSPITAL").unwrap();
+
+    assert_eq!(handler.get_field_separator(), '#');
+    assert_eq!(handler.get_component_separator(), '!');
+    assert_eq!(handler.encoding_characters(), "!~\\&");
+    assert_eq!(handler.unescape("A\\F\\B"), "A#B");
+
+    assert!(Hl7EscapeHandler::from_msh_segment("PID|1").is_err());
+    assert!(Hl7EscapeHandler::from_msh_segment("MSH|^~|ADT1").is_err());
+}
+
+#[test]
+fn test_escape_handler_truncation_character() {
+    let handler = Hl7EscapeHandler::from_msh_segment("MSH|^~\\&#|ADT1|HOSPITAL").unwrap();
+
+    assert_eq!(handler.get_component_separator(), '^');
+    assert_eq!(handler.get_subcomponent_separator(), '&');
+    assert_eq!(handler.get_truncation_character(), Some('#'));
+    assert_eq!(handler.encoding_characters(), "^~\\&#");
+    assert_eq!(handler.escape("No. 5#"), "No. 5\\P\\");
+    assert_eq!(handler.unescape("No. 5\\P\\"), "No. 5#");
+
+    let message = crate::message::Hl7Message::parse(
+        "MSH|^~\\&#|ADT1|HOSP|LAB|HOSP|20240101||ADT^A01^ADT_A01|1|P|2.7\nPID|1||123^^^HOSP||DOE^JOHN",
+        &crate::message::ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(message.get("PID-5-2").unwrap().as_deref(), Some("JOHN"));
+    #[cfg(feature = "validation")]
+    assert!(Hl7Validator::new()
+        .validate_message(&message.segments)
+        .is_ok());
+
+    assert!(Hl7EscapeHandler::from_msh_segment("MSH|^~\\&#!|ADT1").is_err());
 }
 
 #[test]
@@ -102,9 +150,19 @@ fn test_field_with_escaping() {
 
     let field = Hl7Field::from_string("Test\\F\\Field\\S\\Component", Some(&handler));
     assert_eq!(field.value, "Test|Field^Component");
+    assert_eq!(field.components, None);
+
+    let nested = Hl7Field::from_string("A\\S\\B^C\\T\\D&E", Some(&handler));
+    assert_eq!(
+        nested.components,
+        Some(vec!["A^B".to_string(), "C&D&E".to_string()])
+    );
     assert_eq!(
-        field.components,
-        Some(vec!["Test|Field".to_string(), "Component".to_string()])
+        nested.subcomponents,
+        Some(vec![
+            vec!["A^B".to_string()],
+            vec!["C&D".to_string(), "E".to_string()]
+        ])
     );
 
     let back_to_string = handler.escape(&field.value);
@@ -0,0 +1,126 @@
+use crate::errors::Hl7Error;
+use crate::{DeidStrategy, Deidentifier, Hl7MutableMessage, MshNumbering, PhiCategory};
+
+const ADT: &str = "MSH|^~\\&|APP|FAC|EHR|FAC|20240101||ADT^A01|MSG1|P|2.5\nEVN|A01|20240315083000\nPID|1||12345^^^HOSP^MR~999^^^SSA||DOE^JOHN||19800229|M|||1 MAIN ST^^BOSTON^MA^02110||555-1234~555-9999|||||ACC1^^^HOSP|123-45-6789\nNK1|1|DOE^JANE|SPO|1 MAIN ST^^BOSTON^MA|555-1234\nPV1|1|I|WARD^101^A";
+
+fn deidentify(deidentifier: &mut Deidentifier, hl7: &str) -> (Hl7MutableMessage, Vec<String>) {
+    let mut message = Hl7MutableMessage::parse(hl7, MshNumbering::Legacy).unwrap();
+    let report = deidentifier.deidentify(&mut message).unwrap();
+    let paths = report.paths().into_iter().map(str::to_string).collect();
+    (message, paths)
+}
+
+#[test]
+fn test_default_rules() {
+    let mut deidentifier = Deidentifier::new("secret");
+    let (message, paths) = deidentify(&mut deidentifier, ADT);
+
+    assert_eq!(message.get("PID-5").unwrap().as_deref(), Some("NAME0001"));
+    assert_eq!(message.get("NK1-2").unwrap().as_deref(), Some("NAME0002"));
+    assert_eq!(message.get("PID-3-4").unwrap().as_deref(), Some("HOSP"));
+    assert_eq!(message.get("PID-3(1)-4").unwrap().as_deref(), Some("SSA"));
+    let mrn = message.get("PID-3-1").unwrap().unwrap();
+    assert_eq!(mrn.len(), 16);
+    assert_ne!(mrn, "12345");
+    assert_eq!(message.get("PID-11").unwrap().as_deref(), Some(""));
+    assert_eq!(message.get_all("PID-13").unwrap(), vec!["", ""]);
+    assert_eq!(message.get("PID-8").unwrap().as_deref(), Some("M"));
+    assert_eq!(message.get("MSH-6").unwrap().as_deref(), Some("20240101"));
+    assert_ne!(message.get("PID-7").unwrap().as_deref(), Some("19800229"));
+
+    assert_eq!(
+        paths,
+        vec![
+            "PID-5",
+            "NK1-2",
+            "PID-3-1",
+            "PID-3(1)-1",
+            "PID-18-1",
+            "PID-19",
+            "PID-11",
+            "NK1-4",
+            "PID-13",
+            "PID-13(1)",
+            "NK1-5",
+            "PID-7",
+            "EVN-2",
+        ]
+    );
+}
+
+#[test]
+fn test_date_shift_is_per_patient() {
+    let deidentifier = Deidentifier::new("secret").with_max_date_shift_days(30);
+    let offset = deidentifier.date_shift_days("12345");
+    assert!((-30..=30).contains(&offset));
+    assert_eq!(offset, deidentifier.date_shift_days("12345"));
+    assert_eq!(
+        Deidentifier::new("secret")
+            .with_max_date_shift_days(0)
+            .date_shift_days("12345"),
+        0
+    );
+
+    let mut deidentifier = deidentifier;
+    let (message, _) = deidentify(&mut deidentifier, ADT);
+    let birth = message.get("PID-7").unwrap().unwrap();
+    let event = message.get("EVN-2").unwrap().unwrap();
+    assert_eq!(birth.len(), 8);
+    assert!(event.ends_with("083000"));
+
+    let days = |value: &str| {
+        crate::utils::days_from_civil(
+            value[..4].parse().unwrap(),
+            value[4..6].parse().unwrap(),
+            value[6..8].parse().unwrap(),
+        )
+    };
+    assert_eq!(days(&birth) - days("19800229"), offset);
+    assert_eq!(days(&event) - days("20240315"), offset);
+}
+
+#[test]
+fn test_pseudonyms_are_consistent_across_batch() {
+    let mut deidentifier = Deidentifier::new("secret")
+        .with_strategy(PhiCategory::Identifier, DeidStrategy::Pseudonym)
+        .with_strategy(PhiCategory::Date, DeidStrategy::Keep);
+    let second = ADT.replace("DOE^JOHN", "ROE^RICHARD");
+
+    let (first, _) = deidentify(&mut deidentifier, ADT);
+    let (second, _) = deidentify(&mut deidentifier, &second);
+    assert_eq!(first.get("PID-5").unwrap().as_deref(), Some("NAME0001"));
+    assert_eq!(second.get("PID-5").unwrap().as_deref(), Some("NAME0003"));
+    assert_eq!(second.get("NK1-2").unwrap(), first.get("NK1-2").unwrap());
+    assert_eq!(first.get("PID-3-1").unwrap().as_deref(), Some("ID0001"));
+    assert_eq!(second.get("PID-3-1").unwrap().as_deref(), Some("ID0001"));
+    assert_eq!(second.get("PID-7").unwrap().as_deref(), Some("19800229"));
+
+    let mut other_key = Deidentifier::new("other")
+        .with_strategy(PhiCategory::Name, DeidStrategy::Hash)
+        .with_redaction_text("XXX");
+    let mut same_key =
+        Deidentifier::new("secret").with_strategy(PhiCategory::Name, DeidStrategy::Hash);
+    let (hashed, _) = deidentify(&mut same_key, ADT);
+    let (rehashed, _) = deidentify(&mut other_key, ADT);
+    assert_ne!(hashed.get("PID-5").unwrap(), rehashed.get("PID-5").unwrap());
+    assert_eq!(rehashed.get("PID-11").unwrap().as_deref(), Some("XXX"));
+}
+
+#[test]
+fn test_custom_rules_and_errors() {
+    let mut deidentifier = Deidentifier::new("secret")
+        .with_rule("NTE-3", PhiCategory::Name)
+        .with_strategy(PhiCategory::Name, DeidStrategy::Redact);
+    let (message, paths) = deidentify(&mut deidentifier, &format!("{}\nNTE|1||Call JOHN", ADT));
+    assert_eq!(message.get("NTE-3").unwrap().as_deref(), Some(""));
+    assert!(paths.contains(&"NTE-3".to_string()));
+
+    let mut deidentifier = Deidentifier::new("secret").with_rule("MSH-1", PhiCategory::Name);
+    let mut message = Hl7MutableMessage::parse(ADT, MshNumbering::Legacy).unwrap();
+    assert!(matches!(
+        deidentifier.deidentify(&mut message),
+        Err(Hl7Error::InvalidPath { .. })
+    ));
+    assert!(PhiCategory::parse("ssn").is_err());
+    assert!(DeidStrategy::parse("shuffle").is_err());
+}
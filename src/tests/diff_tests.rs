@@ -0,0 +1,121 @@
+use crate::{diff_messages, ChangeKind, Hl7Change, Hl7Message, MessageDiff, ParseOptions};
+
+const OLD: &str = "MSH|^~\\&|LAB|FAC|EHR|FAC|20240101||ORU^R01|MSG1|P|2.5\nPID|1||111^^^HOSP||DOE^JOHN||19700101|M\nOBR|1|||CBC\nOBX|1|NM|WBC||7.1|10*3/uL\nOBX|2|NM|RBC||4.5\nNTE|1||first note";
+const NEW: &str = "MSH|^~\\&|LAB|FAC|EHR|FAC|20240101||ORU^R01|MSG2|P|2.5\nPID|1||111^^^HOSP~222||DOE^JANE||19700101\nOBR|1|||CBC\nOBX|2|NM|RBC||4.6\nOBX|1|NM|WBC||7.1|10*3/uL\nOBX|3|NM|HGB||13.5";
+
+fn parse(hl7: &str) -> Hl7Message {
+    Hl7Message::parse(hl7, &ParseOptions::default()).unwrap()
+}
+
+fn change(
+    kind: ChangeKind,
+    segment: &str,
+    path: &str,
+    old_value: Option<&str>,
+    new_value: Option<&str>,
+) -> Hl7Change {
+    Hl7Change {
+        kind,
+        segment: segment.to_string(),
+        path: path.to_string(),
+        old_value: old_value.map(str::to_string),
+        new_value: new_value.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_identical_messages() {
+    let diff = diff_messages(&parse(OLD), &parse(OLD));
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_unified_text(), "--- old\n+++ new");
+}
+
+#[test]
+fn test_component_changes_and_set_id_matching() {
+    let diff = MessageDiff::between(&parse(OLD), &parse(NEW));
+    assert_eq!(
+        diff.changes,
+        vec![
+            change(
+                ChangeKind::Changed,
+                "MSH",
+                "MSH-9",
+                Some("MSG1"),
+                Some("MSG2")
+            ),
+            change(ChangeKind::Added, "PID", "PID-3(1)", None, Some("222")),
+            change(
+                ChangeKind::Changed,
+                "PID",
+                "PID-5-2",
+                Some("JOHN"),
+                Some("JANE")
+            ),
+            change(ChangeKind::Removed, "PID", "PID-8", Some("M"), None),
+            change(
+                ChangeKind::Changed,
+                "OBX",
+                "OBX-5",
+                Some("4.5"),
+                Some("4.6")
+            ),
+            change(
+                ChangeKind::Removed,
+                "NTE",
+                "NTE",
+                Some("NTE|1||first note"),
+                None
+            ),
+            change(
+                ChangeKind::Added,
+                "OBX(2)",
+                "OBX(2)",
+                None,
+                Some("OBX|3|NM|HGB||13.5")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_segments_without_set_id_match_by_occurrence() {
+    let old = parse("MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\nEVN|A01|20240101\nZZZ|a\nZZZ|b");
+    let new =
+        parse("MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\nEVN|A01|20240102\nZZZ|a\nZZZ|c^d");
+
+    let diff = diff_messages(&old, &new);
+    let paths: Vec<&str> = diff.changes.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(paths, vec!["EVN-2", "ZZZ(1)-1-1", "ZZZ(1)-1-2"]);
+    assert_eq!(diff.changes[1].kind, ChangeKind::Changed);
+    assert_eq!(diff.changes[2].kind, ChangeKind::Added);
+}
+
+#[test]
+fn test_unified_text() {
+    let diff = diff_messages(&parse(OLD), &parse(NEW));
+    let text = diff.to_unified_text();
+    assert!(text.starts_with(
+        "--- old\n+++ new\n@@ MSH @@\n-MSH-9: MSG1\n+MSH-9: MSG2\n@@ PID @@\n+PID-3(1): 222\n-PID-5-2: JOHN\n+PID-5-2: JANE\n-PID-8: M\n"
+    ));
+    assert!(text.ends_with(
+        "@@ NTE @@\n-NTE: NTE|1||first note\n@@ OBX(2) @@\n+OBX(2): OBX|3|NM|HGB||13.5"
+    ));
+    assert_eq!(diff.to_string(), text);
+
+    let json = serde_json::to_value(&diff.changes[0]).unwrap();
+    assert_eq!(json["kind"], "changed");
+}
+
+#[test]
+fn test_added_segment_text_keeps_escapes() {
+    let header = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5";
+    let added = "NTE|1||Fish \\T\\ Chips^A\\F\\B&C~\\E\\";
+    let new = format!("{header}\n{added}");
+
+    for options in [ParseOptions::default(), ParseOptions::lossless()] {
+        let old = Hl7Message::parse(header, &options).unwrap();
+        let new = Hl7Message::parse(&new, &options).unwrap();
+        let diff = diff_messages(&old, &new);
+        assert_eq!(diff.changes[0].new_value.as_deref(), Some(added));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    hl7_to_google_parsed_data, GoogleParsedData, GoogleSegment, Hl7Message, MshNumbering,
+    ParseOptions, SerializeOptions,
+};
+use serde_json::json;
+
+const MESSAGE: &str = "MSH|^~\\&|SENDER|FAC|RECEIVER|FAC|20240101||ORU^R01|1|P|2.5.1\nPID|1||111^^^HOSP~222^^^SSA||DOE^JOHN\nOBX|1|ST|CODE||Fish \\F\\ Chips\nOBX|2|NM|GLU||5.6";
+
+#[test]
+fn test_google_parsed_data_export() {
+    let parsed_data = hl7_to_google_parsed_data(MESSAGE, &ParseOptions::default()).unwrap();
+
+    let msh = &parsed_data.segments[0];
+    assert_eq!(msh.segment_id, "MSH");
+    assert_eq!(msh.set_id, "");
+    assert_eq!(msh.fields.get("0").unwrap(), "MSH");
+    assert_eq!(msh.fields.get("1").unwrap(), "^~\\&");
+    assert_eq!(msh.fields.get("8.1").unwrap(), "ORU");
+    assert!(!msh.fields.contains_key("7"));
+
+    let pid = &parsed_data.segments[1];
+    assert_eq!(pid.set_id, "1");
+    assert_eq!(pid.fields.get("3[0].1").unwrap(), "111");
+    assert_eq!(pid.fields.get("3[1].4").unwrap(), "SSA");
+    assert_eq!(pid.fields.get("5[0].2").unwrap(), "JOHN");
+    assert!(!pid.fields.contains_key("5.2"));
+
+    let obx = &parsed_data.segments[3];
+    assert_eq!(obx.set_id, "2");
+    assert_eq!(
+        parsed_data.segments[2].fields.get("5[0]").unwrap(),
+        "Fish | Chips"
+    );
+}
+
+#[test]
+fn test_google_parsed_data_standard_numbering() {
+    let legacy = hl7_to_google_parsed_data(MESSAGE, &ParseOptions::default()).unwrap();
+    let options = ParseOptions::new().with_msh_numbering(MshNumbering::Standard);
+    let message = Hl7Message::parse(MESSAGE, &options).unwrap();
+
+    assert_eq!(GoogleParsedData::from_message(&message), legacy);
+}
+
+#[test]
+fn test_google_parsed_data_serde_shape() {
+    let parsed_data = hl7_to_google_parsed_data(MESSAGE, &ParseOptions::default()).unwrap();
+    let value = serde_json::to_value(&parsed_data).unwrap();
+
+    assert_eq!(value["segments"][1]["segmentId"], "PID");
+    assert_eq!(value["segments"][1]["setId"], "1");
+    assert_eq!(value["segments"][1]["fields"]["5[0].1"], "DOE");
+}
+
+#[test]
+fn test_google_parsed_data_import() {
+    let parsed_data = hl7_to_google_parsed_data(MESSAGE, &ParseOptions::default()).unwrap();
+    assert_eq!(parsed_data.to_hl7(&SerializeOptions::default()), MESSAGE);
+
+    let standard = SerializeOptions::new().with_msh_numbering(MshNumbering::Standard);
+    assert_eq!(parsed_data.to_hl7(&standard), MESSAGE);
+
+    let resource = json!({
+        "name": "projects/p/locations/l/datasets/d/hl7V2Stores/s/messages/m",
+        "parsedData": {
+            "segments": [
+                {"segmentId": "MSH", "fields": {"0": "MSH", "1": "^~\\&", "2": "A", "8.1": "ADT", "8.2": "A01"}},
+                {"segmentId": "PID", "setId": "1", "fields": {"0": "PID", "1": "1", "3[0].1": "111", "3[1].1": "222"}}
+            ]
+        }
+    });
+    let imported = GoogleParsedData::from_value(resource).unwrap();
+    assert_eq!(
+        imported.to_hl7(&SerializeOptions::default()),
+        "MSH|^~\\&|A||||||ADT^A01\nPID|1||111~222"
+    );
+
+    let segment = GoogleSegment {
+        segment_id: "NTE".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(segment.to_json().get("segment_name").unwrap(), "NTE");
+
+    assert!(GoogleParsedData::from_value(json!({"segments": "none"})).is_err());
+}
+
+#[test]
+fn test_google_parsed_data_matches_store_sample() {
+    let resource: serde_json::Value =
+        serde_json::from_str(include_str!("test_data/google_store_message.json")).unwrap();
+    let hl7_string = "MSH|^~\\&|A|SEND_FACILITY|A|A|20180101000000||TYPE^A|20180101000000|T|0.0|||AA||00|ASCII\rEVN|A00|20180101040000\rPID||14\u{1}111^^^^MR|11\u{1}111^^^^MR||SMITH^JOHN";
+
+    let store = GoogleParsedData::from_value(resource).unwrap();
+    let exported = hl7_to_google_parsed_data(hl7_string, &ParseOptions::default()).unwrap();
+    assert_eq!(exported.segments.len(), store.segments.len());
+    for (exported, store) in exported.segments.iter().zip(&store.segments) {
+        assert_eq!(exported.segment_id, store.segment_id);
+        assert_eq!(exported.fields, store.fields);
+    }
+
+    assert_eq!(
+        store.to_hl7(&SerializeOptions::default()),
+        hl7_string.replace('\r', "\n")
+    );
+}
+
+#[test]
+fn test_google_parsed_data_unknown_segment_keys() {
+    let parsed_data =
+        hl7_to_google_parsed_data("MSH|^~\\&|A\nZPD|1|A^B|C~D", &ParseOptions::default()).unwrap();
+    let zpd = &parsed_data.segments[1].fields;
+    assert_eq!(zpd.get("1").unwrap(), "1");
+    assert_eq!(zpd.get("2[0].1").unwrap(), "A");
+    assert_eq!(zpd.get("3[1]").unwrap(), "D");
+}
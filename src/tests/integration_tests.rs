@@ -1,3 +1,4 @@
+use crate::hl7_json::Hl7Json;
 use crate::json_hl7::JsonHl7;
 use crate::segments::Hl7Segment;
 use crate::utils;
@@ -207,7 +208,7 @@ fn test_json_to_hl7_roundtrip_complex() {
     let json_result: Vec<BTreeMap<String, String>> =
         segments.iter().map(|seg| seg.to_json()).collect();
 
-    let json_hl7 = JsonHl7::new(json_result);
+    let json_hl7 = JsonHl7::new(json_result, None);
     let converted_hl7 = json_hl7._convert_json_to_hl7(true);
 
     let converted_lines: Vec<&str> = converted_hl7.lines().collect();
@@ -264,7 +265,7 @@ fn test_json_to_hl7_roundtrip_edge_cases() {
     let json_result: Vec<BTreeMap<String, String>> =
         segments.iter().map(|seg| seg.to_json()).collect();
 
-    let json_hl7 = JsonHl7::new(json_result);
+    let json_hl7 = JsonHl7::new(json_result, None);
     let converted_hl7 = json_hl7._convert_json_to_hl7(true);
 
     let converted_lines: Vec<&str> = converted_hl7.lines().collect();
@@ -321,7 +322,7 @@ fn test_json_to_hl7_from_existing_example() {
     let json_result: Vec<BTreeMap<String, String>> =
         segments.iter().map(|seg| seg.to_json()).collect();
 
-    let json_hl7 = JsonHl7::new(json_result);
+    let json_hl7 = JsonHl7::new(json_result, None);
     let converted_hl7 = json_hl7._convert_json_to_hl7(true);
 
     let converted_lines: Vec<&str> = converted_hl7.lines().collect();
@@ -363,3 +364,68 @@ fn test_json_to_hl7_from_existing_example() {
         }
     }
 }
+
+#[test]
+fn test_hl7_to_json_with_custom_delimiters() {
+    let hl7_string = "MSH#!~\\&#ADT1#HOSPITAL#LAB#HOSPITAL#20240101120000##ADT!A01!ADT_A01#MSG00001#T#2.5.1\nPID#1##12345!!!HOSP&1.2.3&ISO~67890##DOE!JOHN\\F\\JR";
+    let hl7_json = Hl7Json::new(hl7_string.to_string(), None, None, None, None);
+    let json_result = hl7_json._convert_hl7_to_json().unwrap();
+
+    let msh = &json_result[0];
+    assert_eq!(msh.get("1").unwrap(), "!~\\&");
+    assert_eq!(msh.get("2").unwrap(), "ADT1");
+    assert_eq!(msh.get("8.1").unwrap(), "ADT");
+    assert_eq!(msh.get("8.2").unwrap(), "A01");
+
+    let pid = &json_result[1];
+    assert_eq!(pid.get("3[0].1").unwrap(), "12345");
+    assert_eq!(pid.get("3[0].4.2").unwrap(), "1.2.3");
+    assert_eq!(pid.get("3[1]").unwrap(), "67890");
+    assert_eq!(pid.get("5.1").unwrap(), "DOE");
+    assert_eq!(pid.get("5.2").unwrap(), "JOHN#JR");
+}
+
+#[test]
+fn test_hl7_to_json_with_custom_delimiters_without_escaping() {
+    let hl7_string = "MSH|!~\\&|ADT1|HOSPITAL\nPID|1||12345!!!HOSP&1.2.3";
+    let hl7_json = Hl7Json::new(hl7_string.to_string(), None, None, Some(false), None);
+    let json_result = hl7_json._convert_hl7_to_json().unwrap();
+
+    assert_eq!(json_result[0].get("1").unwrap(), "!~\\&");
+    assert_eq!(json_result[1].get("3.1").unwrap(), "12345");
+    assert_eq!(json_result[1].get("3.4.1").unwrap(), "HOSP");
+}
+
+#[test]
+fn test_json_to_hl7_with_custom_encoding_characters() {
+    let original_hl7 =
+        "MSH|!~\\&|ADT1|HOSPITAL|||||ADT!A01\nPID|1||12345!!!HOSP&1.2.3~67890||DOE!JOHN!JR";
+    let hl7_json = Hl7Json::new(original_hl7.to_string(), None, None, None, None);
+    let json_result = hl7_json._convert_hl7_to_json().unwrap();
+
+    let json_hl7 = JsonHl7::new(json_result, None);
+    let converted_hl7 = json_hl7._convert_json_to_hl7(true);
+
+    assert_eq!(converted_hl7, original_hl7);
+}
+
+#[cfg(feature = "validation")]
+#[test]
+fn test_standard_msh_numbering_roundtrip() {
+    let original_hl7 = "MSH|^~\\&|ADT1|HOSPITAL|LAB|HOSPITAL|20240101120000||ADT^A01^ADT_A01|MSG00001|T|2.5.1\nPID|1||PATID1234||DOE^JOHN";
+    let hl7_json = Hl7Json::new(
+        original_hl7.to_string(),
+        Some(true),
+        Some(true),
+        None,
+        Some(true),
+    );
+    let json_result = hl7_json._convert_hl7_to_json().unwrap();
+
+    assert_eq!(json_result[0].get("1").unwrap(), "|");
+    assert_eq!(json_result[0].get("9.2").unwrap(), "A01");
+    assert_eq!(json_result[0].get("12").unwrap(), "2.5.1");
+
+    let json_hl7 = JsonHl7::new(json_result, Some(true));
+    assert_eq!(json_hl7._convert_json_to_hl7(true), original_hl7);
+}
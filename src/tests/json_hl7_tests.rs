@@ -13,7 +13,7 @@ fn test_json_to_hl7_with_repetitions() {
     segment_json.insert("4".to_string(), "".to_string());
     segment_json.insert("5".to_string(), "Result".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(hl7_string, "OBX|1|TX|HbA1c~CBC~Glucose||Result");
@@ -32,7 +32,7 @@ fn test_json_to_hl7_with_repetitions_and_components() {
     segment_json.insert("4".to_string(), "".to_string());
     segment_json.insert("5".to_string(), "Result".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(hl7_string, "OBX|1|TX|HbA1c^Test1~CBC^Test2||Result");
@@ -53,7 +53,7 @@ fn test_json_to_hl7_roundtrip() {
     segment_json.insert("4".to_string(), "".to_string());
     segment_json.insert("5".to_string(), "Result".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let converted_hl7 = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(converted_hl7, original_hl7);
@@ -71,7 +71,7 @@ fn test_json_to_hl7_mixed_repetitions_and_regular_fields() {
     segment_json.insert("4.2".to_string(), "Component2".to_string());
     segment_json.insert("5".to_string(), "Result".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(
@@ -88,7 +88,7 @@ fn test_json_to_hl7_escaping_component_separator() {
     segment_json.insert("5.1".to_string(), "DOE^JOHN".to_string());
     segment_json.insert("5.2".to_string(), "JOHN".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(hl7_string, "PID|1||||DOE\\S\\JOHN^JOHN");
@@ -101,7 +101,7 @@ fn test_json_to_hl7_escaping_field_separator() {
     segment_json.insert("1".to_string(), "1".to_string());
     segment_json.insert("5.1".to_string(), "DOE|JOHN".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(hl7_string, "PID|1||||DOE\\F\\JOHN");
@@ -114,7 +114,7 @@ fn test_json_to_hl7_escaping_repetition_separator() {
     segment_json.insert("1".to_string(), "1".to_string());
     segment_json.insert("5.1".to_string(), "DOE~JOHN".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(hl7_string, "PID|1||||DOE\\R\\JOHN");
@@ -127,10 +127,10 @@ fn test_json_to_hl7_escaping_escape_character() {
     segment_json.insert("1".to_string(), "1".to_string());
     segment_json.insert("5.1".to_string(), "DOE\\JOHN".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
-    assert_eq!(hl7_string, "PID|1||||DOE\\\\JOHN");
+    assert_eq!(hl7_string, "PID|1||||DOE\\E\\JOHN");
 }
 
 #[test]
@@ -140,7 +140,7 @@ fn test_json_to_hl7_escaping_subcomponent_separator() {
     segment_json.insert("1".to_string(), "1".to_string());
     segment_json.insert("5.1".to_string(), "DOE&JOHN".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(hl7_string, "PID|1||||DOE\\T\\JOHN");
@@ -156,12 +156,12 @@ fn test_json_to_hl7_escaping_multiple_special_chars() {
         "DOE^JOHN|SMITH~BROWN\\JONES&WILSON".to_string(),
     );
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(
         hl7_string,
-        "PID|1||||DOE\\S\\JOHN\\F\\SMITH\\R\\BROWN\\\\JONES\\T\\WILSON"
+        "PID|1||||DOE\\S\\JOHN\\F\\SMITH\\R\\BROWN\\E\\JONES\\T\\WILSON"
     );
 }
 
@@ -173,7 +173,7 @@ fn test_json_to_hl7_escaping_with_repetitions() {
     segment_json.insert("3[0]".to_string(), "ID1^TYPE1".to_string());
     segment_json.insert("3[1]".to_string(), "ID2^TYPE2".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(hl7_string, "PID|1||ID1\\S\\TYPE1~ID2\\S\\TYPE2");
@@ -188,7 +188,7 @@ fn test_json_to_hl7_escaping_with_components() {
     segment_json.insert("5.2".to_string(), "JOHN^MIDDLE".to_string());
     segment_json.insert("5.3".to_string(), "A".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
     assert_eq!(hl7_string, "PID|1||||DOE^JOHN\\S\\MIDDLE^A");
@@ -205,10 +205,13 @@ fn test_json_to_hl7_escaping_newlines_and_spaces() {
         "Line1\nLine2\rLine3\r\n  Spaces  \tTab".to_string(),
     );
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_string = json_hl7._convert_json_to_hl7(true);
 
-    assert_eq!(hl7_string, "NTE|1|L|Line1\\X0A\\Line2\\X0D\\Line3\\X0D\\\\X0A\\\\X20\\\\X20\\Spaces\\X20\\\\X20\\\\X09\\Tab");
+    assert_eq!(
+        hl7_string,
+        "NTE|1|L|Line1\\X0A\\Line2\\X0D\\Line3\\X0D\\\\X0A\\  Spaces  \tTab"
+    );
 }
 
 #[test]
@@ -219,10 +222,176 @@ fn test_json_to_hl7_unescaped() {
     segment_json.insert("5.1".to_string(), "DOE^JOHN".to_string());
     segment_json.insert("5.2".to_string(), "JOHN".to_string());
 
-    let json_hl7 = JsonHl7::new(vec![segment_json]);
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
     let hl7_escaped = json_hl7._convert_json_to_hl7(true);
     let hl7_unescaped = json_hl7._convert_json_to_hl7(false);
 
     assert_eq!(hl7_escaped, "PID|1||||DOE\\S\\JOHN^JOHN");
     assert_eq!(hl7_unescaped, "PID|1||||DOE^JOHN^JOHN");
 }
+
+#[test]
+fn test_json_to_hl7_with_subcomponents() {
+    let mut segment_json = BTreeMap::new();
+    segment_json.insert("segment_name".to_string(), "PID".to_string());
+    segment_json.insert("1".to_string(), "1".to_string());
+    segment_json.insert("3.1".to_string(), "12345".to_string());
+    segment_json.insert("3.4.1".to_string(), "HOSP".to_string());
+    segment_json.insert("3.4.2".to_string(), "1.2.3".to_string());
+    segment_json.insert("3.4.3".to_string(), "ISO".to_string());
+    segment_json.insert("3.5".to_string(), "MR".to_string());
+
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
+    let hl7_string = json_hl7._convert_json_to_hl7(true);
+
+    assert_eq!(hl7_string, "PID|1||12345^^^HOSP&1.2.3&ISO^MR");
+}
+
+#[test]
+fn test_json_to_hl7_with_subcomponents_in_repetitions() {
+    let mut segment_json = BTreeMap::new();
+    segment_json.insert("segment_name".to_string(), "PID".to_string());
+    segment_json.insert("1".to_string(), "1".to_string());
+    segment_json.insert("3[0].1".to_string(), "12345".to_string());
+    segment_json.insert("3[0].4.1".to_string(), "HOSP".to_string());
+    segment_json.insert("3[0].4.3".to_string(), "ISO".to_string());
+    segment_json.insert("3[1].1".to_string(), "67890".to_string());
+    segment_json.insert("3[1].4.2".to_string(), "2.16.840".to_string());
+
+    let json_hl7 = JsonHl7::new(vec![segment_json], None);
+    let hl7_string = json_hl7._convert_json_to_hl7(true);
+
+    assert_eq!(hl7_string, "PID|1||12345^^^HOSP&&ISO~67890^^^&2.16.840");
+}
+
+#[test]
+fn test_json_to_hl7_subcomponents_roundtrip() {
+    let original_hl7 = "PID|1||12345^^^HOSP&1.2.3&ISO^MR~67890^^^CLINIC&2.16.840&ISO^PI";
+    let segment = crate::segments::Hl7Segment::from_string(original_hl7, None);
+
+    let json_hl7 = JsonHl7::new(vec![segment.to_json()], None);
+    let converted_hl7 = json_hl7._convert_json_to_hl7(true);
+
+    assert_eq!(converted_hl7, original_hl7);
+}
+
+#[test]
+fn test_json_to_hl7_standard_msh_numbering() {
+    let mut segment_json = BTreeMap::new();
+    segment_json.insert("segment_name".to_string(), "MSH".to_string());
+    segment_json.insert("1".to_string(), "#".to_string());
+    segment_json.insert("2".to_string(), "^~\\&".to_string());
+    segment_json.insert("3".to_string(), "ADT1".to_string());
+    segment_json.insert("9.1".to_string(), "ADT".to_string());
+    segment_json.insert("9.2".to_string(), "A01".to_string());
+    segment_json.insert("12".to_string(), "2.5.1".to_string());
+
+    let json_hl7 = JsonHl7::new(vec![segment_json], Some(true));
+    let hl7_string = json_hl7._convert_json_to_hl7(true);
+
+    assert_eq!(hl7_string, "MSH#^~\\&#ADT1######ADT^A01###2.5.1");
+}
+
+#[test]
+fn test_json_input_nested() {
+    use crate::json_hl7::JsonInput;
+
+    let json = r#"[
+        {"segment_name": "MSH", "fields": [[[["^~\\&"]]], [[["ADT1"]]]]},
+        {"segment_name": "PID", "fields": [[[["1"]]], [], [[["111"]], [["222"], [], [], ["HOSP", "ISO"]]]]}
+    ]"#;
+    let input: JsonInput = serde_json::from_str(json).unwrap();
+    let json_hl7 = JsonHl7::new(input.into_flat(), None);
+
+    assert_eq!(
+        json_hl7._convert_json_to_hl7(true),
+        "MSH|^~\\&|ADT1\nPID|1||111~222^^^HOSP&ISO"
+    );
+
+    let flat: JsonInput = serde_json::from_str(r#"[{"segment_name": "PID", "1": "1"}]"#).unwrap();
+    assert_eq!(flat.into_flat()[0].get("1").unwrap(), "1");
+}
+
+#[test]
+fn test_json_input_typed_null() {
+    use crate::json_hl7::JsonInput;
+
+    let json =
+        r#"[{"segment_name": "PID", "1": "1", "2": "", "3": null, "5.1": "DOE", "5.2": null}]"#;
+    let input: JsonInput = serde_json::from_str(json).unwrap();
+    assert!(matches!(input, JsonInput::Typed(_)));
+
+    let json_hl7 = JsonHl7::new(input.into_flat(), None);
+    assert_eq!(json_hl7._convert_json_to_hl7(true), "PID|1||\"\"||DOE^\"\"");
+}
+
+#[test]
+fn test_json_hl7_strict_keys() {
+    use crate::errors::Hl7Error;
+    use serde_json::json;
+
+    let json_data = json!([
+        {"segment_name": "PID", "1": "1", "3": "111", "3.1": "222", "5": 5, "6.x": "X", "7": "DOE"}
+    ]);
+
+    let result = JsonHl7::from_value(json_data.clone(), None, Some(true), None);
+    assert!(matches!(
+        result,
+        Err(Hl7Error::ComponentError {
+            field: 3,
+            component: 1,
+            ..
+        })
+    ));
+
+    let json_hl7 = JsonHl7::from_value(json_data, None, None, None).unwrap();
+    assert_eq!(json_hl7.key_warnings.len(), 3);
+    assert!(json_hl7.key_warnings[1].contains("\"5\" must be a string"));
+    assert!(json_hl7.key_warnings[2].contains("\"6.x\""));
+    assert_eq!(json_hl7._convert_json_to_hl7(true), "PID|1||222||||DOE");
+
+    let nested = json!([{"segment_name": "PID", "fields": [[[["1"]]]]}]);
+    let json_hl7 = JsonHl7::from_value(nested, None, Some(true), None).unwrap();
+    assert!(json_hl7.key_warnings.is_empty());
+    assert_eq!(json_hl7._convert_json_to_hl7(true), "PID|1");
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn test_json_hl7_validate_schema() {
+    use serde_json::json;
+
+    let json_data = json!([
+        {"segment_name": "MSH", "1": "^~\\&", "8.1": "ADT"},
+        {"segment_name": "PID", "1": "1", "3[0].1": "111"}
+    ]);
+    let json_hl7 = JsonHl7::from_value(json_data, None, None, None).unwrap();
+    assert!(json_hl7.validate_schema().is_ok());
+
+    let json_hl7 = JsonHl7::from_value(
+        json!([{"segment_name": "PID", "3.x": "A"}]),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    assert!(json_hl7.validate_schema().is_err());
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn test_json_hl7_validate_schema_checks_raw_input() {
+    use crate::errors::Hl7Error;
+    use serde_json::json;
+
+    let non_string = json!([{"segment_name": "PID", "3": 5}]);
+    let json_hl7 = JsonHl7::from_value(non_string.clone(), None, None, None).unwrap();
+    assert!(json_hl7.validate_schema().is_ok());
+    assert!(matches!(
+        JsonHl7::from_value(non_string, None, None, Some(true)),
+        Err(Hl7Error::ValidationFailed { .. })
+    ));
+
+    let missing_name = json!([{"3": "111"}]);
+    assert!(JsonHl7::from_value(missing_name, None, None, Some(true)).is_err());
+}
@@ -0,0 +1,228 @@
+use crate::errors::Hl7Error;
+use crate::{
+    hl7_to_json, json_to_hl7, parse_message, parse_messages, split_messages, MshNumbering,
+    ParseOptions, SerializeOptions,
+};
+
+#[test]
+fn test_parse_message() {
+    let hl7_string = "MSH|^~\\&|ADT1|HOSPITAL|LAB|HOSPITAL|20240101120000||ADT^A01^ADT_A01|MSG00001|T|2.5.1\r\nPID|1||PATID1234||DOE^JOHN\r\n";
+    let message = parse_message(hl7_string, &ParseOptions::default()).unwrap();
+
+    assert_eq!(message.segments.len(), 2);
+    assert_eq!(message.segments[0].segment_name, "MSH");
+    assert_eq!(message.segments[1].segment_name, "PID");
+    assert_eq!(message.msh_numbering, MshNumbering::Legacy);
+}
+
+#[test]
+fn test_parse_message_empty() {
+    let result = parse_message("  \n", &ParseOptions::default());
+    assert!(matches!(result, Err(Hl7Error::EmptyMessage)));
+}
+
+#[test]
+fn test_parse_message_without_unescaping() {
+    let options = ParseOptions::new().with_unescaping(false);
+    let message = parse_message("MSH|^~\\&|A\nNTE|1||A\\T\\B", &options).unwrap();
+
+    assert_eq!(message.segments[1].fields.get(&3).unwrap().value, "A\\T\\B");
+}
+
+#[test]
+fn test_hl7_to_json_and_back() {
+    let hl7_string = "MSH|^~\\&|ADT1|HOSPITAL|LAB|HOSPITAL|20240101120000||ADT^A01^ADT_A01|MSG00001|T|2.5.1\nPID|1||PATID1234^^^HOSP&1.2.3&ISO||DOE^JOHN";
+    let options = ParseOptions::new().with_msh_numbering(MshNumbering::Standard);
+    let json = hl7_to_json(hl7_string, &options).unwrap();
+
+    assert_eq!(json[0].get("9.3").unwrap(), "ADT_A01");
+    assert_eq!(json[1].get("3.4.2").unwrap(), "1.2.3");
+
+    let serialize_options = SerializeOptions::new().with_msh_numbering(MshNumbering::Standard);
+    assert_eq!(json_to_hl7(&json, &serialize_options), hl7_string);
+}
+
+#[cfg(feature = "validation")]
+#[test]
+fn test_message_validate() {
+    use crate::Hl7Validator;
+
+    let hl7_string = "MSH|^~\\&|ADT1|HOSPITAL|LAB|HOSPITAL|20240101120000||ADT^A01^ADT_A01|MSG00001|T|2.5.1\nPID|1||PATID1234";
+    let options = ParseOptions::new().with_msh_numbering(MshNumbering::Standard);
+    let message = parse_message(hl7_string, &options).unwrap();
+
+    assert!(message.validate(&Hl7Validator::new()).is_ok());
+}
+
+#[test]
+fn test_split_messages() {
+    let input = "\r\nMSH|^~\\&|A|1\rPID|1\r\nMSH|^~\\&|A|2\nPID|2\n\nMSH|^~\\&|A|3\r";
+    let messages = split_messages(input);
+
+    assert_eq!(
+        messages,
+        vec![
+            "MSH|^~\\&|A|1\rPID|1",
+            "MSH|^~\\&|A|2\nPID|2",
+            "MSH|^~\\&|A|3"
+        ]
+    );
+}
+
+#[test]
+fn test_split_messages_keeps_leading_segments() {
+    let messages = split_messages("NTE|1|preamble\nMSH|^~\\&|A|1\nMSH|^~\\&|A|2");
+
+    assert_eq!(
+        messages,
+        vec!["NTE|1|preamble\nMSH|^~\\&|A|1", "MSH|^~\\&|A|2"]
+    );
+    assert!(split_messages(" \r\n").is_empty());
+}
+
+#[test]
+fn test_parse_messages() {
+    let input = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|T|2.5.1\rPID|1||111\rMSH|^~\\&|A|B|C|D|20240101||ADT^A08|2|T|2.5.1\rPID|1||222";
+    let messages = parse_messages(input, &ParseOptions::default()).unwrap();
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].segments[1].fields.get(&3).unwrap().value, "222");
+    assert!(matches!(
+        parse_messages("\n", &ParseOptions::default()),
+        Err(Hl7Error::EmptyMessage)
+    ));
+}
+
+#[cfg(feature = "validation")]
+#[test]
+fn test_validate_messages_reports_index() {
+    use crate::message::validate_messages;
+    use crate::Hl7Validator;
+
+    let input = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1\rPID|1||111\rMSH|^~\\&|A|B|C\rPID|1||222";
+    let options = ParseOptions::new().with_msh_numbering(MshNumbering::Standard);
+    let messages = parse_messages(input, &options).unwrap();
+    let error = validate_messages(&messages, &Hl7Validator::new()).unwrap_err();
+
+    assert!(error.to_string().starts_with("HL7 message 1: "));
+    assert!(matches!(error, Hl7Error::MessageError { index: 1, .. }));
+}
+
+#[test]
+fn test_nested_json_roundtrip() {
+    use crate::{hl7_to_nested_json, nested_json_to_hl7};
+
+    let hl7_string = "MSH|^~\\&|ADT1|HOSPITAL|LAB|HOSPITAL|20240101120000||ADT^A01^ADT_A01|MSG00001|T|2.5.1\nPID|1||111~222^^^HOSP&1.2.3&ISO||DOE^JOHN^^^^||19800101|M";
+    for numbering in [MshNumbering::Legacy, MshNumbering::Standard] {
+        let parse_options = ParseOptions::new().with_msh_numbering(numbering);
+        let serialize_options = SerializeOptions::new().with_msh_numbering(numbering);
+
+        let nested = hl7_to_nested_json(hl7_string, &parse_options).unwrap();
+        let json = serde_json::to_string(&nested).unwrap();
+        let nested = serde_json::from_str::<Vec<crate::Hl7NestedSegment>>(&json).unwrap();
+
+        assert_eq!(nested_json_to_hl7(&nested, &serialize_options), hl7_string);
+    }
+}
+
+#[test]
+fn test_typed_json_roundtrip() {
+    use crate::{hl7_to_typed_json, typed_json_to_hl7};
+
+    let hl7_string = "MSH|^~\\&|ADT1|HOSPITAL|LAB|HOSPITAL|20240101120000||ADT^A08^ADT_A01|MSG00001|T|2.5.1\nPID|1||111||DOE^\"\"||\"\"|";
+    let typed = hl7_to_typed_json(hl7_string, &ParseOptions::default()).unwrap();
+
+    let json = serde_json::to_value(&typed[1]).unwrap();
+    assert_eq!(json["7"], serde_json::Value::Null);
+    assert_eq!(json["6"], "");
+    assert_eq!(json["5.2"], serde_json::Value::Null);
+    assert!(json.get("9").is_none());
+
+    let typed: Vec<crate::TypedSegmentJson> =
+        serde_json::from_str(&serde_json::to_string(&typed).unwrap()).unwrap();
+    assert_eq!(
+        typed_json_to_hl7(&typed, &SerializeOptions::default()),
+        hl7_string
+    );
+}
+
+#[test]
+fn test_check_json_keys() {
+    use crate::{check_json, try_json_to_hl7};
+    use std::collections::BTreeMap;
+
+    let segment = |entries: &[(&str, &str)]| -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    };
+
+    let valid = vec![segment(&[
+        ("segment_name", "PID"),
+        ("1", "1"),
+        ("3[0].1", "111"),
+        ("3[1].1", "222"),
+        ("5.1", "DOE"),
+        ("5.2.1", "JOHN"),
+    ])];
+    assert!(check_json(&valid).is_empty());
+    assert!(try_json_to_hl7(&valid, &SerializeOptions::default()).is_ok());
+
+    let findings = check_json(&[
+        segment(&[("segment_name", "PID"), ("3.x", "A")]),
+        segment(&[("segment_name", "OBX"), ("3[a]", "A"), ("0", "B")]),
+        segment(&[("segment_name", "NTE"), ("3.1.2.3", "A")]),
+        segment(&[("1", "A")]),
+    ]);
+    assert_eq!(findings.len(), 5);
+    assert!(matches!(
+        &findings[0],
+        Hl7Error::ComponentError { segment, field: 3, component: 0, .. } if segment == "0 (PID)"
+    ));
+    assert!(matches!(
+        &findings[1],
+        Hl7Error::FieldError { segment, field: 0, .. } if segment == "1 (OBX)"
+    ));
+    assert!(matches!(
+        &findings[2],
+        Hl7Error::FieldError { segment, field: 3, message } if segment == "1 (OBX)" && message.contains("3[a]")
+    ));
+    assert!(matches!(
+        &findings[3],
+        Hl7Error::ComponentError {
+            field: 3,
+            component: 1,
+            ..
+        }
+    ));
+    assert!(matches!(
+        &findings[4],
+        Hl7Error::FieldError { segment, message, .. } if segment == "3" && message.contains("segment_name")
+    ));
+
+    let conflicting = vec![segment(&[
+        ("segment_name", "PID"),
+        ("3", "A"),
+        ("3.1", "B"),
+        ("5.1", "C"),
+        ("5.1.2", "D"),
+        ("7[1]", "E"),
+        ("7.1", "F"),
+    ])];
+    let findings = check_json(&conflicting);
+    assert_eq!(findings.len(), 2);
+    assert!(matches!(
+        &findings[0],
+        Hl7Error::ComponentError { field: 3, component: 1, message, .. } if message.contains("\"3\"")
+    ));
+    assert!(matches!(
+        &findings[1],
+        Hl7Error::ComponentError {
+            field: 5,
+            component: 1,
+            ..
+        }
+    ));
+    assert!(try_json_to_hl7(&conflicting, &SerializeOptions::default()).is_err());
+}
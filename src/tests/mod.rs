@@ -8,16 +8,67 @@ mod segments_tests;
 mod core_tests;
 
 #[cfg(test)]
-mod json_hl7_tests;
+mod message_tests;
 
 #[cfg(test)]
-mod integration_tests;
+mod borrowed_tests;
+
+#[cfg(test)]
+mod batch_tests;
+
+#[cfg(test)]
+mod roundtrip_tests;
+
+#[cfg(test)]
+mod google_tests;
 
 #[cfg(test)]
+mod structure_tests;
+
+#[cfg(all(test, feature = "python"))]
+mod json_hl7_tests;
+
+#[cfg(all(test, feature = "python"))]
+mod integration_tests;
+
+#[cfg(all(test, feature = "validation"))]
 mod validation_tests;
 
 #[cfg(test)]
 mod errors_tests;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "python", feature = "validation"))]
 mod pyo3_api_tests;
+
+#[cfg(all(test, feature = "streaming"))]
+mod streaming_tests;
+
+#[cfg(all(test, feature = "schema"))]
+mod schema_tests;
+
+#[cfg(all(test, feature = "xml"))]
+mod xml_tests;
+
+#[cfg(all(test, feature = "arrow"))]
+mod columnar_tests;
+
+#[cfg(test)]
+mod terser_tests;
+
+#[cfg(test)]
+mod mutable_tests;
+
+#[cfg(test)]
+mod builder_tests;
+
+#[cfg(test)]
+mod diff_tests;
+
+#[cfg(test)]
+mod query_tests;
+
+#[cfg(all(test, feature = "transform"))]
+mod transform_tests;
+
+#[cfg(all(test, feature = "deidentify"))]
+mod deidentify_tests;
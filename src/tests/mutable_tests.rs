@@ -0,0 +1,131 @@
+use crate::errors::Hl7Error;
+use crate::{Hl7MutableMessage, MshNumbering};
+
+const ADT: &str = "MSH|^~\\&|REG|FAC|EHR|FAC|20240101||ADT^A01|MSG1|P|2.5\nEVN|A01|20240101\nPID|1||111^^^HOSP~222^^^SSA||DOE^JOHN||19700101|M\nNTE|1||Fish \\T\\ Chips";
+
+#[test]
+fn test_untouched_message_round_trips() {
+    let message = Hl7MutableMessage::parse(ADT, MshNumbering::Legacy).unwrap();
+    assert_eq!(message.to_er7(), ADT);
+    assert_eq!(message.segment_names(), vec!["MSH", "EVN", "PID", "NTE"]);
+
+    let standard = Hl7MutableMessage::parse(ADT, MshNumbering::Standard).unwrap();
+    assert_eq!(standard.to_er7(), ADT);
+}
+
+#[test]
+fn test_insert_remove_move_segments() {
+    let mut message = Hl7MutableMessage::parse(ADT, MshNumbering::Legacy).unwrap();
+
+    message.insert_segment(3, "PV1|1|I|WARD^101").unwrap();
+    message.append_segment("OBX|1|ST|NOTE||done").unwrap();
+    assert_eq!(
+        message.segment_names(),
+        vec!["MSH", "EVN", "PID", "PV1", "NTE", "OBX"]
+    );
+
+    let removed = message.remove_segment(1).unwrap();
+    assert_eq!(removed.segment_name, "EVN");
+
+    message.move_segment(4, 1).unwrap();
+    assert_eq!(
+        message.segment_names(),
+        vec!["MSH", "OBX", "PID", "PV1", "NTE"]
+    );
+    assert!(message
+        .to_er7()
+        .ends_with("\nPV1|1|I|WARD^101\nNTE|1||Fish \\T\\ Chips"));
+}
+
+#[test]
+fn test_segment_errors() {
+    let mut message = Hl7MutableMessage::parse(ADT, MshNumbering::Legacy).unwrap();
+
+    assert!(matches!(
+        message.insert_segment(9, "PV1|1"),
+        Err(Hl7Error::InvalidSegment(_))
+    ));
+    assert!(matches!(
+        message.insert_segment(1, "pv1|1"),
+        Err(Hl7Error::InvalidSegmentName { .. })
+    ));
+    assert_eq!(
+        message.remove_segment(4).unwrap_err().to_string(),
+        "HL7 invalid segment: segment index 4 is out of range for a message with 4 segments"
+    );
+    assert!(message.move_segment(0, 4).is_err());
+    assert_eq!(message.len(), 4);
+}
+
+#[test]
+fn test_set_literal_escapes_values() {
+    let mut message = Hl7MutableMessage::parse(ADT, MshNumbering::Legacy).unwrap();
+
+    message.set_field_literal(2, 8, "F").unwrap();
+    message
+        .set_repetition_literal(2, 3, 1, "333^^^SSA")
+        .unwrap();
+    message.set_component_literal(2, 5, 0, 2, "JANE").unwrap();
+    message
+        .set_subcomponent_literal(2, 3, 2, 4, 2, "1.2.3")
+        .unwrap();
+    message.set_field_literal(3, 3, "A|B & C").unwrap();
+    message.set_field_literal(3, 6, "tail").unwrap();
+
+    assert_eq!(
+        message.to_er7(),
+        "MSH|^~\\&|REG|FAC|EHR|FAC|20240101||ADT^A01|MSG1|P|2.5\nEVN|A01|20240101\nPID|1||111^^^HOSP~333\\S\\\\S\\\\S\\SSA~^^^&1.2.3||DOE^JANE||19700101|F\nNTE|1||A\\F\\B \\T\\ C|||tail"
+    );
+    assert_eq!(message.get("NTE-3").unwrap().as_deref(), Some("A|B & C"));
+}
+
+#[test]
+fn test_set_literal_differs_from_set_by_path() {
+    let mut literal = Hl7MutableMessage::parse(ADT, MshNumbering::Legacy).unwrap();
+    literal.set_field_literal(2, 5, "ROE^JANE").unwrap();
+    assert_eq!(literal.get("PID-5").unwrap().as_deref(), Some("ROE^JANE"));
+    assert_eq!(literal.get("PID-5-2").unwrap(), None);
+
+    let mut split = Hl7MutableMessage::parse(ADT, MshNumbering::Legacy).unwrap();
+    split.set("PID-5", "ROE^JANE").unwrap();
+    assert_eq!(split.get("PID-5-2").unwrap().as_deref(), Some("JANE"));
+}
+
+#[test]
+fn test_set_literal_errors() {
+    let mut message = Hl7MutableMessage::parse(ADT, MshNumbering::Legacy).unwrap();
+
+    assert!(matches!(
+        message.set_field_literal(0, 1, "x"),
+        Err(Hl7Error::InvalidPath { .. })
+    ));
+    assert!(matches!(
+        message.set_field_literal(2, 0, "x"),
+        Err(Hl7Error::FieldError { .. })
+    ));
+    assert!(matches!(
+        message.set_field_literal(7, 1, "x"),
+        Err(Hl7Error::InvalidSegment(_))
+    ));
+}
+
+#[test]
+fn test_set_by_path_and_reparse() {
+    let mut message = Hl7MutableMessage::parse(ADT, MshNumbering::Standard).unwrap();
+
+    message.set("MSH-10", "MSG2").unwrap();
+    message.set("PID-5", "ROE^JANE").unwrap();
+    message.set("NTE-3-1-1", "Salt & Pepper").unwrap();
+
+    assert_eq!(message.get("PID-5-1").unwrap().as_deref(), Some("ROE"));
+    assert_eq!(
+        message.get("NTE-3").unwrap().as_deref(),
+        Some("Salt & Pepper")
+    );
+
+    let json = message.to_json().unwrap();
+    assert_eq!(json[0]["10"], "MSG2");
+    assert_eq!(json[2]["5.2"], "JANE");
+    assert_eq!(json[3]["3"], "Salt & Pepper");
+    assert!(message.to_er7().contains("\nNTE|1||Salt \\T\\ Pepper"));
+}
@@ -152,3 +152,920 @@ fn pyo3_api_tests() {
         assert!(hl7_str_json.starts_with("MSH|"));
     });
 }
+
+#[cfg(feature = "streaming")]
+#[test]
+fn pyo3_iter_file() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+
+        let temp_archive = std::env::temp_dir().join("hl7conv2_iter_file_test.hl7");
+        let content = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1\rPID|1||111\rMSH|^~\\&|A|B|C|D|20240101||ADT^A08^ADT_A01|2|T|2.5.1\rPID|1||222\r";
+        std::fs::write(&temp_archive, content).unwrap();
+
+        let iterator = hl7_json_class
+            .call_method1(
+                py,
+                "iter_file",
+                (
+                    temp_archive.to_string_lossy().to_string(),
+                    false,
+                    false,
+                    false,
+                ),
+            )
+            .unwrap();
+        let iterator = iterator.bind(py).try_iter().unwrap();
+
+        let mut control_ids = Vec::new();
+        for message in iterator {
+            let message = message.unwrap();
+            assert!(!message
+                .getattr("escaping_enabled")
+                .unwrap()
+                .extract::<bool>()
+                .unwrap());
+            let json = message.getattr("hl7_json").unwrap();
+            let msh = json.get_item(0).unwrap();
+            control_ids.push(msh.get_item("9").unwrap().extract::<String>().unwrap());
+        }
+        assert_eq!(control_ids, vec!["1", "2"]);
+
+        let missing = hl7_json_class.call_method1(py, "iter_file", ("/nonexistent/archive.hl7",));
+        assert!(missing.is_err());
+    });
+}
+
+#[test]
+fn pyo3_batch_json() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+        let json_hl7_class = module.getattr(py, "JsonHl7").unwrap();
+
+        let batch = "FHS|^~\\&|A\nBHS|^~\\&|A\nMSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|T|2.5.1\nPID|1||111\nBTS|1\nFTS|1";
+        let hl7 = hl7_json_class.call1(py, (batch,)).unwrap();
+        let batch_json = hl7.getattr(py, "hl7_batch_json").unwrap();
+        let batch_json = batch_json.bind(py);
+
+        let batches = batch_json.get_item("batches").unwrap();
+        let messages = batches.get_item(0).unwrap().get_item("messages").unwrap();
+        let pid = messages.get_item(0).unwrap().get_item(1).unwrap();
+        let patient_id: String = pid.get_item("3").unwrap().extract().unwrap();
+        assert_eq!(patient_id, "111");
+
+        let hl7_string: String = json_hl7_class
+            .call_method1(py, "batch_json_to_hl7", (batch_json,))
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(hl7_string, batch);
+
+        let mismatched = hl7_json_class
+            .call1(py, (batch.replace("FTS|1", "FTS|5"),))
+            .unwrap();
+        assert!(mismatched.getattr(py, "hl7_batch_json").is_err());
+    });
+}
+
+#[test]
+fn pyo3_split_messages() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+
+        let text = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1|\rPID|1||111\rMSH|^~\\&|A|B|C\rPID|1||222";
+        let messages = hl7_json_class
+            .call_method1(py, "split_messages", (text,))
+            .unwrap();
+        let messages = messages.bind(py);
+        assert_eq!(messages.len().unwrap(), 2);
+        let second: String = messages
+            .get_item(1)
+            .unwrap()
+            .getattr("hl7_string")
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(second, "MSH|^~\\&|A|B|C\nPID|1||222");
+
+        let combined = hl7_json_class.call1(py, (text, true)).unwrap();
+        let error = combined.getattr(py, "hl7_messages_json").unwrap_err();
+        assert!(error.to_string().contains("HL7 message 1: "));
+
+        combined.setattr(py, "validation_enabled", false).unwrap();
+        let json = combined.getattr(py, "hl7_messages_json").unwrap();
+        assert_eq!(json.bind(py).len().unwrap(), 2);
+    });
+}
+
+#[test]
+fn pyo3_nested_json() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+        let json_hl7_class = module.getattr(py, "JsonHl7").unwrap();
+
+        let hl7_string =
+            "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1\nPID|1||111~222^^^HOSP&ISO";
+        let hl7 = hl7_json_class.call1(py, (hl7_string,)).unwrap();
+        let nested = hl7.getattr(py, "hl7_nested_json").unwrap();
+        let nested = nested.bind(py);
+
+        let pid = nested.get_item(1).unwrap();
+        let name: String = pid.get_item("segment_name").unwrap().extract().unwrap();
+        assert_eq!(name, "PID");
+        let authority: Vec<String> = pid
+            .get_item("fields")
+            .unwrap()
+            .get_item(2)
+            .unwrap()
+            .get_item(1)
+            .unwrap()
+            .get_item(3)
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(authority, vec!["HOSP", "ISO"]);
+
+        let json_hl7 = json_hl7_class.call1(py, (nested,)).unwrap();
+        let converted: String = json_hl7
+            .getattr(py, "hl7_string")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(converted, hl7_string);
+    });
+}
+
+#[test]
+fn pyo3_typed_json() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+        let json_hl7_class = module.getattr(py, "JsonHl7").unwrap();
+
+        let hl7_string = "MSH|^~\\&|A\nPID|1||\"\"||DOE";
+        let hl7 = hl7_json_class.call1(py, (hl7_string,)).unwrap();
+        let typed = hl7.getattr(py, "hl7_typed_json").unwrap();
+        let typed = typed.bind(py);
+
+        let pid = typed.get_item(1).unwrap();
+        assert!(pid.get_item("3").unwrap().is_none());
+        let empty: String = pid.get_item("2").unwrap().extract().unwrap();
+        assert_eq!(empty, "");
+        assert!(pid.get_item("6").is_err());
+
+        let json_hl7 = json_hl7_class.call1(py, (typed,)).unwrap();
+        let converted: String = json_hl7
+            .getattr(py, "hl7_string")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(converted, hl7_string);
+    });
+}
+
+#[test]
+fn pyo3_json_key_order_and_fidelity() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+        let json_hl7_class = module.getattr(py, "JsonHl7").unwrap();
+
+        let hl7_string = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1\nNTE|1|L|\\H\\bold\\N\\ x\\X20\\y~a^^|||||||A||";
+        let hl7 = hl7_json_class
+            .call1(py, (hl7_string, false, false, false))
+            .unwrap();
+        let json = hl7.getattr(py, "hl7_json").unwrap();
+
+        let keys: Vec<String> = json
+            .bind(py)
+            .get_item(1)
+            .unwrap()
+            .call_method0("keys")
+            .unwrap()
+            .try_iter()
+            .unwrap()
+            .map(|key| key.unwrap().extract().unwrap())
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "segment_name",
+                "1",
+                "2",
+                "3[0]",
+                "3[1].1",
+                "3[1].2",
+                "3[1].3",
+                "4",
+                "5",
+                "6",
+                "7",
+                "8",
+                "9",
+                "10",
+                "11",
+                "12"
+            ]
+        );
+
+        let json_hl7 = json_hl7_class.call1(py, (json,)).unwrap();
+        let converted: String = json_hl7
+            .getattr(py, "hl7_string_unescaped")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(converted, hl7_string);
+    });
+}
+
+#[test]
+fn pyo3_google_parsed_data() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+        let json_hl7_class = module.getattr(py, "JsonHl7").unwrap();
+
+        let hl7_string =
+            "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1\nPID|1||111~222||DOE^JOHN";
+        let hl7 = hl7_json_class.call1(py, (hl7_string,)).unwrap();
+        let parsed_data = hl7.getattr(py, "google_parsed_data").unwrap();
+        let parsed_data = parsed_data.bind(py);
+
+        let pid = parsed_data
+            .get_item("segments")
+            .unwrap()
+            .get_item(1)
+            .unwrap();
+        let segment_id: String = pid.get_item("segmentId").unwrap().extract().unwrap();
+        assert_eq!(segment_id, "PID");
+        let set_id: String = pid.get_item("setId").unwrap().extract().unwrap();
+        assert_eq!(set_id, "1");
+        let patient_id: String = pid
+            .get_item("fields")
+            .unwrap()
+            .get_item("3[1]")
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(patient_id, "222");
+
+        let json_hl7 = json_hl7_class
+            .call_method1(py, "from_google_parsed_data", (parsed_data,))
+            .unwrap();
+        let converted: String = json_hl7
+            .getattr(py, "hl7_string")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(converted, hl7_string);
+    });
+}
+
+#[test]
+fn pyo3_grouped_json() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+
+        let hl7_string = "MSH|^~\\&|A|B|C|D|20240101||ORU^R01^ORU_R01|1|P|2.5.1\nPID|1||111\nOBR|1\nOBX|1|NM|GLU||5.6\nZXX|1";
+        let hl7 = hl7_json_class.call1(py, (hl7_string,)).unwrap();
+        let grouped = hl7.getattr(py, "hl7_grouped_json").unwrap();
+        let grouped = grouped.bind(py);
+
+        let structure: String = grouped.get_item("structure").unwrap().extract().unwrap();
+        assert_eq!(structure, "ORU_R01");
+        let patient_result = grouped.get_item("children").unwrap().get_item(1).unwrap();
+        let group_name: String = patient_result
+            .get_item("group_name")
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(group_name, "PATIENT_RESULT");
+        let diagnostic = grouped
+            .get_item("diagnostics")
+            .unwrap()
+            .get_item(0)
+            .unwrap();
+        let segment_name: String = diagnostic
+            .get_item("segment_name")
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!(segment_name, "ZXX");
+    });
+}
+
+#[test]
+fn pyo3_xml() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+        let json_hl7_class = module.getattr(py, "JsonHl7").unwrap();
+
+        let hl7_string = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|P|2.5.1\nEVN|A01\nPID|1||111||DOE^JOHN\nPV1|1|I";
+        let hl7 = hl7_json_class.call1(py, (hl7_string,)).unwrap();
+        let xml: String = hl7.getattr(py, "hl7_xml").unwrap().extract(py).unwrap();
+        assert!(xml.contains("<PID.5><XPN.1><FN.1>DOE</FN.1></XPN.1><XPN.2>JOHN</XPN.2></PID.5>"));
+
+        let from_xml = hl7_json_class
+            .call_method1(py, "from_xml", (xml.as_str(),))
+            .unwrap();
+        let converted: String = from_xml
+            .getattr(py, "hl7_string")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(converted, hl7_string);
+
+        let json = hl7.getattr(py, "hl7_json").unwrap();
+        let json_hl7 = json_hl7_class.call1(py, (json,)).unwrap();
+        let json_xml: String = json_hl7
+            .getattr(py, "hl7_xml")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(json_xml, xml);
+    });
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn pyo3_arrow_tables() {
+    use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+    use pyo3::types::{PyCapsule, PyDict};
+
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+
+        let hl7_string = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|MSG1|P|2.5\nPID|1||111||DOE^JOHN\nMSH|^~\\&|A|B|C|D|20240101||ADT^A01|MSG2|P|2.5\nPID|1||222||ROE^JANE";
+        let hl7 = hl7_json_class.call1(py, (hl7_string,)).unwrap();
+        let tables = hl7.getattr(py, "arrow_tables").unwrap();
+        let tables = tables.bind(py).cast::<PyDict>().unwrap();
+        let pid = tables.get_item("PID").unwrap().unwrap();
+
+        let num_rows: usize = pid.getattr("num_rows").unwrap().extract().unwrap();
+        assert_eq!(num_rows, 2);
+        let column_names: Vec<String> = pid.getattr("column_names").unwrap().extract().unwrap();
+        assert_eq!(
+            column_names,
+            vec![
+                "message_control_id",
+                "segment_sequence",
+                "1",
+                "2",
+                "3",
+                "4",
+                "5.1",
+                "5.2"
+            ]
+        );
+
+        let capsule = pid.call_method0("__arrow_c_stream__").unwrap();
+        let capsule = capsule.cast::<PyCapsule>().unwrap();
+        let stream = capsule
+            .pointer_checked(Some(c"arrow_array_stream"))
+            .unwrap()
+            .cast::<FFI_ArrowArrayStream>();
+        let reader = unsafe { ArrowArrayStreamReader::from_raw(stream.as_ptr()) }.unwrap();
+        let batches: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 2);
+
+        let directory = tempfile::tempdir().unwrap();
+        hl7.call_method1(py, "write_parquet", (directory.path().to_str().unwrap(),))
+            .unwrap();
+        assert!(directory.path().join("MSH.parquet").exists());
+        assert!(directory.path().join("PID.parquet").exists());
+    });
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn pyo3_hl7_to_ndjson() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_to_ndjson = module.getattr(py, "hl7_to_ndjson").unwrap();
+
+        let content = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|T|2.5.1||AL\rPID|1||111\rMSH|^~\\&|A|B\rPID|1||222\r";
+        let directory = tempfile::tempdir().unwrap();
+        let input_path = directory.path().join("archive.hl7");
+        let output_path = directory.path().join("archive.ndjson");
+        std::fs::write(&input_path, content).unwrap();
+
+        let summary = hl7_to_ndjson
+            .call1(
+                py,
+                (
+                    input_path.to_str().unwrap(),
+                    output_path.to_str().unwrap(),
+                    true,
+                ),
+            )
+            .unwrap();
+        let summary = summary.bind(py);
+        assert_eq!(
+            summary
+                .get_item("messages")
+                .unwrap()
+                .extract::<usize>()
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            summary
+                .get_item("errors")
+                .unwrap()
+                .extract::<usize>()
+                .unwrap(),
+            1
+        );
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].contains(r#"{"segment_name":"PID","1":"1","2":"","3":"111"}"#));
+        assert!(lines[1].starts_with(r#"{"message_index":1,"error":"#));
+
+        let stream = py
+            .import("io")
+            .unwrap()
+            .getattr("StringIO")
+            .unwrap()
+            .call1((content,))
+            .unwrap();
+        hl7_to_ndjson
+            .call1(py, (stream, output_path.to_str().unwrap()))
+            .unwrap();
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        assert_eq!(output.lines().count(), 2);
+        assert!(output.contains(r#""3":"222""#));
+    });
+}
+
+#[test]
+fn pyo3_terser_paths() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+
+        let hl7_string = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\nPID|1||111~222||DOE^JOHN\nNTE|1||Fish \\T\\ Chips";
+        let hl7 = hl7_json_class.call1(py, (hl7_string,)).unwrap();
+
+        let family: Option<String> = hl7
+            .call_method1(py, "get", ("PID-5-1",))
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(family.as_deref(), Some("DOE"));
+        let note: String = hl7
+            .call_method1(py, "get", ("NTE-3",))
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(note, "Fish & Chips");
+        let ids: Vec<String> = hl7
+            .call_method1(py, "get_all", ("PID-3",))
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(ids, vec!["111", "222"]);
+
+        hl7.call_method1(py, "set", ("PID-5-2", "JANE")).unwrap();
+        hl7.call_method1(py, "set", ("NTE-3", "Salt^Vinegar"))
+            .unwrap();
+        hl7.call_method1(py, "set", ("NTE-3-1-1", "Salt & Pepper"))
+            .unwrap();
+        let updated: String = hl7.getattr(py, "hl7_string").unwrap().extract(py).unwrap();
+        assert_eq!(
+            updated,
+            "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\nPID|1||111~222||DOE^JANE\nNTE|1||Salt \\T\\ Pepper^Vinegar"
+        );
+
+        let custom = hl7_json_class
+            .call1(py, ("MSH#^~\\&#A#B\rPID#1##111##DOE^JOHN",))
+            .unwrap();
+        custom.call_method1(py, "set", ("PID-8", "F")).unwrap();
+        let updated: String = custom
+            .getattr(py, "hl7_string")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(updated, "MSH#^~\\&#A#B\nPID#1##111##DOE^JOHN###F");
+
+        let error = hl7.call_method1(py, "get", ("PID-x",)).unwrap_err();
+        assert!(error.to_string().contains("Invalid HL7 path PID-x"));
+    });
+}
+
+#[test]
+fn pyo3_mutable_message() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let mutable_class = module.getattr(py, "Hl7MutableMessage").unwrap();
+
+        let hl7_string = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\nPID|1||111||DOE^JOHN";
+        let message = mutable_class.call1(py, (hl7_string,)).unwrap();
+        assert_eq!(message.bind(py).len().unwrap(), 2);
+
+        message
+            .call_method1(py, "append_segment", ("NTE|1||note",))
+            .unwrap();
+        message
+            .call_method1(py, "insert_segment", (1, "EVN|A01"))
+            .unwrap();
+        message
+            .call_method1(py, "set_field_literal", (3, 3, "Fish & Chips"))
+            .unwrap();
+        message
+            .call_method1(py, "set_component_literal", (2, 5, 0, 2, "JANE"))
+            .unwrap();
+        message.call_method1(py, "set", ("PID-3", "222")).unwrap();
+        let removed: String = message
+            .call_method1(py, "remove_segment", (1,))
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(removed, "EVN|A01");
+        message.call_method1(py, "move_segment", (2, 1)).unwrap();
+
+        let names: Vec<String> = message
+            .getattr(py, "segment_names")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(names, vec!["MSH", "NTE", "PID"]);
+        let updated: String = message
+            .getattr(py, "hl7_string")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(
+            updated,
+            "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\nNTE|1||Fish \\T\\ Chips\nPID|1||222||DOE^JANE"
+        );
+        let note: Option<String> = message
+            .call_method1(py, "get", ("NTE-3",))
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(note.as_deref(), Some("Fish & Chips"));
+
+        let json = message.getattr(py, "hl7_json").unwrap();
+        let pid = json.bind(py).get_item(2).unwrap();
+        assert_eq!(
+            pid.get_item("5.2").unwrap().extract::<String>().unwrap(),
+            "JANE"
+        );
+
+        let error = message
+            .call_method1(py, "remove_segment", (5,))
+            .unwrap_err();
+        assert!(error.to_string().contains("out of range"));
+    });
+}
+
+#[test]
+fn pyo3_message_builder() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let builder_class = module.getattr(py, "MessageBuilder").unwrap();
+        let pid_class = module.getattr(py, "PidBuilder").unwrap();
+        let obx_class = module.getattr(py, "ObxBuilder").unwrap();
+        let segment_class = module.getattr(py, "SegmentBuilder").unwrap();
+
+        let pid = pid_class.call0(py).unwrap();
+        pid.call_method1(py, "with_patient_name", ("DOE", "JOHN"))
+            .unwrap()
+            .call_method1(py, "with_administrative_sex", ("F",))
+            .unwrap();
+        let obx = obx_class
+            .call0(py)
+            .unwrap()
+            .call_method1(py, "with_value", ("Fish & Chips",))
+            .unwrap();
+        let custom = segment_class
+            .call1(py, ("ZPI",))
+            .unwrap()
+            .call_method1(py, "with_components", (2, vec!["A", "B"]))
+            .unwrap();
+
+        let builder = builder_class
+            .call1(py, ("ORU", "R01", "2.5.1"))
+            .unwrap()
+            .call_method1(py, "with_control_id", ("MSG1",))
+            .unwrap()
+            .call_method1(py, "with_timestamp", ("20240101120000",))
+            .unwrap();
+        builder
+            .call_method1(py, "with_segment", (pid,))
+            .unwrap()
+            .call_method1(py, "with_segment", (obx,))
+            .unwrap()
+            .call_method1(py, "with_segment", (custom,))
+            .unwrap();
+
+        let hl7: String = builder
+            .call_method0(py, "build")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(
+            hl7,
+            "MSH|^~\\&|||||20240101120000||ORU^R01^ORU_R01|MSG1|P|2.5.1\nPID|1||||DOE^JOHN|||F\nOBX|1||||Fish \\T\\ Chips\nZPI||A^B"
+        );
+        let control_id: String = builder
+            .getattr(py, "control_id")
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(control_id, "MSG1");
+
+        let error = builder
+            .call_method1(py, "with_segment", ("PID|1",))
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("segment must be a SegmentBuilder"));
+    });
+}
+
+#[test]
+fn pyo3_message_diff() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+
+        let old = hl7_json_class
+            .call1(
+                py,
+                ("MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\rPID|1||111||DOE^JOHN\rNTE|1||Fish \\T\\ Chips",),
+            )
+            .unwrap();
+        let new = hl7_json_class
+            .call1(
+                py,
+                ("MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\rPID|1||111||DOE^JANE\rNTE|1||Salt \\T\\ Pepper",),
+            )
+            .unwrap();
+
+        let changes = old.call_method1(py, "diff", (new.clone_ref(py),)).unwrap();
+        let changes = changes.bind(py);
+        assert_eq!(changes.len().unwrap(), 2);
+        let first = changes.get_item(0).unwrap();
+        assert_eq!(
+            first.get_item("kind").unwrap().extract::<String>().unwrap(),
+            "changed"
+        );
+        assert_eq!(
+            first.get_item("path").unwrap().extract::<String>().unwrap(),
+            "PID-5-2"
+        );
+        assert_eq!(
+            first
+                .get_item("old_value")
+                .unwrap()
+                .extract::<String>()
+                .unwrap(),
+            "JOHN"
+        );
+        let second = changes.get_item(1).unwrap();
+        assert_eq!(
+            second
+                .get_item("new_value")
+                .unwrap()
+                .extract::<String>()
+                .unwrap(),
+            "Salt & Pepper"
+        );
+
+        let text: String = old
+            .call_method1(
+                py,
+                "diff_text",
+                ("MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\nPID|1||111||DOE^JOHN",),
+            )
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(
+            text,
+            "--- old\n+++ new\n@@ NTE @@\n-NTE: NTE|1||Fish \\T\\ Chips"
+        );
+    });
+}
+
+#[test]
+fn pyo3_query() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let hl7_json_class = module.getattr(py, "Hl7Json").unwrap();
+
+        let hl7 = hl7_json_class
+            .call1(
+                py,
+                ("MSH|^~\\&|A|B|C|D|20240101||ORU^R01|1|P|2.5\rOBX|1|NM|GLU||5.4|||N\rOBX|2|NM|GLU||11.2|||H\rOBX|3|NM|HGB||13.5|||N",),
+            )
+            .unwrap();
+
+        let conditions = vec![("3.1", "==", "GLU"), ("8", "!=", "N")];
+        let segments = hl7
+            .call_method1(py, "query", ("OBX", conditions.clone()))
+            .unwrap();
+        let segments = segments.bind(py);
+        assert_eq!(segments.len().unwrap(), 1);
+        let segment = segments.get_item(0).unwrap();
+        assert_eq!(
+            segment.get_item("5").unwrap().extract::<String>().unwrap(),
+            "11.2"
+        );
+
+        let kwargs = pyo3::types::PyDict::new(py);
+        kwargs.set_item("select", vec!["1", "5", "7", "9"]).unwrap();
+        let rows = hl7
+            .bind(py)
+            .call_method(
+                "query",
+                (
+                    "OBX",
+                    [("3.1", "GLU")]
+                        .into_iter()
+                        .collect::<std::collections::HashMap<_, _>>(),
+                ),
+                Some(&kwargs),
+            )
+            .unwrap();
+        assert_eq!(rows.len().unwrap(), 2);
+        let row = rows.get_item(1).unwrap();
+        let keys: Vec<String> = row
+            .call_method0("keys")
+            .unwrap()
+            .try_iter()
+            .unwrap()
+            .map(|k| k.unwrap().extract().unwrap())
+            .collect();
+        assert_eq!(keys, vec!["1", "5", "7", "9"]);
+        assert_eq!(
+            row.get_item("5").unwrap().extract::<String>().unwrap(),
+            "11.2"
+        );
+        assert_eq!(row.get_item("7").unwrap().extract::<String>().unwrap(), "");
+        assert!(row.get_item("9").unwrap().is_none());
+
+        let error = hl7
+            .call_method1(py, "query", ("OBX", vec![("3.1", "<>", "GLU")]))
+            .unwrap_err();
+        assert!(error.to_string().contains("unknown query operator"));
+    });
+}
+
+#[cfg(feature = "transform")]
+#[test]
+fn pyo3_transform() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let transform_class = module.getattr(py, "Hl7Transform").unwrap();
+
+        let mapping = py
+            .import("json")
+            .unwrap()
+            .call_method1(
+                "loads",
+                (r#"{"lookups": {"sex": {"M": "male"}}, "rules": [
+                    {"action": "lookup", "path": "PID-8", "table": "sex"},
+                    {"action": "delete", "path": "PID-7", "when": {"path": "PID-8", "equals": "male"}}
+                ]}"#,),
+            )
+            .unwrap();
+        let transform = transform_class.call1(py, (mapping,)).unwrap();
+        assert_eq!(transform.bind(py).len().unwrap(), 2);
+
+        let output: String = transform
+            .call_method1(
+                py,
+                "apply",
+                ("MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\rPID|1||123||DOE^JOHN||19800101|M",),
+            )
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(
+            output,
+            "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\nPID|1||123||DOE^JOHN|||male"
+        );
+
+        let transform = transform_class
+            .call_method1(
+                py,
+                "from_toml",
+                ("[[rules]]\naction = \"remove_segment\"\nsegment = \"PID\"\n",),
+            )
+            .unwrap();
+        let output: String = transform
+            .call_method1(
+                py,
+                "apply",
+                ("MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\rPID|1",),
+            )
+            .unwrap()
+            .extract(py)
+            .unwrap();
+        assert_eq!(output, "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5");
+
+        let error = transform_class
+            .call1(
+                py,
+                (r#"{"rules": [{"action": "set", "path": "PID", "value": "X"}]}"#,),
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("transform rule 0"));
+    });
+}
+
+#[cfg(feature = "deidentify")]
+#[test]
+fn pyo3_deidentify() {
+    init_python();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(crate::hl7conv2)(py);
+        let deidentifier_class = module.getattr(py, "Deidentifier").unwrap();
+
+        let kwargs = pyo3::types::PyDict::new(py);
+        let strategies = pyo3::types::PyDict::new(py);
+        strategies.set_item("identifier", "redact").unwrap();
+        strategies.set_item("date", "keep").unwrap();
+        kwargs.set_item("strategies", strategies).unwrap();
+        kwargs
+            .set_item("rules", vec![("PID-8", "identifier")])
+            .unwrap();
+        kwargs.set_item("redaction_text", "X").unwrap();
+        let deidentifier = deidentifier_class
+            .bind(py)
+            .call(("secret",), Some(&kwargs))
+            .unwrap();
+
+        let result = deidentifier
+            .call_method1(
+                "deidentify",
+                ("MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\rPID|1||123^^^HOSP||DOE^JOHN||19800101|M",),
+            )
+            .unwrap();
+        let (hl7, report): (String, Bound<'_, PyAny>) = result.extract().unwrap();
+        assert_eq!(
+            hl7,
+            "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5\nPID|1||X^^^HOSP||NAME0001||19800101|X"
+        );
+        assert_eq!(report.len().unwrap(), 3);
+        let change = report.get_item(0).unwrap();
+        assert_eq!(
+            change
+                .get_item("path")
+                .unwrap()
+                .extract::<String>()
+                .unwrap(),
+            "PID-5"
+        );
+        assert_eq!(
+            change
+                .get_item("strategy")
+                .unwrap()
+                .extract::<String>()
+                .unwrap(),
+            "pseudonym"
+        );
+
+        let error = deidentifier_class
+            .call1(
+                py,
+                (
+                    "secret",
+                    [("name", "scramble")]
+                        .into_iter()
+                        .collect::<std::collections::HashMap<_, _>>(),
+                ),
+            )
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("unknown de-identification strategy"));
+    });
+}
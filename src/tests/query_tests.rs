@@ -0,0 +1,93 @@
+use crate::errors::Hl7Error;
+use crate::{Hl7Message, ParseOptions, QueryOperator, SegmentQuery};
+
+const ORU: &str = "MSH|^~\\&|LAB|FAC|EHR|FAC|20240101||ORU^R01|MSG1|P|2.5\nPID|1||111^^^HOSP~222^^^SSA||DOE^JOHN\nOBR|1|||PANEL\nOBX|1|NM|GLU^Glucose^LN||5.4|mmol/L||N\nOBX|2|NM|GLU^Glucose^LN||11.2|mmol/L||H\nOBX|3|NM|HGB^Hemoglobin^LN||13.5|g/dL||N\nOBX|4|ST|GLU^Glucose^LN||Fish \\T\\ Chips";
+
+fn message() -> Hl7Message {
+    Hl7Message::parse(ORU, &ParseOptions::default()).unwrap()
+}
+
+#[test]
+fn test_select_with_predicates() {
+    let message = message();
+    let query = SegmentQuery::new("OBX")
+        .with_equals("3.1", "GLU")
+        .with_not_equals("OBX-8", "N");
+
+    let segments = query.select(&message).unwrap();
+    let set_ids: Vec<&str> = segments
+        .iter()
+        .map(|segment| segment.fields[&1].value.as_str())
+        .collect();
+    assert_eq!(set_ids, vec!["2", "4"]);
+
+    let all = SegmentQuery::new("OBX").select(&message).unwrap();
+    assert_eq!(all.len(), 4);
+    assert!(SegmentQuery::new("NTE")
+        .select(&message)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_operators_and_repetitions() {
+    let message = message();
+
+    let query = SegmentQuery::new("PID").with_equals("3[1].1", "222");
+    assert_eq!(query.select(&message).unwrap().len(), 1);
+    let query = SegmentQuery::new("PID").with_equals("PID-3-1", "222");
+    assert_eq!(query.select(&message).unwrap().len(), 1);
+    let query = SegmentQuery::new("PID").with_equals("3(0)-1", "222");
+    assert!(query.select(&message).unwrap().is_empty());
+
+    let query = SegmentQuery::new("OBX").with_condition(
+        "3.2",
+        QueryOperator::parse("startswith").unwrap(),
+        "Hemo",
+    );
+    assert_eq!(query.select(&message).unwrap().len(), 1);
+    let query = SegmentQuery::new("OBX").with_condition("5", QueryOperator::Contains, "&");
+    assert_eq!(query.select(&message).unwrap().len(), 1);
+}
+
+#[test]
+fn test_select_json_and_project() {
+    let message = message();
+    let query = SegmentQuery::new("OBX").with_equals("3.1", "GLU");
+
+    let json = query.select_json(&message).unwrap();
+    assert_eq!(json.len(), 3);
+    assert_eq!(json[1]["5"], "11.2");
+    assert_eq!(json[1]["3.2"], "Glucose");
+
+    let rows = query.project(&message, &["1", "5", "6", "8"]).unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["5"].as_deref(), Some("5.4"));
+    assert_eq!(rows[2]["5"].as_deref(), Some("Fish & Chips"));
+    assert_eq!(rows[2]["8"], None);
+}
+
+#[test]
+fn test_query_errors() {
+    let message = message();
+
+    let error = SegmentQuery::new("OBX")
+        .with_equals("3.x", "GLU")
+        .select(&message)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Invalid HL7 path 3.x: component \"x\" is not a number"
+    );
+    assert!(matches!(
+        SegmentQuery::new("OBX")
+            .with_equals("PID-3", "1")
+            .select(&message),
+        Err(Hl7Error::InvalidPath { .. })
+    ));
+    assert!(matches!(
+        SegmentQuery::new("OBX").project(&message, &["OBX(1)-5"]),
+        Err(Hl7Error::InvalidPath { .. })
+    ));
+    assert!(QueryOperator::parse("<>").is_err());
+}
@@ -0,0 +1,174 @@
+use crate::{
+    hl7_to_json, hl7_to_nested_json, json_to_hl7, nested_json_to_hl7, Hl7NestedSegment,
+    MshNumbering, ParseOptions, SerializeOptions,
+};
+use proptest::prelude::*;
+
+fn raw_value() -> BoxedStrategy<String> {
+    prop_oneof![
+        "[A-Za-z0-9 .\\-]{0,6}",
+        Just(String::new()),
+        Just("\"\"".to_string()),
+        "[A-Z]{1,3}\\\\[FSTRE]\\\\[a-z]{0,3}",
+        Just("\\X0D\\\\X20\\\\H\\bold\\N\\".to_string()),
+    ]
+    .boxed()
+}
+
+fn canonical_value() -> BoxedStrategy<String> {
+    prop_oneof![
+        "[A-Za-z0-9 .\\-]{0,6}",
+        Just("\"\"".to_string()),
+        "[A-Z ]{0,3}\\\\(F|S|T|R|E|X0A|X0D)\\\\[a-z ]{0,3}",
+    ]
+    .boxed()
+}
+
+fn join(parts: Vec<String>, separator: &str) -> String {
+    parts.join(separator)
+}
+
+fn field(value: BoxedStrategy<String>) -> impl Strategy<Value = String> {
+    let component = prop::collection::vec(value, 1..3).prop_map(|s| join(s, "&"));
+    let repetition = prop::collection::vec(component, 1..4).prop_map(|c| join(c, "^"));
+    prop::collection::vec(repetition, 1..3).prop_map(|r| join(r, "~"))
+}
+
+fn segment(value: BoxedStrategy<String>) -> impl Strategy<Value = String> {
+    (
+        "[A-Z][A-Z0-9]{2}".prop_filter("not a header", |name| {
+            !matches!(name.as_str(), "MSH" | "FHS" | "BHS")
+        }),
+        prop::collection::vec(field(value), 1..14),
+    )
+        .prop_map(|(name, fields)| format!("{}|{}", name, fields.join("|")))
+}
+
+fn message(value: fn() -> BoxedStrategy<String>) -> impl Strategy<Value = String> {
+    (
+        prop::collection::vec(field(value()), 1..14),
+        prop::collection::vec(segment(value()), 0..4),
+    )
+        .prop_map(|(msh_fields, segments)| {
+            let mut lines = vec![format!("MSH|^~\\&|{}", msh_fields.join("|"))];
+            lines.extend(segments);
+            lines.join("\n")
+        })
+}
+
+proptest! {
+    #[test]
+    fn lossless_roundtrip(hl7_string in message(raw_value)) {
+        for numbering in [MshNumbering::Legacy, MshNumbering::Standard] {
+            let parse_options = ParseOptions::lossless().with_msh_numbering(numbering);
+            let serialize_options = SerializeOptions::lossless().with_msh_numbering(numbering);
+
+            let json = hl7_to_json(&hl7_string, &parse_options).unwrap();
+            prop_assert_eq!(json_to_hl7(&json, &serialize_options), hl7_string.clone());
+        }
+    }
+
+    #[test]
+    fn escaped_roundtrip(hl7_string in message(canonical_value)) {
+        let json = hl7_to_json(&hl7_string, &ParseOptions::default()).unwrap();
+        prop_assert_eq!(json_to_hl7(&json, &SerializeOptions::default()), hl7_string);
+    }
+}
+
+fn nested_segment() -> impl Strategy<Value = Hl7NestedSegment> {
+    let text = "[A-Za-z0-9 |^~&\\\\\\n\\t]{1,6}";
+    let component = prop::collection::vec(text, 1..3);
+    let repetition = prop::collection::vec(component, 1..3);
+    let field = prop::collection::vec(repetition, 1..3);
+
+    (
+        "[A-Z][A-Z0-9]{2}".prop_filter("not a header", |name| {
+            !matches!(name.as_str(), "MSH" | "FHS" | "BHS")
+        }),
+        prop::collection::vec(field, 1..8),
+    )
+        .prop_map(|(segment_name, fields)| Hl7NestedSegment {
+            segment_name,
+            fields,
+        })
+}
+
+proptest! {
+    #[test]
+    fn escaped_values_roundtrip(segments in prop::collection::vec(nested_segment(), 1..4)) {
+        let mut json_data = vec![Hl7NestedSegment {
+            segment_name: "MSH".to_string(),
+            fields: vec![vec![vec![vec!["^~\\&".to_string()]]]],
+        }];
+        json_data.extend(segments);
+
+        let hl7_string = nested_json_to_hl7(&json_data, &SerializeOptions::default());
+        let parsed = hl7_to_nested_json(&hl7_string, &ParseOptions::default()).unwrap();
+        prop_assert_eq!(parsed, json_data);
+    }
+}
+
+#[test]
+fn test_compare_json_keys() {
+    use crate::conversion::compare_json_keys;
+
+    let mut keys = vec![
+        "10",
+        "2[1].1",
+        "segment_name",
+        "2[0]",
+        "1",
+        "3.1.2",
+        "3.1.1",
+        "x",
+        "3.10",
+        "3.2",
+    ];
+    keys.sort_by(|a, b| compare_json_keys(a, b));
+
+    assert_eq!(
+        keys,
+        vec![
+            "segment_name",
+            "1",
+            "2[0]",
+            "2[1].1",
+            "3.1.1",
+            "3.1.2",
+            "3.2",
+            "3.10",
+            "10",
+            "x"
+        ]
+    );
+}
+
+#[test]
+fn test_escaped_roundtrip_preserves_structure() {
+    let hl7_string = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1\nPID|1||111~222^^^HOSP&1.2.3&ISO||DOE\\S\\SMITH^JOHN\\T\\JR^^|||||||||\nNTE|1||A\\F\\B\\R\\C\\E\\D&E";
+    let json = hl7_to_json(hl7_string, &ParseOptions::default()).unwrap();
+
+    assert_eq!(json[1].get("5.1").unwrap(), "DOE^SMITH");
+    assert_eq!(json[1].get("5.2").unwrap(), "JOHN&JR");
+    assert_eq!(json[2].get("3.1.1").unwrap(), "A|B~C\\D");
+    assert_eq!(json_to_hl7(&json, &SerializeOptions::default()), hl7_string);
+}
+
+#[test]
+fn test_custom_field_separator_roundtrip() {
+    let hl7_string =
+        "MSH#^~\\&#A#B#C#D#20240101##ADT^A01^ADT_A01#1#T#2.5.1\nPID#1##111^^^HOSP##DOE^JOHN";
+
+    let parse_options = ParseOptions::default().with_msh_numbering(MshNumbering::Standard);
+    let serialize_options = SerializeOptions::default().with_msh_numbering(MshNumbering::Standard);
+    let json = hl7_to_json(hl7_string, &parse_options).unwrap();
+    assert_eq!(json[0].get("1").unwrap(), "#");
+    assert_eq!(json_to_hl7(&json, &serialize_options), hl7_string);
+
+    let json = hl7_to_json(hl7_string, &ParseOptions::default()).unwrap();
+    assert!(!json[0].contains_key("0"));
+    assert_eq!(
+        json_to_hl7(&json, &SerializeOptions::default()),
+        hl7_string.replace('#', "|")
+    );
+}
@@ -0,0 +1,129 @@
+use crate::errors::Hl7Error;
+use crate::{
+    hl7_to_json, json_schema, segment_json_schema, validate_json_schema, MshNumbering, ParseOptions,
+};
+use serde_json::json;
+
+const MESSAGE: &str = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1\nPID|1||111^^^HOSP~222||DOE^JOHN&J\nPV1|1|I";
+
+#[test]
+fn test_json_schema_accepts_converted_messages() {
+    for numbering in [MshNumbering::Legacy, MshNumbering::Standard] {
+        let options = ParseOptions::new().with_msh_numbering(numbering);
+        let json = serde_json::to_value(hl7_to_json(MESSAGE, &options).unwrap()).unwrap();
+
+        assert!(validate_json_schema(&json, &json_schema(numbering)).is_ok());
+    }
+}
+
+#[test]
+fn test_json_schema_rejects_invalid_data() {
+    let schema = json_schema(MshNumbering::Legacy);
+
+    let invalid = [
+        json!([{"segment_name": "PID", "3.x": "A"}]),
+        json!([{"segment_name": "PID", "3.1.2.3": "A"}]),
+        json!([{"segment_name": "PID", "3": 5}]),
+        json!([{"1": "A"}]),
+        json!([{"segment_name": "pid"}]),
+        json!([{"segment_name": "MSH", "2": "A"}]),
+        json!({"segment_name": "PID"}),
+    ];
+    for instance in invalid {
+        assert!(
+            matches!(
+                validate_json_schema(&instance, &schema),
+                Err(Hl7Error::ValidationFailed { .. })
+            ),
+            "{} should be rejected",
+            instance
+        );
+    }
+
+    let result = validate_json_schema(&json!([{"segment_name": "PID", "3[a]": "A"}]), &schema);
+    assert!(
+        matches!(result, Err(Hl7Error::ValidationFailed { details }) if details.contains("/0"))
+    );
+}
+
+#[test]
+fn test_json_schema_header_numbering() {
+    let legacy = json!([{"segment_name": "MSH", "1": "^~\\&", "8.1": "ADT"}]);
+    let standard = json!([{"segment_name": "MSH", "1": "|", "2": "^~\\&", "9.1": "ADT"}]);
+
+    assert!(validate_json_schema(&legacy, &json_schema(MshNumbering::Legacy)).is_ok());
+    assert!(validate_json_schema(&standard, &json_schema(MshNumbering::Standard)).is_ok());
+    assert!(validate_json_schema(&standard, &json_schema(MshNumbering::Legacy)).is_err());
+    assert!(validate_json_schema(&legacy, &json_schema(MshNumbering::Standard)).is_err());
+}
+
+#[test]
+fn test_segment_json_schema() {
+    let schema = segment_json_schema("MSH", MshNumbering::Standard);
+    assert_eq!(schema["required"], json!(["segment_name", "1", "2"]));
+    assert!(validate_json_schema(
+        &json!({"segment_name": "MSH", "1": "|", "2": "^~\\&"}),
+        &schema
+    )
+    .is_ok());
+    assert!(validate_json_schema(
+        &json!({"segment_name": "PID", "1": "|", "2": "^~\\&"}),
+        &schema
+    )
+    .is_err());
+
+    let schema = segment_json_schema("PID", MshNumbering::Legacy);
+    assert_eq!(schema["required"], json!(["segment_name"]));
+    assert!(
+        validate_json_schema(&json!({"segment_name": "PID", "3[1].4.1": "A"}), &schema).is_ok()
+    );
+}
+
+#[test]
+fn test_segment_json_schema_fields_from_definitions() {
+    let schema = segment_json_schema("PID", MshNumbering::Legacy);
+    let valid = |key: &str| {
+        validate_json_schema(&json!({"segment_name": "PID", key: "A"}), &schema).is_ok()
+    };
+    assert!(valid("1"));
+    assert!(valid("5.1.1"));
+    assert!(valid("5[1].14"));
+    assert!(valid("7.1"));
+    assert!(valid("31.2.1"));
+    assert!(!valid("1.1"));
+    assert!(!valid("1[0]"));
+    assert!(!valid("5.15"));
+    assert!(!valid("5.2.1"));
+    assert!(!valid("7.1.1"));
+
+    let header = json!({"segment_name": "MSH", "1": "^~\\&", "8.3": "ADT_A01"});
+    assert!(
+        validate_json_schema(&header, &segment_json_schema("MSH", MshNumbering::Legacy)).is_ok()
+    );
+    let header = json!({"segment_name": "MSH", "1": "|", "2": "^~\\&", "8.3": "ADT_A01"});
+    assert!(
+        validate_json_schema(&header, &segment_json_schema("MSH", MshNumbering::Standard)).is_err()
+    );
+
+    let schema = segment_json_schema("OBX", MshNumbering::Legacy);
+    assert!(
+        validate_json_schema(&json!({"segment_name": "OBX", "5[0].7.2": "A"}), &schema).is_ok()
+    );
+    let schema = segment_json_schema("ZPI", MshNumbering::Legacy);
+    assert!(
+        validate_json_schema(&json!({"segment_name": "ZPI", "1[0].9.9": "A"}), &schema).is_ok()
+    );
+}
+
+#[test]
+fn test_segment_json_schema_accepts_converted_segments() {
+    let hl7 = include_str!("test_data/lipid_panel_oru.hl7");
+    for numbering in [MshNumbering::Legacy, MshNumbering::Standard] {
+        let options = ParseOptions::new().with_msh_numbering(numbering);
+        for segment in hl7_to_json(hl7, &options).unwrap() {
+            let schema = segment_json_schema(&segment["segment_name"], numbering);
+            let segment = serde_json::to_value(&segment).unwrap();
+            assert!(validate_json_schema(&segment, &schema).is_ok());
+        }
+    }
+}
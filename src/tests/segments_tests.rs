@@ -1,4 +1,5 @@
-use crate::segments::{Hl7Field, Hl7Segment};
+use crate::escape::Hl7EscapeHandler;
+use crate::segments::{Hl7Field, Hl7NestedSegment, Hl7Segment, MshNumbering};
 
 #[test]
 fn test_segment_from_string() {
@@ -131,3 +132,166 @@ fn test_segment_to_json_with_repetitions_and_components() {
     assert_eq!(json.get("4"), Some(&"".to_string()));
     assert_eq!(json.get("5"), Some(&"Result".to_string()));
 }
+
+#[test]
+fn test_field_with_subcomponents() {
+    let field = Hl7Field::from_string("12345^^^HOSP&1.2.3&ISO^MR", None);
+
+    assert_eq!(field.components.as_ref().unwrap().len(), 5);
+    assert_eq!(field.components.as_ref().unwrap()[3], "HOSP&1.2.3&ISO");
+
+    let subcomponents = field.subcomponents.unwrap();
+    assert_eq!(subcomponents[0], vec!["12345".to_string()]);
+    assert_eq!(
+        subcomponents[3],
+        vec!["HOSP".to_string(), "1.2.3".to_string(), "ISO".to_string()]
+    );
+}
+
+#[test]
+fn test_field_with_subcomponents_without_components() {
+    let field = Hl7Field::from_string("HOSP&1.2.3&ISO", None);
+
+    assert_eq!(field.components, Some(vec!["HOSP&1.2.3&ISO".to_string()]));
+    assert_eq!(
+        field.subcomponents,
+        Some(vec![vec![
+            "HOSP".to_string(),
+            "1.2.3".to_string(),
+            "ISO".to_string()
+        ]])
+    );
+}
+
+#[test]
+fn test_segment_to_json_with_subcomponents() {
+    let segment = Hl7Segment::from_string(
+        "PID|1||12345^^^HOSP&1.2.3&ISO^MR~67890^^^CLINIC&2.16.840&ISO^PI",
+        None,
+    );
+    let json = segment.to_json();
+
+    assert_eq!(json.get("3[0].1"), Some(&"12345".to_string()));
+    assert_eq!(json.get("3[0].4.1"), Some(&"HOSP".to_string()));
+    assert_eq!(json.get("3[0].4.2"), Some(&"1.2.3".to_string()));
+    assert_eq!(json.get("3[0].4.3"), Some(&"ISO".to_string()));
+    assert_eq!(json.get("3[0].5"), Some(&"MR".to_string()));
+    assert_eq!(json.get("3[1].4.2"), Some(&"2.16.840".to_string()));
+    assert!(!json.contains_key("3[0].4"));
+}
+
+#[test]
+fn test_segment_to_json_with_subcomponents_no_repetitions() {
+    let segment = Hl7Segment::from_string("PID|1||12345^^^HOSP&1.2.3&ISO", None);
+    let json = segment.to_json();
+
+    assert_eq!(json.get("3.1"), Some(&"12345".to_string()));
+    assert_eq!(json.get("3.4.1"), Some(&"HOSP".to_string()));
+    assert_eq!(json.get("3.4.2"), Some(&"1.2.3".to_string()));
+    assert_eq!(json.get("3.4.3"), Some(&"ISO".to_string()));
+}
+
+#[test]
+fn test_msh_encoding_characters_not_split_into_subcomponents() {
+    let segment = Hl7Segment::from_string("MSH|^~\\&|ADT1", None);
+    let json = segment.to_json();
+
+    assert_eq!(json.get("1"), Some(&"^~\\&".to_string()));
+    assert!(segment.fields.get(&1).unwrap().subcomponents.is_none());
+}
+
+#[test]
+fn test_segment_standard_msh_numbering() {
+    let handler = Hl7EscapeHandler::default();
+    let segment = Hl7Segment::parse_with_numbering(
+        "MSH|^~\\&|ADT1|HOSPITAL|LAB|HOSPITAL|20240101120000||ADT^A01^ADT_A01|MSG00001|T|2.5.1",
+        &handler,
+        true,
+        MshNumbering::Standard,
+    );
+    let json = segment.to_json();
+
+    assert_eq!(json.get("1"), Some(&"|".to_string()));
+    assert_eq!(json.get("2"), Some(&"^~\\&".to_string()));
+    assert_eq!(json.get("3"), Some(&"ADT1".to_string()));
+    assert_eq!(json.get("9.1"), Some(&"ADT".to_string()));
+    assert_eq!(json.get("10"), Some(&"MSG00001".to_string()));
+    assert_eq!(json.get("12"), Some(&"2.5.1".to_string()));
+}
+
+#[test]
+fn test_segment_standard_msh_numbering_ignores_other_segments() {
+    let handler = Hl7EscapeHandler::default();
+    let segment =
+        Hl7Segment::parse_with_numbering("PID|1||12345", &handler, false, MshNumbering::Standard);
+
+    assert_eq!(segment.fields.get(&1).unwrap().value, "1");
+    assert_eq!(segment.fields.get(&3).unwrap().value, "12345");
+}
+
+#[test]
+fn test_segment_to_nested_json() {
+    let segment = Hl7Segment::from_string("PID|1||111~222^^^HOSP&1.2.3&ISO||DOE^JOHN", None);
+    let nested = segment.to_nested_json();
+
+    assert_eq!(nested.segment_name, "PID");
+    assert_eq!(nested.fields.len(), 5);
+    assert_eq!(nested.fields[0], vec![vec![vec!["1".to_string()]]]);
+    assert!(nested.fields[1].is_empty());
+    assert_eq!(nested.fields[2].len(), 2);
+    assert_eq!(nested.fields[2][0], vec![vec!["111".to_string()]]);
+    assert_eq!(nested.fields[2][1][3], vec!["HOSP", "1.2.3", "ISO"]);
+    assert_eq!(nested.fields[4][0][1], vec!["JOHN"]);
+}
+
+#[test]
+fn test_nested_segment_header_fields() {
+    let handler = Hl7EscapeHandler::default();
+    let segment =
+        Hl7Segment::parse_with_numbering("MSH|^~\\&|ADT1", &handler, false, MshNumbering::Standard);
+    let nested = segment.to_nested_json();
+
+    assert_eq!(nested.fields[0], vec![vec![vec!["|".to_string()]]]);
+    assert_eq!(nested.fields[1], vec![vec![vec!["^~\\&".to_string()]]]);
+}
+
+#[test]
+fn test_nested_segment_to_flat_json() {
+    let nested = Hl7NestedSegment {
+        segment_name: "PID".to_string(),
+        fields: vec![
+            vec![vec![vec!["1".to_string()]]],
+            vec![],
+            vec![
+                vec![vec!["111".to_string()]],
+                vec![
+                    vec!["222".to_string()],
+                    vec!["HOSP".to_string(), "ISO".to_string()],
+                ],
+            ],
+        ],
+    };
+    let flat = nested.to_flat_json();
+
+    assert_eq!(flat.get("segment_name").unwrap(), "PID");
+    assert_eq!(flat.get("1").unwrap(), "1");
+    assert!(!flat.contains_key("2"));
+    assert_eq!(flat.get("3[0].1.1").unwrap(), "111");
+    assert_eq!(flat.get("3[1].1.1").unwrap(), "222");
+    assert_eq!(flat.get("3[1].2.2").unwrap(), "ISO");
+}
+
+#[test]
+fn test_segment_to_typed_json() {
+    let segment = Hl7Segment::from_string("PID|1||\"\"||DOE^\"\"^^JR", None);
+    let typed = segment.to_typed_json();
+
+    assert_eq!(typed.get("segment_name").unwrap().as_deref(), Some("PID"));
+    assert_eq!(typed.get("1").unwrap().as_deref(), Some("1"));
+    assert_eq!(typed.get("2").unwrap().as_deref(), Some(""));
+    assert_eq!(typed.get("3").unwrap(), &None);
+    assert_eq!(typed.get("5.1").unwrap().as_deref(), Some("DOE"));
+    assert_eq!(typed.get("5.2").unwrap(), &None);
+    assert_eq!(typed.get("5.3").unwrap().as_deref(), Some(""));
+    assert!(!typed.contains_key("6"));
+}
@@ -0,0 +1,154 @@
+#[cfg(feature = "validation")]
+use crate::{Hl7Validator, NdjsonErrorRecord};
+use crate::{MessageReader, MshNumbering, NdjsonSummary, NdjsonWriter, ParseOptions};
+use std::collections::BTreeMap;
+use std::io::{BufReader, Cursor, Write};
+
+const ARCHIVE: &str = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1\rPID|1||111||DOE^JOHN\r\nMSH|^~\\&|A|B|C|D|20240101||ADT^A08^ADT_A01|2|T|2.5.1\nPID|1||222||ROE^JANE\n\n\rMSH|^~\\&|A|B|C|D|20240101||ORU^R01^ORU_R01|3|T|2.5.1\rOBX|1|ST|CODE||Fish \\T\\ Chips";
+
+#[test]
+fn test_message_reader_splits_at_msh() {
+    let reader = MessageReader::new(Cursor::new(ARCHIVE), ParseOptions::default());
+    let messages: Vec<_> = reader.map(|m| m.unwrap()).collect();
+
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0].segments.len(), 2);
+    assert_eq!(messages[1].segments[1].fields.get(&3).unwrap().value, "222");
+    assert_eq!(
+        messages[2].segments[1].fields.get(&5).unwrap().value,
+        "Fish & Chips"
+    );
+}
+
+#[test]
+fn test_message_reader_small_buffer() {
+    let buffered = BufReader::with_capacity(4, Cursor::new(ARCHIVE));
+    let mut reader = MessageReader::new(buffered, ParseOptions::default());
+
+    let first = reader.next_raw().unwrap().unwrap();
+    assert_eq!(
+        first,
+        "MSH|^~\\&|A|B|C|D|20240101||ADT^A01^ADT_A01|1|T|2.5.1\nPID|1||111||DOE^JOHN"
+    );
+    assert!(reader.next_raw().unwrap().is_some());
+    assert!(reader.next_raw().unwrap().is_some());
+    assert!(reader.next_raw().unwrap().is_none());
+}
+
+#[test]
+fn test_message_reader_keeps_leading_segments() {
+    let input = "FHS|^~\\&|A\rMSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|T|2.5.1\rPID|1";
+    let mut reader = MessageReader::new(Cursor::new(input), ParseOptions::default());
+
+    let message = reader.next().unwrap().unwrap();
+    assert_eq!(message.segments.len(), 3);
+    assert_eq!(message.segments[0].segment_name, "FHS");
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_message_reader_empty_input() {
+    let mut reader = MessageReader::new(Cursor::new("\r\n\n"), ParseOptions::default());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_message_reader_invalid_utf8() {
+    let input: &[u8] = b"MSH|^~\\&|A\rPID|1||\xff\xfe";
+    let mut reader = MessageReader::new(input, ParseOptions::default());
+
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_message_reader_open_with_options() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(ARCHIVE.as_bytes()).unwrap();
+
+    let options = ParseOptions::new().with_msh_numbering(MshNumbering::Standard);
+    let reader = MessageReader::open(file.path(), options).unwrap();
+    let message_types: Vec<String> = reader
+        .map(|m| m.unwrap().segments[0].fields.get(&9).unwrap().value.clone())
+        .collect();
+
+    assert_eq!(
+        message_types,
+        vec!["ADT^A01^ADT_A01", "ADT^A08^ADT_A01", "ORU^R01^ORU_R01"]
+    );
+}
+
+#[test]
+fn test_ndjson_writer_one_line_per_message() {
+    let mut reader = MessageReader::new(Cursor::new(ARCHIVE), ParseOptions::default());
+    let mut writer = NdjsonWriter::new(Vec::new());
+    let summary = writer.write_reader(&mut reader).unwrap();
+    assert_eq!(
+        summary,
+        NdjsonSummary {
+            messages: 3,
+            errors: 0
+        }
+    );
+
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with(r#"[{"segment_name":"MSH","1":"^~\\&","2":"A","#));
+    assert!(lines[0].contains(
+        r#"{"segment_name":"PID","1":"1","2":"","3":"111","4":"","5.1":"DOE","5.2":"JOHN"}"#
+    ));
+
+    let third: Vec<BTreeMap<String, String>> = serde_json::from_str(lines[2]).unwrap();
+    assert_eq!(third[1]["5"], "Fish & Chips");
+}
+
+#[cfg(feature = "validation")]
+#[test]
+fn test_ndjson_writer_error_records() {
+    let input = "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|T|2.5.1||AL\rPID|1||111\rMSH|^~\\&|A|B|C|D|20240101\rPID|1||222\rMSH|^~\\&|A|B|C|D|20240101||ADT^A08|3|T|2.5.1||AL\rPID|1||333";
+    let mut reader = MessageReader::new(Cursor::new(input), ParseOptions::default());
+    let mut writer = NdjsonWriter::new(Vec::new()).with_validator(
+        Hl7Validator::new()
+            .with_strict_mode(false)
+            .with_required_fields_validation(false),
+    );
+    let summary = writer.write_reader(&mut reader).unwrap();
+    assert_eq!(
+        summary,
+        NdjsonSummary {
+            messages: 3,
+            errors: 1
+        }
+    );
+
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    let record: NdjsonErrorRecord = serde_json::from_str(lines[1]).unwrap();
+    assert_eq!(record.message_index, 1);
+    assert_eq!(
+        record.error,
+        "HL7 validation failed: MSH segment must have at least 11 fields"
+    );
+    assert!(lines[2].contains(r#""3":"333""#));
+}
+
+#[test]
+fn test_ndjson_writer_create() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("out.ndjson");
+
+    let mut writer = NdjsonWriter::create(&path).unwrap();
+    writer
+        .write_message(
+            "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|T|2.5.1\rPID|1||111",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(writer.summary().messages, 1);
+    drop(writer.into_inner().unwrap());
+
+    let output = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(output.lines().count(), 1);
+    assert!(output.ends_with("]\n"));
+}
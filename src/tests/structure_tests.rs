@@ -0,0 +1,167 @@
+use crate::errors::Hl7Error;
+use crate::{
+    parse_message, GroupedNode, Hl7GroupedJson, MessageStructure, MshNumbering, ParseOptions,
+    StructureElement,
+};
+
+const ORU_R01: &str = "MSH|^~\\&|LAB|FAC|EHR|FAC|20240101||ORU^R01^ORU_R01|1|P|2.5.1\rPID|1||111||DOE^JOHN\rPV1|1|O\rORC|RE|1\rOBR|1|1||CBC\rNTE|1||order note\rOBX|1|NM|WBC||7.1\rNTE|1||wbc note\rOBX|2|NM|RBC||4.5\rOBR|2|2||BMP\rOBX|1|NM|GLU||5.6";
+
+fn group<'a>(node: &'a GroupedNode, name: &str) -> &'a [GroupedNode] {
+    match node {
+        GroupedNode::Group(group) if group.group_name == name => &group.children,
+        _ => panic!("expected group {}, got {:?}", name, node),
+    }
+}
+
+fn segment_name(node: &GroupedNode) -> &str {
+    match node {
+        GroupedNode::Segment(segment) => segment.get("segment_name").unwrap(),
+        GroupedNode::Group(group) => panic!("expected segment, got group {}", group.group_name),
+    }
+}
+
+#[test]
+fn test_oru_r01_grouping() {
+    let message = parse_message(ORU_R01, &ParseOptions::default()).unwrap();
+    let grouped = message.to_grouped_json().unwrap();
+
+    assert_eq!(grouped.structure, "ORU_R01");
+    assert!(grouped.diagnostics.is_empty());
+    assert_eq!(grouped.children.len(), 2);
+    assert_eq!(segment_name(&grouped.children[0]), "MSH");
+
+    let patient_result = group(&grouped.children[1], "PATIENT_RESULT");
+    assert_eq!(patient_result.len(), 3);
+
+    let patient = group(&patient_result[0], "PATIENT");
+    assert_eq!(segment_name(&patient[0]), "PID");
+    assert_eq!(segment_name(&group(&patient[1], "VISIT")[0]), "PV1");
+
+    let first_order = group(&patient_result[1], "ORDER_OBSERVATION");
+    let names: Vec<_> = first_order.iter().take(3).map(segment_name).collect();
+    assert_eq!(names, vec!["ORC", "OBR", "NTE"]);
+    let observation = group(&first_order[3], "OBSERVATION");
+    assert_eq!(observation.len(), 2);
+    assert_eq!(segment_name(&observation[1]), "NTE");
+    assert_eq!(group(&first_order[4], "OBSERVATION").len(), 1);
+
+    let second_order = group(&patient_result[2], "ORDER_OBSERVATION");
+    assert_eq!(segment_name(&second_order[0]), "OBR");
+    assert_eq!(group(&second_order[1], "OBSERVATION").len(), 1);
+}
+
+#[test]
+fn test_adt_a01_grouping_by_trigger_event() {
+    let hl7_string = "MSH|^~\\&|A|B|C|D|20240101||ADT^A08|1|P|2.5.1\rEVN|A08\rPID|1||111\rPV1|1|I\rPR1|1||P1\rROL|1\rIN1|1|PLAN\rIN2|1\rIN1|2|PLAN2";
+    let message = parse_message(hl7_string, &ParseOptions::default()).unwrap();
+    let grouped = message.to_grouped_json().unwrap();
+
+    assert_eq!(grouped.structure, "ADT_A01");
+    let names: Vec<_> = grouped.children[..4].iter().map(segment_name).collect();
+    assert_eq!(names, vec!["MSH", "EVN", "PID", "PV1"]);
+    assert_eq!(group(&grouped.children[4], "PROCEDURE").len(), 2);
+    assert_eq!(group(&grouped.children[5], "INSURANCE").len(), 2);
+    assert_eq!(group(&grouped.children[6], "INSURANCE").len(), 1);
+}
+
+#[test]
+fn test_grouping_with_standard_msh_numbering() {
+    let options = ParseOptions::new().with_msh_numbering(MshNumbering::Standard);
+    let message = parse_message(ORU_R01, &options).unwrap();
+    assert_eq!(message.to_grouped_json().unwrap().structure, "ORU_R01");
+}
+
+#[test]
+fn test_grouping_diagnostics() {
+    let hl7_string = ORU_R01.replace("PV1|1|O", "ZPI|1\rPV1|1|O") + "\rPID|2||222\rEVN|A01";
+    let message = parse_message(&hl7_string, &ParseOptions::default()).unwrap();
+    let grouped = message.to_grouped_json().unwrap();
+
+    let diagnostics: Vec<_> = grouped
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.index, diagnostic.segment_name.as_str()))
+        .collect();
+    assert_eq!(
+        diagnostics,
+        vec![(2, "ZPI"), (13, "EVN"), (14, "ORDER_OBSERVATION")]
+    );
+
+    assert_eq!(grouped.children.len(), 3);
+    let second_result = group(&grouped.children[2], "PATIENT_RESULT");
+    assert_eq!(segment_name(&group(&second_result[0], "PATIENT")[0]), "PID");
+
+    let json = serde_json::to_string(&grouped).unwrap();
+    let deserialized: Hl7GroupedJson = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, grouped);
+}
+
+#[test]
+fn test_missing_required_diagnostics() {
+    let diagnostics = |hl7_string: &str| {
+        let message = parse_message(hl7_string, &ParseOptions::default()).unwrap();
+        message
+            .to_grouped_json()
+            .unwrap()
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.index,
+                    diagnostic.segment_name,
+                    diagnostic.message,
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        diagnostics(
+            "MSH|^~\\&|A|B|C|D|20240101||ADT^A01|1|P|2.5.1\rEVN|A01\rPID|1||111\rIN1|1|PLAN"
+        ),
+        vec![(
+            3,
+            "PV1".to_string(),
+            "required segment is missing".to_string()
+        )]
+    );
+    assert_eq!(
+        diagnostics(&ORU_R01.replace("\rOBR|1|1||CBC", "")),
+        vec![(
+            4,
+            "OBR".to_string(),
+            "required segment is missing".to_string()
+        )]
+    );
+    assert_eq!(
+        diagnostics("MSH|^~\\&|A|B|C|D|20240101||ACK|1|P|2.5.1"),
+        vec![(
+            1,
+            "MSA".to_string(),
+            "required segment is missing".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_custom_and_unknown_structures() {
+    let message = parse_message(
+        "MSH|^~\\&|A|B|C|D|20240101||ZZZ^Z01|1|P|2.5.1\rPID|1",
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert!(matches!(
+        message.to_grouped_json(),
+        Err(Hl7Error::UnknownMessageStructure(name)) if name == "ZZZ^Z01"
+    ));
+
+    let structure = MessageStructure::new(
+        "ZZZ_Z01",
+        vec![
+            StructureElement::segment("MSH"),
+            StructureElement::group("PATIENT", vec![StructureElement::segment("PID")]).repeating(),
+        ],
+    );
+    let grouped = structure.group(&message.segments);
+    assert_eq!(group(&grouped.children[1], "PATIENT").len(), 1);
+}
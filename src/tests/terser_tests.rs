@@ -0,0 +1,176 @@
+use crate::errors::Hl7Error;
+use crate::{json_to_hl7, Hl7Message, Hl7Path, MshNumbering, ParseOptions, SerializeOptions};
+
+const ORU: &str = "MSH|^~\\&|LAB|FAC|EHR|FAC|20240101||ORU^R01|MSG1|P|2.5\nPID|1||111^^^HOSP~222^^^SSA&1.2&ISO||DOE^JOHN^Q\nOBR|1|1||CBC\nOBX|1|NM|WBC||7.1\nOBX|2|NM|RBC||4.5\nOBX|3|ST|NOTE||Fish \\T\\ Chips";
+
+#[test]
+fn test_path_parse() {
+    let path = Hl7Path::parse("PID-3(1)-4-2").unwrap();
+    assert_eq!(
+        path,
+        Hl7Path {
+            segment: "PID".to_string(),
+            occurrence: None,
+            field: 3,
+            repetition: Some(1),
+            component: Some(4),
+            subcomponent: Some(2),
+        }
+    );
+    assert_eq!(path.to_string(), "PID-3(1)-4-2");
+    assert_eq!(Hl7Path::parse("/OBX(2)-5").unwrap().to_string(), "OBX(2)-5");
+}
+
+#[test]
+fn test_path_parse_errors() {
+    let message = |path: &str| match Hl7Path::parse(path) {
+        Err(Hl7Error::InvalidPath { message, .. }) => message,
+        other => panic!("expected InvalidPath for {}, got {:?}", path, other),
+    };
+
+    assert_eq!(message("PID"), "expected SEG-field, e.g. PID-5");
+    assert_eq!(message("PID-0"), "field numbers start at 1");
+    assert_eq!(message("PID-5-x"), "component \"x\" is not a number");
+    assert_eq!(message("PID(1-5"), "unclosed index in \"PID(1\"");
+    assert_eq!(
+        message("pid-5"),
+        "segment name \"pid\" must be 3 uppercase letters or digits"
+    );
+    assert_eq!(
+        message("PID-5-1-1-1"),
+        "expected at most SEG-field-component-subcomponent"
+    );
+    assert_eq!(
+        Hl7Path::parse("PID-x").unwrap_err().to_string(),
+        "Invalid HL7 path PID-x: field \"x\" is not a number"
+    );
+}
+
+#[test]
+fn test_get() {
+    let message = Hl7Message::parse(ORU, &ParseOptions::default()).unwrap();
+
+    assert_eq!(message.get("PID-5").unwrap().as_deref(), Some("DOE^JOHN^Q"));
+    assert_eq!(message.get("PID-5-2").unwrap().as_deref(), Some("JOHN"));
+    assert_eq!(message.get("PID-3-1").unwrap().as_deref(), Some("111"));
+    assert_eq!(
+        message.get("PID-3(1)-4").unwrap().as_deref(),
+        Some("SSA&1.2&ISO")
+    );
+    assert_eq!(message.get("PID-3(1)-4-2").unwrap().as_deref(), Some("1.2"));
+    assert_eq!(
+        message.get("OBX(2)-5").unwrap().as_deref(),
+        Some("Fish & Chips")
+    );
+    assert_eq!(message.get("PID-2").unwrap().as_deref(), Some(""));
+    assert_eq!(message.get("PID-3(2)").unwrap(), None);
+    assert_eq!(message.get("PID-30").unwrap(), None);
+    assert_eq!(message.get("OBX(3)-5").unwrap(), None);
+    assert_eq!(message.get("NK1-2").unwrap(), None);
+}
+
+#[test]
+fn test_get_all() {
+    let message = Hl7Message::parse(ORU, &ParseOptions::default()).unwrap();
+
+    assert_eq!(
+        message.get_all("OBX-3").unwrap(),
+        vec!["WBC", "RBC", "NOTE"]
+    );
+    assert_eq!(message.get_all("PID-3-1").unwrap(), vec!["111", "222"]);
+    assert_eq!(message.get_all("PID-3(1)-1").unwrap(), vec!["222"]);
+    assert_eq!(message.get_all("OBX(1)-5").unwrap(), vec!["4.5"]);
+    assert!(message.get_all("NTE-3").unwrap().is_empty());
+}
+
+#[test]
+fn test_msh_numbering() {
+    let legacy = Hl7Message::parse(ORU, &ParseOptions::default()).unwrap();
+    assert_eq!(legacy.get("MSH-8-1").unwrap().as_deref(), Some("ORU"));
+    assert_eq!(legacy.get("MSH-1").unwrap().as_deref(), Some("^~\\&"));
+    assert!(legacy.get("MSH-1-1").is_err());
+
+    let options = ParseOptions::default().with_msh_numbering(MshNumbering::Standard);
+    let standard = Hl7Message::parse(ORU, &options).unwrap();
+    assert_eq!(standard.get("MSH-9-1").unwrap().as_deref(), Some("ORU"));
+    assert_eq!(standard.get("MSH-10").unwrap().as_deref(), Some("MSG1"));
+    assert_eq!(standard.get("MSH-1").unwrap().as_deref(), Some("|"));
+    assert_eq!(standard.get("MSH-2").unwrap().as_deref(), Some("^~\\&"));
+}
+
+#[test]
+fn test_set() {
+    let mut message = Hl7Message::parse(ORU, &ParseOptions::lossless()).unwrap();
+
+    message.set("PID-5-2", "JANE").unwrap();
+    message.set("PID-3(1)-4-3", "DNS").unwrap();
+    message.set("PID-3(2)-1", "333").unwrap();
+    message.set("PID-8", "F").unwrap();
+    message.set("OBX(1)-5", "5.0^x10*12/L").unwrap();
+    message.set("OBR-4", "").unwrap();
+
+    assert_eq!(message.get("PID-5").unwrap().as_deref(), Some("DOE^JANE^Q"));
+    assert_eq!(
+        message.get_all("PID-3-1").unwrap(),
+        vec!["111", "222", "333"]
+    );
+    assert_eq!(
+        message.get("OBX(1)-5-2").unwrap().as_deref(),
+        Some("x10*12/L")
+    );
+
+    let hl7 = json_to_hl7(&message.to_json(), &SerializeOptions::lossless());
+    let lines: Vec<&str> = hl7.lines().collect();
+    assert_eq!(
+        lines[1],
+        "PID|1||111^^^HOSP~222^^^SSA&1.2&DNS~333||DOE^JANE^Q|||F"
+    );
+    assert_eq!(lines[2], "OBR|1|1||");
+    assert_eq!(lines[4], "OBX|2|NM|RBC||5.0^x10*12/L");
+}
+
+#[test]
+fn test_set_errors() {
+    let mut message = Hl7Message::parse(ORU, &ParseOptions::default()).unwrap();
+
+    assert_eq!(
+        message.set("NK1-2", "DOE").unwrap_err().to_string(),
+        "Invalid HL7 path NK1-2: segment NK1(0) does not exist"
+    );
+    assert_eq!(
+        message.set("OBX(5)-5", "1").unwrap_err().to_string(),
+        "Invalid HL7 path OBX(5)-5: segment OBX(5) does not exist"
+    );
+    assert!(matches!(
+        message.set("MSH-1", "#~\\&"),
+        Err(Hl7Error::InvalidPath { .. })
+    ));
+    assert!(message.set("PID-", "x").is_err());
+}
+
+#[test]
+fn test_constructed_path_zero_positions() {
+    let mut message = Hl7Message::parse(ORU, &ParseOptions::default()).unwrap();
+    let path = |component, subcomponent| Hl7Path {
+        segment: "PID".to_string(),
+        occurrence: None,
+        field: 5,
+        repetition: None,
+        component,
+        subcomponent,
+    };
+
+    assert_eq!(
+        path(Some(0), None).get(&message).unwrap_err().to_string(),
+        "Invalid HL7 path PID-5-0: component numbers start at 1"
+    );
+    assert!(matches!(
+        path(Some(1), Some(0)).set(&mut message, "x"),
+        Err(Hl7Error::InvalidPath { .. })
+    ));
+    assert!(matches!(
+        path(None, Some(1)).get_all(&message),
+        Err(Hl7Error::InvalidPath { .. })
+    ));
+    assert_eq!(message.get("PID-5-1").unwrap().as_deref(), Some("DOE"));
+}
@@ -0,0 +1,161 @@
+use crate::errors::Hl7Error;
+use crate::{Hl7MutableMessage, Hl7Transform, MshNumbering};
+
+const ADT: &str = "MSH|^~\\&|APP|FAC|EHR|FAC|20240101||ADT^A01|MSG1|P|2.5\nPID|1||12345^^^HOSP||DOE^JOHN||19800101|M\nPV1|1|I|WARD^101^A\nZPD|1|internal";
+
+fn apply(transform: &Hl7Transform) -> String {
+    transform.apply_to_hl7(ADT, MshNumbering::Legacy).unwrap()
+}
+
+#[test]
+fn test_set_copy_move_delete() {
+    let transform = Hl7Transform::from_json(
+        r#"{
+            "rules": [
+                {"action": "set", "path": "MSH-3", "value": "HUB"},
+                {"action": "set", "path": "PID-18", "value": "{PID-5-2} {PID-5-1} {{co}}"},
+                {"action": "copy", "from": "PID-3", "to": "PID-2"},
+                {"action": "move", "from": "PV1-3-2", "to": "PV1-6"},
+                {"action": "delete", "path": "PID-7"}
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let output = apply(&transform);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines[0],
+        "MSH|^~\\&|APP|HUB|EHR|FAC|20240101||ADT^A01|MSG1|P|2.5"
+    );
+    assert_eq!(
+        lines[1],
+        "PID|1|12345^^^HOSP|12345^^^HOSP||DOE^JOHN|||M||||||||||JOHN DOE {co}"
+    );
+    assert_eq!(lines[2], "PV1|1|I|WARD^^A|||101");
+}
+
+#[test]
+fn test_conditions_and_lookups() {
+    let transform = Hl7Transform::from_json(
+        r#"{
+            "lookups": {"sex": {"M": "male", "F": "female"}},
+            "rules": [
+                {"action": "lookup", "path": "PID-8", "table": "sex", "default": "unknown"},
+                {"action": "lookup", "path": "PV1-2", "table": "sex", "to": "PV1-4"},
+                {"action": "set", "path": "PV1-10", "value": "INPATIENT", "when": {"path": "PV1-2", "equals": "I"}},
+                {"action": "set", "path": "PV1-11", "value": "X", "when": {"path": "PV1-2", "not_equals": "I"}},
+                {"action": "set", "path": "PV1-12", "value": "Y", "when": {"path": "PV1-9", "exists": false}}
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let output = apply(&transform);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[1], "PID|1||12345^^^HOSP||DOE^JOHN||19800101|male");
+    assert_eq!(lines[2], "PV1|1|I|WARD^101^A|||||||INPATIENT||Y");
+}
+
+#[test]
+fn test_segment_rules_from_toml() {
+    let transform = Hl7Transform::from_toml(
+        r#"
+        [[rules]]
+        action = "remove_segment"
+        segment = "ZPD"
+
+        [[rules]]
+        action = "add_segment"
+        segment = "EVN|A01|20240101"
+        before = "PID"
+
+        [[rules]]
+        action = "add_segment"
+        segment = "NTE|1||transformed"
+        after = "PV1"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(transform.rules.len(), 3);
+
+    let mut message = Hl7MutableMessage::parse(ADT, MshNumbering::Legacy).unwrap();
+    transform.apply(&mut message).unwrap();
+    assert_eq!(
+        message.segment_names(),
+        vec!["MSH", "EVN", "PID", "PV1", "NTE"]
+    );
+    assert_eq!(
+        message.get("NTE-3").unwrap().as_deref(),
+        Some("transformed")
+    );
+}
+
+#[test]
+fn test_errors_report_rule_index() {
+    let error = Hl7Transform::from_json(
+        r#"{"rules": [
+            {"action": "set", "path": "PID-5", "value": "X"},
+            {"action": "lookup", "path": "PID-8", "table": "missing"}
+        ]}"#,
+    )
+    .unwrap_err();
+    assert!(matches!(error, Hl7Error::TransformError { index: 1, .. }));
+    assert!(error.to_string().contains("missing"));
+
+    let error =
+        Hl7Transform::from_json(r#"{"rules": [{"action": "set", "path": "PID", "value": "X"}]}"#)
+            .unwrap_err();
+    assert!(matches!(error, Hl7Error::TransformError { index: 0, .. }));
+
+    assert!(
+        Hl7Transform::from_json(r#"{"rules": [{"action": "rename", "path": "PID-5"}]}"#).is_err()
+    );
+
+    let error = Hl7Transform::from_json(
+        r#"{"rules": [{"action": "delete", "path": "PID-8", "when": {"path": "PID-8"}}]}"#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "HL7 transform rule 0: Configuration error: when takes at least one of equals, not_equals or exists"
+    );
+    assert!(Hl7Transform::from_json(
+        r#"{"rules": [{"action": "delete", "path": "PID-8", "when": {"path": "PID-8", "exists": true, "equal": "M"}}]}"#,
+    )
+    .is_err());
+    assert!(Hl7Transform::from_toml(
+        "[[rules]]\naction = \"delete\"\npath = \"PID-8\"\nwhen = { path = \"PID-8\", exists = true, equal = \"M\" }",
+    )
+    .is_err());
+    assert!(Hl7Transform::from_toml("rules = 5").is_err());
+
+    let error = Hl7Transform::from_json(
+        r#"{"rules": [{"action": "add_segment", "segment": "ZPD", "afer": "PID"}]}"#,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("unknown field `afer`"));
+    assert!(Hl7Transform::from_toml(
+        "[[rules]]\naction = \"remove_segment\"\nsegment = \"ZPD\"\nwen = { path = \"PID-8\", exists = true }",
+    )
+    .is_err());
+    assert!(Hl7Transform::from_json(
+        r#"{"rules": [{"action": "delete", "path": "PID-8", "when": null}]}"#,
+    )
+    .is_ok());
+
+    let transform = Hl7Transform::from_json(
+        r#"{"rules": [
+            {"action": "set", "path": "PID-5", "value": "CHANGED"},
+            {"action": "add_segment", "segment": "NTE|1", "after": "OBR"}
+        ]}"#,
+    )
+    .unwrap();
+    let mut message = Hl7MutableMessage::parse(ADT, MshNumbering::Legacy).unwrap();
+    let error = transform.apply(&mut message).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "HL7 transform rule 1: Missing required segment: OBR"
+    );
+    assert_eq!(message.get("PID-5-1").unwrap().as_deref(), Some("DOE"));
+}
@@ -1,4 +1,5 @@
-use crate::segments::{Hl7Field, Hl7Segment};
+use crate::escape::Hl7EscapeHandler;
+use crate::segments::{Hl7Field, Hl7Segment, MshNumbering};
 use crate::validation::Hl7Validator;
 use std::collections::BTreeMap;
 
@@ -13,11 +14,12 @@ fn simple_field(value: &str) -> Hl7Field {
     Hl7Field {
         value: value.to_string(),
         components: None,
+        subcomponents: None,
         repetitions: None,
     }
 }
 
-fn msh_fields(f1: &str, f8: &str, f12: &str) -> BTreeMap<usize, Hl7Field> {
+fn msh_fields(f1: &str, f8: &str, version: &str) -> BTreeMap<usize, Hl7Field> {
     let mut m: BTreeMap<usize, Hl7Field> = BTreeMap::new();
     m.insert(1, simple_field(f1));
     m.insert(2, simple_field("ADT1"));
@@ -29,8 +31,8 @@ fn msh_fields(f1: &str, f8: &str, f12: &str) -> BTreeMap<usize, Hl7Field> {
     m.insert(8, simple_field(f8));
     m.insert(9, simple_field("MSG00001"));
     m.insert(10, simple_field("T"));
-    m.insert(11, simple_field("2.5.1"));
-    m.insert(12, simple_field(f12));
+    m.insert(11, simple_field(version));
+    m.insert(12, simple_field("1"));
     m
 }
 
@@ -58,14 +60,14 @@ fn validation_missing_msh() {
 fn validation_msh_too_few_fields() {
     let validator = Hl7Validator::new();
     let mut fields = BTreeMap::new();
-    for i in 1..=11 {
+    for i in 1..=10 {
         fields.insert(i, simple_field("x"));
     }
     let seg = segment_with_fields("MSH", fields);
     let result = validator.validate_message(&[seg]);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert!(err.to_string().contains("12 fields"));
+    assert!(err.to_string().contains("11 fields"));
 }
 
 #[test]
@@ -104,7 +106,7 @@ fn validation_unsupported_version_strict() {
 fn validation_version_accept_non_strict() {
     let validator = Hl7Validator::new().with_strict_mode(false);
     let mut fields = msh_fields("^~\\&", "ADT^A01^ADT_A01", "2.5.1");
-    fields.insert(12, simple_field("99.99"));
+    fields.insert(11, simple_field("99.99"));
     let seg = segment_with_fields("MSH", fields);
     let pid = Hl7Segment::from_string("PID|1||x", None);
     let result = validator.validate_message(&[seg, pid]);
@@ -112,16 +114,15 @@ fn validation_version_accept_non_strict() {
 }
 
 #[test]
-fn validation_missing_field_12() {
+fn validation_missing_version_field() {
     let validator = Hl7Validator::new();
     let mut fields = msh_fields("^~\\&", "ADT^A01^ADT_A01", "2.5.1");
-    fields.remove(&12);
+    fields.remove(&11);
     let seg = segment_with_fields("MSH", fields);
     let result = validator.validate_message(&[seg]);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    let s = err.to_string();
-    assert!(s.contains("field 12") || s.contains("12 fields"));
+    assert!(err.to_string().contains("field 11"));
 }
 
 #[test]
@@ -194,15 +195,19 @@ fn validation_segment_name_wrong_length() {
 }
 
 #[test]
-fn validation_segment_name_non_alphabetic() {
+fn validation_segment_name_characters() {
     let validator = Hl7Validator::new().with_required_fields_validation(false);
     let fields = msh_fields("^~\\&", "ADT^A01^ADT_A01", "2.5.1");
     let msh = segment_with_fields("MSH", fields);
-    let bad = segment_with_fields("M12", BTreeMap::new());
-    let result = validator.validate_message(&[msh, bad]);
-    assert!(result.is_err());
-    let err = result.unwrap_err();
-    assert!(err.to_string().contains("3 alphabetic"));
+
+    let pv1 = segment_with_fields("PV1", BTreeMap::new());
+    assert!(validator.validate_message(&[msh.clone(), pv1]).is_ok());
+
+    for name in ["1AB", "Pv1", "P-1"] {
+        let bad = segment_with_fields(name, BTreeMap::new());
+        let err = validator.validate_message(&[msh.clone(), bad]).unwrap_err();
+        assert!(err.to_string().contains("starting with a letter"));
+    }
 }
 
 #[test]
@@ -227,6 +232,7 @@ fn validation_component_too_long() {
     let field_with_long_component = Hl7Field {
         value: "a^b".to_string(),
         components: Some(vec!["a".to_string(), long_comp]),
+        subcomponents: None,
         repetitions: None,
     };
     fields.insert(2, field_with_long_component);
@@ -246,3 +252,80 @@ fn validation_builder_with_strict_and_required() {
     assert!(!v.strict_mode);
     assert!(v.validate_required_fields);
 }
+
+fn standard_msh(segment_str: &str) -> Hl7Segment {
+    Hl7Segment::parse_with_numbering(
+        segment_str,
+        &Hl7EscapeHandler::default(),
+        false,
+        MshNumbering::Standard,
+    )
+}
+
+#[test]
+fn validation_standard_msh_numbering() {
+    let validator = Hl7Validator::new().with_msh_numbering(MshNumbering::Standard);
+    let msh = standard_msh(
+        "MSH|^~\\&|ADT1|HOSP|LAB|HOSP|20240101120000||ADT^A01^ADT_A01|MSG00001|T|2.5.1",
+    );
+    let pid = Hl7Segment::from_string("PID|1||x", None);
+    let result = validator.validate_message(&[msh, pid]);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn validation_standard_msh_numbering_checks_msh_12_version() {
+    let validator = Hl7Validator::new().with_msh_numbering(MshNumbering::Standard);
+    let msh = standard_msh(
+        "MSH|^~\\&|ADT1|HOSP|LAB|HOSP|20240101120000||ADT^A01^ADT_A01|MSG00001|T|99.99",
+    );
+    let pid = Hl7Segment::from_string("PID|1||x", None);
+    let result = validator.validate_message(&[msh, pid]);
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("99.99"));
+}
+
+#[test]
+fn validation_standard_msh_numbering_checks_msh_9_message_type() {
+    let validator = Hl7Validator::new().with_msh_numbering(MshNumbering::Standard);
+    let msh = standard_msh("MSH|^~\\&|ADT1|HOSP|LAB|HOSP|20240101120000||ADT^A01|MSG00001|T|2.5.1");
+    let pid = Hl7Segment::from_string("PID|1||x", None);
+    let result = validator.validate_message(&[msh, pid]);
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("field 9"));
+}
+
+#[test]
+fn validation_standard_msh_numbering_invalid_encoding_characters() {
+    let validator = Hl7Validator::new().with_msh_numbering(MshNumbering::Standard);
+    let msh = standard_msh(
+        "MSH|^~\\|ADT1|HOSP|LAB|HOSP|20240101120000||ADT^A01^ADT_A01|MSG00001|T|2.5.1",
+    );
+    let result = validator.validate_message(&[msh]);
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("4 characters"));
+}
+
+#[test]
+fn validation_legacy_msh_ending_at_version() {
+    let validator = Hl7Validator::new();
+    let msh = Hl7Segment::from_string(
+        "MSH|^~\\&|ADT1|HOSP|LAB|HOSP|20240101120000||ADT^A01^ADT_A01|1|P|2.5",
+        None,
+    );
+    let pid = Hl7Segment::from_string("PID|1||x", None);
+    assert!(validator.validate_message(&[msh, pid]).is_ok());
+}
+
+#[test]
+fn validation_legacy_checks_msh_12_version() {
+    let validator = Hl7Validator::new();
+    let msh = Hl7Segment::from_string(
+        "MSH|^~\\&|ADT1|HOSP|LAB|HOSP|20240101120000||ADT^A01^ADT_A01|1|P|9.9|1",
+        None,
+    );
+    let pid = Hl7Segment::from_string("PID|1||x", None);
+    let result = validator.validate_message(&[msh, pid]);
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("9.9"));
+}
@@ -0,0 +1,99 @@
+use crate::errors::Hl7Error;
+use crate::{hl7_to_xml, xml_to_hl7, xml_to_message, MshNumbering, ParseOptions};
+
+const ORU_R01: &str = "MSH|^~\\&|LAB|FAC|EHR|FAC|20240101||ORU^R01^ORU_R01|1|P|2.5.1\nPID|1||111^^^HOSP~222^^^SSA&1.2&ISO||DOE^JOHN^Q||19800101|M\nOBR|1|1||CBC^Complete blood count^LN\nOBX|1|CE|WBC||7.1^High \\H\\count\\N\\^L||||||F\nNTE|1||Fish \\F\\ Chips \\T\\ <peas> \\E\\ \\S\\";
+
+#[test]
+fn test_hl7_to_xml_names() {
+    let xml = hl7_to_xml(ORU_R01).unwrap();
+
+    assert!(xml.contains("<ORU_R01 xmlns=\"urn:hl7-org:v2xml\">"));
+    assert!(xml.contains("<ORU_R01.PATIENT_RESULT>"));
+    assert!(xml.contains("<ORU_R01.ORDER_OBSERVATION>"));
+    assert!(xml.contains("<MSH.1>|</MSH.1>"));
+    assert!(xml.contains("<MSH.2>^~\\&amp;</MSH.2>"));
+    assert!(
+        xml.contains("<MSH.9><MSG.1>ORU</MSG.1><MSG.2>R01</MSG.2><MSG.3>ORU_R01</MSG.3></MSH.9>")
+    );
+    assert!(xml.contains("<PID.3><CX.1>111</CX.1><CX.4><HD.1>HOSP</HD.1></CX.4></PID.3>"));
+    assert!(xml.contains(
+        "<PID.3><CX.1>222</CX.1><CX.4><HD.1>SSA</HD.1><HD.2>1.2</HD.2><HD.3>ISO</HD.3></CX.4></PID.3>"
+    ));
+    assert!(xml.contains(
+        "<PID.5><XPN.1><FN.1>DOE</FN.1></XPN.1><XPN.2>JOHN</XPN.2><XPN.3>Q</XPN.3></PID.5>"
+    ));
+    assert!(xml.contains("<PID.7><TS.1>19800101</TS.1></PID.7>"));
+    assert!(xml.contains("<OBX.5><CE.1>7.1</CE.1><CE.2>High <escape V=\"H\"/>count<escape V=\"N\"/></CE.2><CE.3>L</CE.3></OBX.5>"));
+    assert!(xml.contains("<NTE.3>Fish | Chips &amp; &lt;peas&gt; \\ ^</NTE.3>"));
+}
+
+#[test]
+fn test_xml_roundtrip() {
+    let xml = hl7_to_xml(ORU_R01).unwrap();
+    assert_eq!(xml_to_hl7(&xml).unwrap(), ORU_R01);
+
+    let message = xml_to_message(&xml, &ParseOptions::default()).unwrap();
+    assert_eq!(
+        message.segments[1].fields.get(&5).unwrap().value,
+        "DOE^JOHN^Q"
+    );
+    assert_eq!(
+        message.segments[4].fields.get(&3).unwrap().value,
+        "Fish | Chips & <peas> \\ ^"
+    );
+
+    let options = ParseOptions::new().with_msh_numbering(MshNumbering::Standard);
+    let message = xml_to_message(&xml, &options).unwrap();
+    assert_eq!(message.segments[0].fields.get(&1).unwrap().value, "|");
+}
+
+#[test]
+fn test_xml_roundtrip_unknown_structure_and_segments() {
+    let hl7_string = "MSH|^~\\&|A|B|C|D|20240101||ZZZ^Z01|1|P|2.5.1\nZPI|1|A^B&C~~D||\"\"";
+    let xml = hl7_to_xml(hl7_string).unwrap();
+
+    assert!(xml.contains("<ZZZ_Z01 xmlns"));
+    assert!(xml.contains("<ZPI.2><ZPI.2.1>A</ZPI.2.1><ZPI.2.2><ZPI.2.2.1>B</ZPI.2.2.1><ZPI.2.2.2>C</ZPI.2.2.2></ZPI.2.2></ZPI.2>"));
+    assert!(xml.contains("<ZPI.2/>"));
+    assert_eq!(xml_to_hl7(&xml).unwrap(), hl7_string);
+}
+
+#[test]
+fn test_xml_reader_accepts_partner_formatting() {
+    let xml = r#"<?xml version="1.0"?>
+<ADT_A01 xmlns="urn:hl7-org:v2xml">
+  <MSH>
+    <MSH.1>|</MSH.1>
+    <MSH.2>^~\&amp;</MSH.2>
+    <MSH.9>
+      <MSG.1>ADT</MSG.1>
+      <MSG.2>A01</MSG.2>
+    </MSH.9>
+  </MSH>
+  <PID>
+    <PID.5><XPN.1><FN.1>O&apos;Brien</FN.1></XPN.1><XPN.2><![CDATA[Mary^Ann]]></XPN.2></PID.5>
+    <PID.11><XAD.1><SAD.1>1 Main St</SAD.1></XAD.1><XAD.3>Town</XAD.3></PID.11>
+  </PID>
+</ADT_A01>"#;
+
+    assert_eq!(
+        xml_to_hl7(xml).unwrap(),
+        "MSH|^~\\&|||||||ADT^A01\nPID|||||O'Brien^Mary\\S\\Ann||||||1 Main St^^Town"
+    );
+}
+
+#[test]
+fn test_xml_reader_errors() {
+    assert!(matches!(
+        xml_to_hl7("<ADT_A01><MSH><MSH.1>|</MSH.1></ADT_A01>"),
+        Err(Hl7Error::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        xml_to_hl7("<ADT_A01/>"),
+        Err(Hl7Error::EmptyMessage)
+    ));
+    assert!(matches!(
+        xml_to_hl7("<ADT_A01><PID><PID.1>&bogus;</PID.1></PID></ADT_A01>"),
+        Err(Hl7Error::InvalidFormat(_))
+    ));
+}
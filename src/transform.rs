@@ -0,0 +1,400 @@
+use crate::errors::Hl7Error;
+use crate::mutable::Hl7MutableMessage;
+use crate::segments::MshNumbering;
+use crate::terser::Hl7Path;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TransformAction {
+    Set {
+        path: String,
+        value: String,
+    },
+    Copy {
+        from: String,
+        to: String,
+    },
+    Move {
+        from: String,
+        to: String,
+    },
+    Delete {
+        path: String,
+    },
+    Lookup {
+        path: String,
+        table: String,
+        #[serde(default)]
+        to: Option<String>,
+        #[serde(default)]
+        default: Option<String>,
+    },
+    AddSegment {
+        segment: String,
+        #[serde(default)]
+        after: Option<String>,
+        #[serde(default)]
+        before: Option<String>,
+    },
+    RemoveSegment {
+        segment: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleCondition {
+    pub path: String,
+    #[serde(default)]
+    pub equals: Option<String>,
+    #[serde(default)]
+    pub not_equals: Option<String>,
+    #[serde(default)]
+    pub exists: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "BTreeMap<String, serde_json::Value>")]
+pub struct TransformRule {
+    #[serde(flatten)]
+    pub action: TransformAction,
+    #[serde(default)]
+    pub when: Option<RuleCondition>,
+}
+
+impl TryFrom<BTreeMap<String, serde_json::Value>> for TransformRule {
+    type Error = serde_json::Error;
+
+    fn try_from(mut fields: BTreeMap<String, serde_json::Value>) -> Result<Self, Self::Error> {
+        let when = match fields.remove("when") {
+            Some(when) => serde_json::from_value(when)?,
+            None => None,
+        };
+        let action = serde_json::from_value(fields.into_iter().collect())?;
+        Ok(TransformRule { action, when })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", pyclass(skip_from_py_object))]
+pub struct Hl7Transform {
+    #[serde(default)]
+    pub lookups: BTreeMap<String, BTreeMap<String, String>>,
+    #[serde(default)]
+    pub rules: Vec<TransformRule>,
+}
+
+impl Hl7Transform {
+    pub fn from_json(json: &str) -> Result<Self, Hl7Error> {
+        let transform: Hl7Transform = serde_json::from_str(json)?;
+        transform.check()?;
+        Ok(transform)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, Hl7Error> {
+        let transform: Hl7Transform = toml::from_str(toml)
+            .map_err(|error| Hl7Error::ConfigurationError(error.to_string()))?;
+        transform.check()?;
+        Ok(transform)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Hl7Error> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&contents),
+            _ => Self::from_json(&contents),
+        }
+    }
+
+    pub fn check(&self) -> Result<(), Hl7Error> {
+        for (index, rule) in self.rules.iter().enumerate() {
+            self.check_rule(rule)
+                .map_err(|error| error.in_rule(index))?;
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, message: &mut Hl7MutableMessage) -> Result<(), Hl7Error> {
+        let mut transformed = message.clone();
+        for (index, rule) in self.rules.iter().enumerate() {
+            self.apply_rule(rule, &mut transformed)
+                .map_err(|error| error.in_rule(index))?;
+        }
+        *message = transformed;
+        Ok(())
+    }
+
+    pub fn apply_to_hl7(
+        &self,
+        hl7_string: &str,
+        msh_numbering: MshNumbering,
+    ) -> Result<String, Hl7Error> {
+        let mut message = Hl7MutableMessage::parse(hl7_string, msh_numbering)?;
+        self.apply(&mut message)?;
+        Ok(message.to_er7())
+    }
+
+    fn check_rule(&self, rule: &TransformRule) -> Result<(), Hl7Error> {
+        let mut paths: Vec<&str> = Vec::new();
+        match &rule.action {
+            TransformAction::Set { path, value } => {
+                paths.push(path);
+                paths.extend(placeholders(value)?);
+            }
+            TransformAction::Copy { from, to } | TransformAction::Move { from, to } => {
+                paths.push(from);
+                paths.push(to);
+            }
+            TransformAction::Delete { path } => paths.push(path),
+            TransformAction::Lookup {
+                path, table, to, ..
+            } => {
+                if !self.lookups.contains_key(table) {
+                    return Err(Hl7Error::ConfigurationError(format!(
+                        "lookup table \"{}\" is not defined",
+                        table
+                    )));
+                }
+                paths.push(path);
+                paths.extend(to.as_deref());
+            }
+            TransformAction::AddSegment { after, before, .. } => {
+                if after.is_some() && before.is_some() {
+                    return Err(Hl7Error::ConfigurationError(
+                        "add_segment takes either after or before, not both".to_string(),
+                    ));
+                }
+            }
+            TransformAction::RemoveSegment { .. } => {}
+        }
+        if let Some(condition) = &rule.when {
+            if condition.equals.is_none()
+                && condition.not_equals.is_none()
+                && condition.exists.is_none()
+            {
+                return Err(Hl7Error::ConfigurationError(
+                    "when takes at least one of equals, not_equals or exists".to_string(),
+                ));
+            }
+            paths.push(&condition.path);
+        }
+
+        for path in paths {
+            Hl7Path::parse(path)?;
+        }
+        Ok(())
+    }
+
+    fn apply_rule(
+        &self,
+        rule: &TransformRule,
+        message: &mut Hl7MutableMessage,
+    ) -> Result<(), Hl7Error> {
+        if let Some(condition) = &rule.when {
+            if !condition.matches(message)? {
+                return Ok(());
+            }
+        }
+
+        match &rule.action {
+            TransformAction::Set { path, value } => {
+                let value = render(value, message)?;
+                message.set(path, &value)
+            }
+            TransformAction::Copy { from, to } => {
+                if let Some(value) = message.get_encoded(from)? {
+                    message.set_encoded(to, &value)?;
+                }
+                Ok(())
+            }
+            TransformAction::Move { from, to } => {
+                if let Some(value) = message.get_encoded(from)? {
+                    message.set_encoded(to, &value)?;
+                    message.set_encoded(from, "")?;
+                }
+                Ok(())
+            }
+            TransformAction::Delete { path } => {
+                if message.get_encoded(path)?.is_some() {
+                    message.set_encoded(path, "")?;
+                }
+                Ok(())
+            }
+            TransformAction::Lookup {
+                path,
+                table,
+                to,
+                default,
+            } => {
+                let table = self.lookups.get(table).ok_or_else(|| {
+                    Hl7Error::ConfigurationError(format!(
+                        "lookup table \"{}\" is not defined",
+                        table
+                    ))
+                })?;
+                let Some(value) = message.get(path)? else {
+                    return Ok(());
+                };
+                match table.get(&value).or(default.as_ref()) {
+                    Some(mapped) => message.set(to.as_deref().unwrap_or(path), mapped),
+                    None => Ok(()),
+                }
+            }
+            TransformAction::AddSegment {
+                segment,
+                after,
+                before,
+            } => {
+                let names = message.segment_names();
+                let position = |anchor: &str, last: bool| {
+                    let mut positions = names
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, name)| **name == anchor);
+                    let position = if last {
+                        positions.next_back()
+                    } else {
+                        positions.next()
+                    };
+                    position.map(|(index, _)| index).ok_or_else(|| {
+                        Hl7Error::MissingRequiredSegment {
+                            segment: anchor.to_string(),
+                        }
+                    })
+                };
+                let index = match (after, before) {
+                    (Some(anchor), _) => position(anchor, true)? + 1,
+                    (None, Some(anchor)) => position(anchor, false)?,
+                    (None, None) => names.len(),
+                };
+                message.insert_segment(index, segment)
+            }
+            TransformAction::RemoveSegment { segment } => {
+                let indexes: Vec<usize> = message
+                    .segment_names()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, name)| *name == segment)
+                    .map(|(index, _)| index)
+                    .collect();
+                for index in indexes.into_iter().rev() {
+                    message.remove_segment(index)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl RuleCondition {
+    fn matches(&self, message: &Hl7MutableMessage) -> Result<bool, Hl7Error> {
+        let value = message.get(&self.path)?.filter(|value| !value.is_empty());
+
+        if let Some(exists) = self.exists {
+            if value.is_some() != exists {
+                return Ok(false);
+            }
+        }
+        if let Some(equals) = &self.equals {
+            if value.as_ref() != Some(equals) {
+                return Ok(false);
+            }
+        }
+        if let Some(not_equals) = &self.not_equals {
+            if value.as_ref() == Some(not_equals) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn placeholders(template: &str) -> Result<Vec<&str>, Hl7Error> {
+    let mut paths = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            rest = &tail[2..];
+            continue;
+        }
+        if tail.starts_with('}') {
+            return Err(Hl7Error::ConfigurationError(format!(
+                "unmatched }} in \"{}\"",
+                template
+            )));
+        }
+        let end = tail.find('}').ok_or_else(|| {
+            Hl7Error::ConfigurationError(format!("unclosed placeholder in \"{}\"", template))
+        })?;
+        paths.push(&tail[1..end]);
+        rest = &tail[end + 1..];
+    }
+    Ok(paths)
+}
+
+fn render(template: &str, message: &Hl7MutableMessage) -> Result<String, Hl7Error> {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        output.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            output.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let end = tail.find('}').ok_or_else(|| {
+            Hl7Error::ConfigurationError(format!("unclosed placeholder in \"{}\"", template))
+        })?;
+        output.push_str(&message.get(&tail[1..end])?.unwrap_or_default());
+        rest = &tail[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Hl7Transform {
+    #[new]
+    fn py_new(mapping: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(json) = mapping.extract::<String>() {
+            return Ok(Hl7Transform::from_json(&json)?);
+        }
+        let value = crate::py_json::py_to_value(mapping)?;
+        let transform: Hl7Transform = serde_json::from_value(value).map_err(Hl7Error::from)?;
+        transform.check()?;
+        Ok(transform)
+    }
+
+    #[classmethod]
+    #[pyo3(name = "from_toml")]
+    fn py_from_toml(_cls: &Bound<PyType>, toml: &str) -> PyResult<Self> {
+        Ok(Hl7Transform::from_toml(toml)?)
+    }
+
+    #[classmethod]
+    #[pyo3(name = "from_file")]
+    fn py_from_file(_cls: &Bound<PyType>, path: &str) -> PyResult<Self> {
+        Ok(Hl7Transform::from_file(path)?)
+    }
+
+    fn __len__(&self) -> usize {
+        self.rules.len()
+    }
+
+    #[pyo3(name = "apply", signature = (hl7_string, standard_msh_numbering=None))]
+    fn py_apply(&self, hl7_string: &str, standard_msh_numbering: Option<bool>) -> PyResult<String> {
+        let numbering = MshNumbering::from_flag(standard_msh_numbering.unwrap_or(false));
+        Ok(self.apply_to_hl7(hl7_string, numbering)?)
+    }
+}
@@ -1,10 +1,11 @@
 use crate::errors::Hl7Error;
-use crate::segments::{Hl7Field, Hl7Segment};
+use crate::segments::{Hl7Field, Hl7Segment, MshNumbering};
 
 #[derive(Debug, Clone)]
 pub struct Hl7Validator {
     pub strict_mode: bool,
     pub validate_required_fields: bool,
+    pub msh_numbering: MshNumbering,
 }
 
 impl Default for Hl7Validator {
@@ -12,6 +13,7 @@ impl Default for Hl7Validator {
         Self {
             strict_mode: true,
             validate_required_fields: true,
+            msh_numbering: MshNumbering::Legacy,
         }
     }
 }
@@ -31,6 +33,18 @@ impl Hl7Validator {
         self
     }
 
+    pub fn with_msh_numbering(mut self, numbering: MshNumbering) -> Self {
+        self.msh_numbering = numbering;
+        self
+    }
+
+    fn encoding_characters_field(&self) -> usize {
+        match self.msh_numbering {
+            MshNumbering::Legacy => 1,
+            MshNumbering::Standard => 2,
+        }
+    }
+
     pub fn validate_message(&self, segments: &[Hl7Segment]) -> Result<(), Hl7Error> {
         if segments.is_empty() {
             return Err(Hl7Error::ValidationError(
@@ -58,9 +72,10 @@ impl Hl7Validator {
             });
         }
 
-        if msh.fields.len() < 12 {
+        let minimum_fields = self.msh_numbering.version_field();
+        if msh.fields.len() < minimum_fields {
             return Err(Hl7Error::ValidationFailed {
-                details: "MSH segment must have at least 12 fields".to_string(),
+                details: format!("MSH segment must have at least {} fields", minimum_fields),
             });
         }
 
@@ -72,15 +87,32 @@ impl Hl7Validator {
     }
 
     fn validate_field_separators(&self, msh: &Hl7Segment) -> Result<(), Hl7Error> {
-        if let Some(field_1) = msh.fields.get(&1) {
-            if field_1.value.len() != 4 {
+        if self.msh_numbering == MshNumbering::Standard {
+            match msh.fields.get(&1) {
+                Some(field_1) if field_1.value.chars().count() == 1 => {}
+                Some(field_1) => {
+                    return Err(Hl7Error::InvalidFieldSeparators {
+                        separators: field_1.value.clone(),
+                    });
+                }
+                None => {
+                    return Err(Hl7Error::ValidationFailed {
+                        details: "Field separator (field 1) is required".to_string(),
+                    });
+                }
+            }
+        }
+
+        let index = self.encoding_characters_field();
+        if let Some(encoding_characters) = msh.fields.get(&index) {
+            if !(4..=5).contains(&encoding_characters.value.chars().count()) {
                 return Err(Hl7Error::InvalidFieldSeparators {
-                    separators: field_1.value.clone(),
+                    separators: encoding_characters.value.clone(),
                 });
             }
         } else {
             return Err(Hl7Error::ValidationFailed {
-                details: "Field separators (field 1) are required".to_string(),
+                details: format!("Field separators (field {}) are required", index),
             });
         }
 
@@ -88,8 +120,9 @@ impl Hl7Validator {
     }
 
     fn validate_hl7_version(&self, msh: &Hl7Segment) -> Result<(), Hl7Error> {
-        if let Some(field_12) = msh.fields.get(&12) {
-            let version = &field_12.value;
+        let index = self.msh_numbering.version_field();
+        if let Some(version_field) = msh.fields.get(&index) {
+            let version = &version_field.value;
             let supported_versions = [
                 "2.1", "2.2", "2.3", "2.4", "2.5", "2.5.1", "2.6", "2.7", "2.8", "2.9",
             ];
@@ -102,7 +135,7 @@ impl Hl7Validator {
             }
         } else {
             return Err(Hl7Error::ValidationFailed {
-                details: "HL7 version (field 12) is required".to_string(),
+                details: format!("HL7 version (field {}) is required", index),
             });
         }
 
@@ -110,20 +143,27 @@ impl Hl7Validator {
     }
 
     fn validate_message_type(&self, msh: &Hl7Segment) -> Result<(), Hl7Error> {
-        if let Some(field_8) = msh.fields.get(&8) {
-            let message_type = &field_8.value;
+        let index = self.msh_numbering.message_type_field();
+        if let Some(message_type_field) = msh.fields.get(&index) {
+            let message_type = &message_type_field.value;
 
             if self.strict_mode {
-                let parts: Vec<&str> = message_type.split('^').collect();
-                if parts.len() < 3 {
+                let component_count = match &message_type_field.components {
+                    Some(components) => components.len(),
+                    None => message_type.split('^').count(),
+                };
+                if component_count < 3 {
                     return Err(Hl7Error::ValidationFailed {
-                        details: "Message type (field 8) must have at least 3 components: event^structure^version".to_string(),
+                        details: format!(
+                            "Message type (field {}) must have at least 3 components: event^structure^version",
+                            index
+                        ),
                     });
                 }
             }
         } else {
             return Err(Hl7Error::ValidationFailed {
-                details: "Message type (field 8) is required".to_string(),
+                details: format!("Message type (field {}) is required", index),
             });
         }
 
@@ -164,11 +204,9 @@ impl Hl7Validator {
             });
         }
 
-        if !segment
-            .segment_name
-            .chars()
-            .all(|c| c.is_ascii_alphabetic())
-        {
+        let mut chars = segment.segment_name.chars();
+        let starts_with_letter = chars.next().is_some_and(|c| c.is_ascii_uppercase());
+        if !starts_with_letter || !chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
             return Err(Hl7Error::InvalidSegmentName {
                 name: segment.segment_name.clone(),
             });
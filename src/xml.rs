@@ -0,0 +1,483 @@
+use crate::conversion::{json_to_hl7, SerializeOptions};
+use crate::definitions::{component_data_types, field_data_type};
+use crate::errors::Hl7Error;
+use crate::escape::{create_default_escape_handler, Hl7EscapeHandler};
+use crate::message::{Hl7Message, ParseOptions};
+use crate::segments::{is_header_segment, Hl7Segment, MshNumbering};
+use crate::structure::{MessageStructure, StructureNode};
+use quick_xml::escape::{escape, resolve_predefined_entity};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::BTreeMap;
+
+pub const V2_XML_NAMESPACE: &str = "urn:hl7-org:v2xml";
+
+pub fn hl7_to_xml(hl7_string: &str) -> Result<String, Hl7Error> {
+    let options = ParseOptions::lossless().with_msh_numbering(MshNumbering::Standard);
+    let message = Hl7Message::parse(hl7_string, &options)?;
+
+    let root_name = root_element_name(&message);
+    let nodes = match MessageStructure::for_message(&message) {
+        Ok(structure) => match structure.layout(&message.segments) {
+            (nodes, _) if placed_segments(&nodes) == message.segments.len() => nodes,
+            _ => flat_layout(&message.segments),
+        },
+        Err(_) => flat_layout(&message.segments),
+    };
+
+    let mut writer = XmlWriter {
+        output: String::new(),
+        handler: &message.escape_handler,
+        segments: &message.segments,
+        root_name: &root_name,
+    };
+    writer
+        .output
+        .push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writer
+        .output
+        .push_str(&format!("<{} xmlns=\"{}\">\n", root_name, V2_XML_NAMESPACE));
+    for node in &nodes {
+        writer.write_node(node, 1);
+    }
+    writer.output.push_str(&format!("</{}>\n", root_name));
+
+    Ok(writer.output)
+}
+
+pub fn xml_to_hl7(xml: &str) -> Result<String, Hl7Error> {
+    let root = parse_document(xml)?;
+
+    let mut segments = Vec::new();
+    collect_segments(&root, &mut segments);
+    if segments.is_empty() {
+        return Err(Hl7Error::EmptyMessage);
+    }
+
+    let handler = segments
+        .iter()
+        .find(|segment| is_header_segment(&segment.name))
+        .and_then(|header| {
+            let field_separator = header_text(header, 1)?.chars().next()?;
+            Hl7EscapeHandler::from_encoding_characters(field_separator, &header_text(header, 2)?)
+                .ok()
+        })
+        .unwrap_or_else(create_default_escape_handler);
+
+    let json_data: Vec<_> = segments
+        .iter()
+        .map(|segment| segment_to_json(segment, &handler))
+        .collect();
+    let options = SerializeOptions::lossless().with_msh_numbering(MshNumbering::Standard);
+    Ok(json_to_hl7(&json_data, &options))
+}
+
+pub fn xml_to_message(xml: &str, options: &ParseOptions) -> Result<Hl7Message, Hl7Error> {
+    Hl7Message::parse(&xml_to_hl7(xml)?, options)
+}
+
+fn root_element_name(message: &Hl7Message) -> String {
+    let message_type = message
+        .segments
+        .iter()
+        .find(|segment| segment.segment_name == "MSH")
+        .and_then(|msh| msh.fields.get(&MshNumbering::Standard.message_type_field()))
+        .map(|field| {
+            field
+                .components
+                .clone()
+                .unwrap_or_else(|| vec![field.value.clone()])
+        })
+        .unwrap_or_default();
+    let component = |index: usize| message_type.get(index).map(String::as_str).unwrap_or("");
+
+    match (component(0), component(1), component(2)) {
+        (_, _, structure) if !structure.is_empty() => structure.to_string(),
+        (code, "", _) if !code.is_empty() => code.to_string(),
+        (code, event, _) if !code.is_empty() => format!("{}_{}", code, event),
+        _ => "HL7Message".to_string(),
+    }
+}
+
+fn placed_segments(nodes: &[StructureNode]) -> usize {
+    nodes.iter().map(StructureNode::segment_count).sum()
+}
+
+fn flat_layout(segments: &[Hl7Segment]) -> Vec<StructureNode> {
+    (0..segments.len()).map(StructureNode::Segment).collect()
+}
+
+struct XmlWriter<'a> {
+    output: String,
+    handler: &'a Hl7EscapeHandler,
+    segments: &'a [Hl7Segment],
+    root_name: &'a str,
+}
+
+impl XmlWriter<'_> {
+    fn indent(&mut self, depth: usize) {
+        self.output.push_str(&"  ".repeat(depth));
+    }
+
+    fn write_node(&mut self, node: &StructureNode, depth: usize) {
+        match node {
+            StructureNode::Segment(index) => self.write_segment(&self.segments[*index], depth),
+            StructureNode::Group { name, children } => {
+                let element = format!("{}.{}", self.root_name, name);
+                self.indent(depth);
+                self.output.push_str(&format!("<{}>\n", element));
+                for child in children {
+                    self.write_node(child, depth + 1);
+                }
+                self.indent(depth);
+                self.output.push_str(&format!("</{}>\n", element));
+            }
+        }
+    }
+
+    fn write_segment(&mut self, segment: &Hl7Segment, depth: usize) {
+        let name = &segment.segment_name;
+        self.indent(depth);
+        self.output.push_str(&format!("<{}>\n", name));
+
+        for (&index, field) in &segment.fields {
+            let element = format!("{}.{}", name, index);
+
+            if is_header_segment(name) && index <= 2 {
+                self.indent(depth + 1);
+                self.output.push_str(&format!(
+                    "<{0}>{1}</{0}>\n",
+                    element,
+                    escape(field.value.as_str())
+                ));
+                continue;
+            }
+
+            let repetitions = field.to_nested_json();
+            let is_empty = repetitions
+                .iter()
+                .flatten()
+                .flatten()
+                .all(|value| value.is_empty());
+            if is_empty {
+                continue;
+            }
+
+            let data_type = match field_data_type(name, index) {
+                Some("varies") => segment
+                    .fields
+                    .get(&2)
+                    .map(|value_type| value_type.value.as_str()),
+                data_type => data_type,
+            };
+
+            for repetition in &repetitions {
+                self.indent(depth + 1);
+                self.write_repetition(&element, repetition, data_type);
+                self.output.push('\n');
+            }
+        }
+
+        self.indent(depth);
+        self.output.push_str(&format!("</{}>\n", name));
+    }
+
+    fn write_repetition(
+        &mut self,
+        element: &str,
+        components: &[Vec<String>],
+        data_type: Option<&str>,
+    ) {
+        let component_types = data_type.and_then(component_data_types);
+        if components.iter().flatten().all(|value| value.is_empty()) {
+            self.output.push_str(&format!("<{}/>", element));
+            return;
+        }
+
+        self.output.push_str(&format!("<{}>", element));
+        if component_types.is_none() && components.len() == 1 && components[0].len() == 1 {
+            self.write_text(&components[0][0]);
+        } else {
+            for (index, subcomponents) in components.iter().enumerate() {
+                if subcomponents.iter().all(|value| value.is_empty()) {
+                    continue;
+                }
+                let name = match data_type.filter(|_| component_types.is_some()) {
+                    Some(data_type) => format!("{}.{}", data_type, index + 1),
+                    None => format!("{}.{}", element, index + 1),
+                };
+                let component_type = component_types.and_then(|types| types.get(index)).copied();
+                self.write_component(&name, subcomponents, component_type);
+            }
+        }
+        self.output.push_str(&format!("</{}>", element));
+    }
+
+    fn write_component(
+        &mut self,
+        element: &str,
+        subcomponents: &[String],
+        data_type: Option<&str>,
+    ) {
+        let is_composite = data_type.and_then(component_data_types).is_some();
+
+        self.output.push_str(&format!("<{}>", element));
+        if !is_composite && subcomponents.len() == 1 {
+            self.write_text(&subcomponents[0]);
+        } else {
+            for (index, value) in subcomponents.iter().enumerate() {
+                if value.is_empty() {
+                    continue;
+                }
+                let name = match data_type.filter(|_| is_composite) {
+                    Some(data_type) => format!("{}.{}", data_type, index + 1),
+                    None => format!("{}.{}", element, index + 1),
+                };
+                self.output.push_str(&format!("<{}>", name));
+                self.write_text(value);
+                self.output.push_str(&format!("</{}>", name));
+            }
+        }
+        self.output.push_str(&format!("</{}>", element));
+    }
+
+    fn write_text(&mut self, raw: &str) {
+        let escape_character = self.handler.get_escape_character();
+        let mut rest = raw;
+
+        while let Some(start) = rest.find(escape_character) {
+            self.output.push_str(&escape(&rest[..start]));
+            let after = &rest[start + escape_character.len_utf8()..];
+            let Some(end) = after.find(escape_character) else {
+                self.output.push_str(&escape(&rest[start..]));
+                return;
+            };
+
+            let sequence = &after[..end];
+            let delimiter = match sequence {
+                "F" => Some(self.handler.get_field_separator()),
+                "S" => Some(self.handler.get_component_separator()),
+                "R" => Some(self.handler.get_repetition_separator()),
+                "T" => Some(self.handler.get_subcomponent_separator()),
+                "E" => Some(escape_character),
+                _ => None,
+            };
+            match delimiter {
+                Some(delimiter) => self.output.push_str(&escape(delimiter.to_string())),
+                None => self
+                    .output
+                    .push_str(&format!("<escape V=\"{}\"/>", escape(sequence))),
+            }
+            rest = &after[end + escape_character.len_utf8()..];
+        }
+
+        self.output.push_str(&escape(rest));
+    }
+}
+
+enum XmlContent {
+    Text(String),
+    Escape(String),
+}
+
+struct XmlElement {
+    name: String,
+    children: Vec<XmlElement>,
+    content: Vec<XmlContent>,
+}
+
+impl XmlElement {
+    fn new(start: &BytesStart) -> Result<Self, Hl7Error> {
+        let name = String::from_utf8(start.local_name().as_ref().to_vec())
+            .map_err(|e| Hl7Error::InvalidFormat(format!("Invalid XML element name: {}", e)))?;
+        Ok(XmlElement {
+            name,
+            children: Vec::new(),
+            content: Vec::new(),
+        })
+    }
+
+    fn index(&self) -> Option<usize> {
+        self.name.rsplit_once('.')?.1.parse().ok()
+    }
+
+    fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|content| match content {
+                XmlContent::Text(text) => Some(text.as_str()),
+                XmlContent::Escape(_) => None,
+            })
+            .collect()
+    }
+
+    fn raw_value(&self, handler: &Hl7EscapeHandler) -> String {
+        let escape_character = handler.get_escape_character();
+        self.content
+            .iter()
+            .map(|content| match content {
+                XmlContent::Text(text) => handler.escape(text),
+                XmlContent::Escape(sequence) => {
+                    format!("{0}{1}{0}", escape_character, sequence)
+                }
+            })
+            .collect()
+    }
+}
+
+fn xml_error(error: impl std::fmt::Display) -> Hl7Error {
+    Hl7Error::InvalidFormat(format!("Invalid XML: {}", error))
+}
+
+fn parse_document(xml: &str) -> Result<XmlElement, Hl7Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<XmlElement> = Vec::new();
+    let mut root = None;
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(start) => stack.push(XmlElement::new(&start)?),
+            Event::Empty(start) if start.local_name().as_ref() == b"escape" => {
+                let sequence = start
+                    .try_get_attribute("V")
+                    .map_err(xml_error)?
+                    .ok_or_else(|| xml_error("escape element without V attribute"))?
+                    .unescape_value()
+                    .map_err(xml_error)?
+                    .into_owned();
+                if let Some(parent) = stack.last_mut() {
+                    parent.content.push(XmlContent::Escape(sequence));
+                }
+            }
+            Event::Empty(start) => {
+                let element = XmlElement::new(&start)?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => root = Some(element),
+                }
+            }
+            Event::End(_) => {
+                let element = stack.pop().ok_or_else(|| xml_error("unexpected end tag"))?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => root = Some(element),
+                }
+            }
+            Event::Text(text) => {
+                if let Some(parent) = stack.last_mut() {
+                    let text = text.decode().map_err(xml_error)?;
+                    parent.content.push(XmlContent::Text(text.into_owned()));
+                }
+            }
+            Event::CData(data) => {
+                if let Some(parent) = stack.last_mut() {
+                    let text = data.decode().map_err(xml_error)?;
+                    parent.content.push(XmlContent::Text(text.into_owned()));
+                }
+            }
+            Event::GeneralRef(reference) => {
+                let text = match reference.resolve_char_ref().map_err(xml_error)? {
+                    Some(ch) => ch.to_string(),
+                    None => {
+                        let name = reference.decode().map_err(xml_error)?;
+                        resolve_predefined_entity(&name)
+                            .ok_or_else(|| xml_error(format!("unknown entity &{};", name)))?
+                            .to_string()
+                    }
+                };
+                if let Some(parent) = stack.last_mut() {
+                    parent.content.push(XmlContent::Text(text));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err(xml_error("unclosed element"));
+    }
+    root.ok_or(Hl7Error::EmptyMessage)
+}
+
+fn is_segment_element(name: &str) -> bool {
+    name.len() == 3
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit())
+}
+
+fn collect_segments<'a>(element: &'a XmlElement, segments: &mut Vec<&'a XmlElement>) {
+    for child in &element.children {
+        if is_segment_element(&child.name) {
+            segments.push(child);
+        } else {
+            collect_segments(child, segments);
+        }
+    }
+}
+
+fn header_text(segment: &XmlElement, index: usize) -> Option<String> {
+    segment
+        .children
+        .iter()
+        .find(|field| field.index() == Some(index))
+        .map(XmlElement::text)
+}
+
+fn segment_to_json(segment: &XmlElement, handler: &Hl7EscapeHandler) -> BTreeMap<String, String> {
+    let mut json = BTreeMap::new();
+    json.insert("segment_name".to_string(), segment.name.clone());
+
+    let mut fields: BTreeMap<usize, Vec<&XmlElement>> = BTreeMap::new();
+    for field in &segment.children {
+        if let Some(index) = field.index() {
+            fields.entry(index).or_default().push(field);
+        }
+    }
+
+    for (index, repetitions) in fields {
+        if is_header_segment(&segment.name) && index <= 2 {
+            if let Some(field) = repetitions.first() {
+                json.insert(index.to_string(), field.text());
+            }
+            continue;
+        }
+
+        for (repetition_index, repetition) in repetitions.iter().enumerate() {
+            let base = if repetitions.len() > 1 {
+                format!("{}[{}]", index, repetition_index)
+            } else {
+                index.to_string()
+            };
+
+            if repetition.children.is_empty() {
+                json.insert(base, repetition.raw_value(handler));
+                continue;
+            }
+
+            for component in &repetition.children {
+                let Some(component_index) = component.index() else {
+                    continue;
+                };
+                let component_key = format!("{}.{}", base, component_index);
+
+                if component.children.is_empty() {
+                    json.insert(component_key, component.raw_value(handler));
+                    continue;
+                }
+
+                for subcomponent in &component.children {
+                    if let Some(subcomponent_index) = subcomponent.index() {
+                        json.insert(
+                            format!("{}.{}", component_key, subcomponent_index),
+                            subcomponent.raw_value(handler),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    json
+}